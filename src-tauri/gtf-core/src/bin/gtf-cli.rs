@@ -0,0 +1,492 @@
+//! Headless command-line interface for CI and batch processing.
+//!
+//! ```text
+//! gtf-cli convert INPUT OUTPUT [--from FORMAT] [--to FORMAT]
+//! gtf-cli validate [--strict] FILE...
+//! gtf-cli lint [--strict] [--fix] [--json] [--config .gtflint.json] FILE...
+//! gtf-cli render FILE -o OUTPUT.png (--text TEXT | --request REQUEST.json) [--scale N]
+//! gtf-cli info [--json] FILE
+//! gtf-cli diff [--json] [--png OUTPUT.png] [--scale N] OLD NEW
+//! ```
+//!
+//! Formats default to the file extension. The tool never starts the GUI, so
+//! it runs without a display server.
+//!
+//! Exit codes: 0 = success, 1 = check failed (validation problems, lint
+//! errors, or the documents differ for `diff`), 2 = usage or I/O error.
+
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+
+use gtf_core::gtf::lint::{self, LintConfig, Severity};
+use gtf_core::gtf::{self, GtfDocument};
+use gtf_core::rendering::departure_board;
+use gtf_core::rendering::diff_image;
+use gtf_core::rendering::{ColumnDef, RenderRequest, RenderRow};
+use gtf_core::{exporters, importers};
+
+const USAGE: &str = "\
+Usage:
+  gtf-cli convert INPUT OUTPUT [--from FORMAT] [--to FORMAT]
+  gtf-cli validate [--strict] FILE...
+  gtf-cli lint [--strict] [--fix] [--json] [--config .gtflint.json] FILE...
+  gtf-cli render FILE -o OUTPUT.png (--text TEXT | --request REQUEST.json) [--scale N]
+  gtf-cli info [--json] FILE
+  gtf-cli diff [--json] [--png OUTPUT.png] [--scale N] OLD NEW";
+
+/// Largest accepted `--scale`.
+const MAX_SCALE: u32 = 64;
+/// Largest image `render` writes, in pixels (256 MB of RGBA).
+const MAX_OUTPUT_PIXELS: u64 = 64 * 1024 * 1024;
+
+/// Result of a subcommand that ran without errors.
+enum Outcome {
+    Success,
+    CheckFailed,
+}
+
+/// Splits arguments into positional ones and `--option value` / `--flag` pairs.
+struct Args {
+    positional: Vec<String>,
+    options: Vec<(String, Option<String>)>,
+}
+
+impl Args {
+    /// `flags` lists options that take no value.
+    fn parse(args: &[String], flags: &[&str]) -> Result<Self, String> {
+        let mut positional = Vec::new();
+        let mut options = Vec::new();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if arg.starts_with('-') && arg.len() > 1 {
+                let value = if flags.contains(&arg.as_str()) {
+                    None
+                } else {
+                    Some(
+                        iter.next()
+                            .cloned()
+                            .ok_or_else(|| format!("Option {} needs a value", arg))?,
+                    )
+                };
+                options.push((arg.clone(), value));
+            } else {
+                positional.push(arg.clone());
+            }
+        }
+        Ok(Self {
+            positional,
+            options,
+        })
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.options.iter().any(|(n, _)| n == name)
+    }
+
+    fn value(&self, names: &[&str]) -> Option<&str> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(n, _)| names.contains(&n.as_str()))
+            .and_then(|(_, v)| v.as_deref())
+    }
+
+    fn scale(&self, default: u32) -> Result<u32, String> {
+        match self.value(&["--scale"]) {
+            Some(s) => s
+                .parse()
+                .ok()
+                .filter(|s| (1..=MAX_SCALE).contains(s))
+                .ok_or_else(|| format!("Invalid scale '{}' (1 to {})", s, MAX_SCALE)),
+            None => Ok(default),
+        }
+    }
+
+    fn check_known(&self, known: &[&str]) -> Result<(), String> {
+        match self
+            .options
+            .iter()
+            .find(|(n, _)| !known.contains(&n.as_str()))
+        {
+            Some((name, _)) => Err(format!("Unknown option {}", name)),
+            None => Ok(()),
+        }
+    }
+}
+
+fn format_of(path: &str) -> Result<String, String> {
+    Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .ok_or_else(|| format!("Cannot tell the format of '{}', use --from/--to", path))
+}
+
+fn read_gtf(path: &str) -> Result<GtfDocument, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|err| format!("Failed to read file '{}': {}", path, err))?;
+    gtf::parse_gtf_content(&content).map_err(|err| format!("{}: {}", path, err))
+}
+
+fn write_file(path: &str, bytes: &[u8]) -> Result<(), String> {
+    fs::write(path, bytes).map_err(|err| format!("Failed to write file '{}': {}", path, err))
+}
+
+fn to_json(value: &impl serde::Serialize) -> Result<String, String> {
+    serde_json::to_string_pretty(value).map_err(|err| format!("Failed to serialize: {}", err))
+}
+
+fn convert(args: &Args) -> Result<Outcome, String> {
+    args.check_known(&["--from", "--to"])?;
+    let [input, output] = args.positional.as_slice() else {
+        return Err("convert needs INPUT and OUTPUT".to_string());
+    };
+    let from = match args.value(&["--from"]) {
+        Some(f) => f.to_string(),
+        None => format_of(input)?,
+    };
+    let to = match args.value(&["--to"]) {
+        Some(t) => t.to_string(),
+        None => format_of(output)?,
+    };
+
+    let doc = importers::import_file(input, &from)?;
+    exporters::export_file(&doc, output, &to)?;
+    println!("{} -> {} ({} glyphs)", input, output, doc.glyphs.len());
+    Ok(Outcome::Success)
+}
+
+fn validate(args: &Args) -> Result<Outcome, String> {
+    args.check_known(&["--strict"])?;
+    if args.positional.is_empty() {
+        return Err("validate needs at least one FILE".to_string());
+    }
+    let strict = args.flag("--strict");
+
+    let mut failed = false;
+    for path in &args.positional {
+        let doc = match read_gtf(path) {
+            Ok(doc) => doc,
+            Err(err) => {
+                println!("error: {}", err);
+                failed = true;
+                continue;
+            }
+        };
+        let mut warnings = 0;
+        for glyph in &doc.glyphs {
+            for warning in glyph.validation_warnings.iter().flatten() {
+                println!("warning: {}: glyph '{}': {}", path, glyph.name, warning);
+                warnings += 1;
+            }
+        }
+        if warnings > 0 && strict {
+            failed = true;
+        }
+        println!(
+            "{}: {} glyphs, {} warnings",
+            path,
+            doc.glyphs.len(),
+            warnings
+        );
+    }
+    Ok(if failed {
+        Outcome::CheckFailed
+    } else {
+        Outcome::Success
+    })
+}
+
+/// Lints each file with `--config`, or the nearest `.gtflint.json`, or the
+/// default rules. Errors fail the check, warnings only with `--strict`.
+/// `--fix` writes the automatic fixes back before linting.
+fn lint(args: &Args) -> Result<Outcome, String> {
+    args.check_known(&["--strict", "--fix", "--json", "--config"])?;
+    if args.positional.is_empty() {
+        return Err("lint needs at least one FILE".to_string());
+    }
+    let explicit = args
+        .value(&["--config"])
+        .map(|path| LintConfig::load(Path::new(path)))
+        .transpose()?;
+    let fail_at = if args.flag("--strict") {
+        Severity::Warning
+    } else {
+        Severity::Error
+    };
+
+    let mut failed = false;
+    let mut reports = Vec::new();
+    for path in &args.positional {
+        let config = match &explicit {
+            Some(config) => config.clone(),
+            None => LintConfig::discover(Path::new(path))?
+                .map(|(_, config)| config)
+                .unwrap_or_default(),
+        };
+        let mut doc = read_gtf(path)?;
+        if args.flag("--fix") {
+            let fixed = lint::fix_document(&mut doc, &config, None);
+            if !fixed.is_empty() {
+                write_file(path, gtf::serialize_gtf_document(&doc)?.as_bytes())?;
+                eprintln!("{}: fixed {} finding(s)", path, fixed.len());
+            }
+        }
+
+        let findings = lint::lint_document(&doc, &config);
+        failed |= findings.iter().any(|f| f.severity >= fail_at);
+        if args.flag("--json") {
+            reports.push(serde_json::json!({ "file": path, "findings": findings }));
+        } else {
+            for finding in &findings {
+                println!("{}: {}", path, finding);
+            }
+        }
+    }
+    if args.flag("--json") {
+        println!("{}", to_json(&reports)?);
+    }
+    Ok(if failed {
+        Outcome::CheckFailed
+    } else {
+        Outcome::Success
+    })
+}
+
+fn render(args: &Args) -> Result<Outcome, String> {
+    args.check_known(&["-o", "--output", "--text", "--request", "--scale"])?;
+    let [path] = args.positional.as_slice() else {
+        return Err("render needs exactly one FILE".to_string());
+    };
+    let output = args
+        .value(&["-o", "--output"])
+        .ok_or("render needs -o OUTPUT.png")?;
+    let doc = read_gtf(path)?;
+
+    let mut request: RenderRequest = match (args.value(&["--request"]), args.value(&["--text"])) {
+        (Some(request_path), None) => {
+            let json = fs::read_to_string(request_path)
+                .map_err(|err| format!("Failed to read file '{}': {}", request_path, err))?;
+            serde_json::from_str(&json)
+                .map_err(|err| format!("Invalid render request '{}': {}", request_path, err))?
+        }
+        (None, Some(text)) => {
+            // Each line of the text becomes one row of a single full-width column
+            let lines: Vec<&str> = text.lines().collect();
+            let (width, height) = departure_board::measure_lines(&doc, &lines);
+            RenderRequest {
+                display_width: width,
+                display_height: height,
+                pixel_scale: 1,
+                show_grid: false,
+                show_header: false,
+                header_lines: Vec::new(),
+                show_footer: false,
+                footer_text: String::new(),
+                columns: vec![ColumnDef {
+                    label: String::new(),
+                    x: 1,
+                    width: width as i32 - 2,
+                    align: "left".to_string(),
+                    color: "#FFFFFF".to_string(),
+                }],
+                rows: lines
+                    .iter()
+                    .map(|line| RenderRow {
+                        cells: vec![line.to_string()],
+                    })
+                    .collect(),
+                gtf_data: None,
+            }
+        }
+        _ => return Err("render needs either --text or --request".to_string()),
+    };
+    request.pixel_scale = args.scale(request.pixel_scale.clamp(1, MAX_SCALE))?;
+    let scaled = |size: u32| size as u64 * request.pixel_scale as u64;
+    let (width, height) = (
+        scaled(request.display_width),
+        scaled(request.display_height),
+    );
+    if width * height > MAX_OUTPUT_PIXELS {
+        return Err(format!(
+            "Output of {}x{} pixels is too large, use a smaller --scale or display",
+            width, height
+        ));
+    }
+
+    let png = departure_board::render_board(&request, &doc)?;
+    write_file(output, &png)?;
+    println!("{}", output);
+    Ok(Outcome::Success)
+}
+
+#[derive(serde::Serialize)]
+struct FontInfo<'a> {
+    font_name: Option<&'a str>,
+    version: Option<&'a str>,
+    author: Option<&'a str>,
+    default_size: Option<String>,
+    default_palette_entries: usize,
+    glyph_count: usize,
+    mapped_glyphs: usize,
+    warning_count: usize,
+}
+
+fn info(args: &Args) -> Result<Outcome, String> {
+    args.check_known(&["--json"])?;
+    let [path] = args.positional.as_slice() else {
+        return Err("info needs exactly one FILE".to_string());
+    };
+    let doc = read_gtf(path)?;
+    let header = &doc.header;
+
+    let info = FontInfo {
+        font_name: header.font_name.as_deref(),
+        version: header.version.as_deref(),
+        author: header.author.as_deref(),
+        default_size: header
+            .default_size
+            .as_ref()
+            .map(|s| format!("{}x{}", s.width, s.height)),
+        default_palette_entries: header
+            .default_palette
+            .as_ref()
+            .map_or(0, |p| p.entries.len()),
+        glyph_count: doc.glyphs.len(),
+        mapped_glyphs: doc.glyphs.iter().filter(|g| g.unicode.is_some()).count(),
+        warning_count: doc
+            .glyphs
+            .iter()
+            .map(|g| g.validation_warnings.as_ref().map_or(0, |w| w.len()))
+            .sum(),
+    };
+
+    if args.flag("--json") {
+        println!("{}", to_json(&info)?);
+    } else {
+        let show = |v: Option<&str>| v.unwrap_or("-").to_string();
+        println!("Font:            {}", show(info.font_name));
+        println!("Version:         {}", show(info.version));
+        println!("Author:          {}", show(info.author));
+        println!("Default size:    {}", show(info.default_size.as_deref()));
+        println!("Default palette: {} entries", info.default_palette_entries);
+        println!(
+            "Glyphs:          {} ({} with UNICODE)",
+            info.glyph_count, info.mapped_glyphs
+        );
+        println!("Warnings:        {}", info.warning_count);
+    }
+    Ok(Outcome::Success)
+}
+
+fn diff(args: &Args) -> Result<Outcome, String> {
+    args.check_known(&["--json", "--png", "--scale"])?;
+    let [old_path, new_path] = args.positional.as_slice() else {
+        return Err("diff needs OLD and NEW".to_string());
+    };
+    let (old, new) = (read_gtf(old_path)?, read_gtf(new_path)?);
+    let diff = gtf::diff::diff_documents(&old, &new);
+
+    if args.flag("--json") {
+        println!("{}", to_json(&diff)?);
+    } else {
+        print!("{}", diff);
+    }
+    if let Some(png_path) = args.value(&["--png"]) {
+        if !diff.glyphs.is_empty() {
+            let png = diff_image::render_diff(&old, &new, &diff, args.scale(8)?)?;
+            write_file(png_path, &png)?;
+        }
+    }
+    Ok(if diff.is_empty() {
+        Outcome::Success
+    } else {
+        Outcome::CheckFailed
+    })
+}
+
+fn run(args: &[String]) -> Result<Outcome, String> {
+    let Some((command, rest)) = args.split_first() else {
+        return Err(USAGE.to_string());
+    };
+    match command.as_str() {
+        "convert" => convert(&Args::parse(rest, &[])?),
+        "validate" => validate(&Args::parse(rest, &["--strict"])?),
+        "lint" => lint(&Args::parse(rest, &["--strict", "--fix", "--json"])?),
+        "render" => render(&Args::parse(rest, &[])?),
+        "info" => info(&Args::parse(rest, &["--json"])?),
+        "diff" => diff(&Args::parse(rest, &["--json"])?),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(Outcome::Success)
+        }
+        other => Err(format!("Unknown command '{}'\n\n{}", other, USAGE)),
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(Outcome::Success) => ExitCode::SUCCESS,
+        Ok(Outcome::CheckFailed) => ExitCode::from(1),
+        Err(err) => {
+            eprintln!("gtf-cli: {}", err);
+            ExitCode::from(2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    fn parse(args: &[&str]) -> Args {
+        Args::parse(&strings(args), &["--json"]).unwrap()
+    }
+
+    fn error(args: &[&str]) -> String {
+        match run(&strings(args)) {
+            Ok(_) => panic!("{:?} should fail", args),
+            Err(err) => err,
+        }
+    }
+
+    #[test]
+    fn scale_is_limited() {
+        assert_eq!(parse(&[]).scale(8), Ok(8));
+        assert_eq!(parse(&["--scale", "3"]).scale(8), Ok(3));
+        assert_eq!(parse(&["--scale", "64"]).scale(8), Ok(64));
+        for bad in ["0", "-1", "x", "65", "100000"] {
+            assert!(parse(&["--scale", bad]).scale(8).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn options_are_checked() {
+        assert!(Args::parse(&strings(&["FILE", "--scale"]), &[]).is_err());
+        let args = parse(&["FILE", "--json", "--bogus", "1"]);
+        assert_eq!(args.positional, vec!["FILE"]);
+        assert!(args.flag("--json"));
+        assert_eq!(
+            args.check_known(&["--json"]),
+            Err("Unknown option --bogus".to_string())
+        );
+        assert!(error(&["render", "font.gtf", "--bogus", "1"]).contains("--bogus"));
+        assert!(error(&["frobnicate"]).starts_with("Unknown command"));
+    }
+
+    #[test]
+    fn missing_input_is_a_usage_error() {
+        assert_eq!(error(&[]), USAGE);
+        assert!(error(&["render", "-o", "out.png", "--text", "A"]).contains("one FILE"));
+        assert!(error(&["render", "font.gtf", "--text", "A"]).contains("-o"));
+        assert!(error(&["convert", "font.gtf"]).contains("INPUT and OUTPUT"));
+        assert!(error(&["validate"]).contains("at least one FILE"));
+        assert!(error(&["diff", "old.gtf"]).contains("OLD and NEW"));
+    }
+}
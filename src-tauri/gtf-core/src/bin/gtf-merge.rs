@@ -0,0 +1,61 @@
+//! Three-way merge of GTF files, usable as a git merge driver.
+//!
+//! ```text
+//! gtf-merge BASE OURS THEIRS [OUTPUT]
+//! ```
+//!
+//! The merged font is written to OUTPUT, or over OURS when OUTPUT is omitted
+//! (which is what git expects from a merge driver). Conflicts keep our value
+//! and are listed on stderr. Exit code: 0 = clean merge, 1 = conflicts,
+//! 2 = error.
+//!
+//! Setup (`.gitattributes` + git config):
+//!
+//! ```text
+//! *.gtf merge=gtf
+//! git config merge.gtf.name "GTF three-way merge"
+//! git config merge.gtf.driver "gtf-merge %O %A %B"
+//! ```
+
+use std::fs;
+use std::process::ExitCode;
+
+use gtf_core::gtf;
+
+fn read(path: &str) -> Result<gtf::GtfDocument, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|err| format!("Failed to read file '{}': {}", path, err))?;
+    gtf::parse_gtf_content(&content).map_err(|err| format!("{}: {}", path, err))
+}
+
+fn run(args: &[String]) -> Result<bool, String> {
+    let [base, ours, theirs, rest @ ..] = args else {
+        return Err("Usage: gtf-merge BASE OURS THEIRS [OUTPUT]".to_string());
+    };
+    let output = rest.first().unwrap_or(ours);
+
+    let result = gtf::merge::merge_documents(&read(base)?, &read(ours)?, &read(theirs)?);
+    for conflict in &result.conflicts {
+        eprintln!("CONFLICT {}", conflict);
+    }
+
+    let content = gtf::serialize_gtf_document(&result.document)?;
+    fs::write(output, content)
+        .map_err(|err| format!("Failed to write file '{}': {}", output, err))?;
+    Ok(result.is_clean())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => {
+            eprintln!("gtf-merge: merged with conflicts (our values were kept)");
+            ExitCode::from(1)
+        }
+        Err(err) => {
+            eprintln!("gtf-merge: {}", err);
+            ExitCode::from(2)
+        }
+    }
+}
@@ -36,8 +36,9 @@ impl FontExporter for BfntBinaryExporter
         // TODO: Implement BFNT binary serialization
         // Steps:
         //   1. Build binary header (magic bytes, version, glyph count)
-        //   2. Build glyph table (character codes, offsets, sizes)
-        //   3. Pack bitmap data into binary pixel arrays
+        //   2. Build glyph table (character codes, data offsets, sizes, plus the
+        //      glyph `OFFSET` and `Glyph::cell_size` as bearing and advance)
+        //   3. Pack the ink of each glyph (`bounds::ink_bitmap`) into binary pixel arrays
         //   4. Concatenate header + table + bitmap data
         Err("BFNT binary export not yet implemented".to_string())
     }
@@ -35,7 +35,8 @@ impl FontExporter for BmpImageExporter
     {
         // TODO: Implement BMP image generation
         // Steps:
-        //   1. For each glyph, create a pixel buffer (width * height * 4 bytes RGBA)
+        //   1. For each glyph, create a pixel buffer of its cell (`Glyph::cell_size`, 4 bytes RGBA)
+        //      and place trimmed bitmaps at `OFFSET` (`bounds::cell_bitmap`)
         //   2. Map palette characters to RGB colors
         //   3. Fill pixel buffer with colors from bitmap
         //   4. Write BMP file header + pixel data
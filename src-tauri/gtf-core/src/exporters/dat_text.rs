@@ -36,7 +36,8 @@ impl FontExporter for DatTextExporter
         // TODO: Implement DAT text serialization
         // Steps:
         //   1. Write DAT header (font name, version, global settings)
-        //   2. Write glyph definitions (character code, bitmap rows)
+        //   2. Write glyph definitions (character code, rows of `bounds::cell_bitmap`,
+        //      so trimmed glyphs keep their position in the cell)
         //   3. Convert palette-based bitmap to DAT pixel values
         Err("DAT text export not yet implemented".to_string())
     }
@@ -8,13 +8,23 @@
 //! - **DAT Text** (.dat) - VISE legacy text format (placeholder)
 //! - **BFNT Binary** (.bfnt) - Binary bitmap font format (placeholder)
 //! - **BMP Image** (.bmp) - Bitmap image export for individual glyphs (placeholder)
+//!
+//! Formats other than GTF are behind the `dat`, `bfnt` and `bmp` crate features.
+//! Trimmed glyphs (`OFFSET` / `CELL`, see `gtf::bounds`) must be exported in
+//! their cell, either as `cell_bitmap` or as `ink_bitmap` with placement metrics.
 
+#[cfg(feature = "dat")]
 mod dat_text;
+#[cfg(feature = "bfnt")]
 mod bfnt_binary;
+#[cfg(feature = "bmp")]
 mod bmp_image;
 
+#[cfg(feature = "dat")]
 pub use dat_text::DatTextExporter;
+#[cfg(feature = "bfnt")]
 pub use bfnt_binary::BfntBinaryExporter;
+#[cfg(feature = "bmp")]
 pub use bmp_image::BmpImageExporter;
 
 use crate::gtf::types::GtfDocument;
@@ -84,10 +94,13 @@ pub fn get_all_exporter_info() -> Vec<ExporterInfo>
             name: "GTF Text".to_string(),
             extensions: vec!["gtf".to_string()],
             mode: ExportMode::Text,
-            description: "Native GTF v2 text format (fully implemented)".to_string(),
+            description: "Native GTF v3 text format (fully implemented)".to_string(),
         },
+        #[cfg(feature = "dat")]
         DatTextExporter.info(),
+        #[cfg(feature = "bfnt")]
         BfntBinaryExporter.info(),
+        #[cfg(feature = "bmp")]
         BmpImageExporter.info(),
     ]
 }
@@ -104,8 +117,11 @@ pub fn export_file(document: &GtfDocument, path: &str, format: &str) -> Result<(
             std::fs::write(path, content)
                 .map_err(|e| format!("Failed to write file '{}': {}", path, e))
         }
+        #[cfg(feature = "dat")]
         "dat" => DatTextExporter.export_to_file(document, path),
+        #[cfg(feature = "bfnt")]
         "bfnt" => BfntBinaryExporter.export_to_file(document, path),
+        #[cfg(feature = "bmp")]
         "bmp" => BmpImageExporter.export_to_file(document, path),
         _ => Err(format!("Unknown export format: '{}'", format)),
     }
@@ -0,0 +1,273 @@
+//! Ink bounding boxes and trimming of blank borders.
+//!
+//! A pixel is ink when its color differs from the font background color
+//! (`palette::background_color`), so every character drawing the background
+//! counts as blank. Trimming crops a glyph to its ink
+//! box, adds the removed top/left border to the glyph `OFFSET` and records the
+//! original cell size as `CELL`, so the glyph renders exactly as before. The
+//! removed pixels no longer count towards the usage-based background
+//! detection, so a font is given a `BACKGROUND` role before its first trim
+//! (`background_role_for_trim`).
+//!
+//! Renderers and exporters place trimmed bitmaps with `cell_bitmap` (or pack
+//! `ink_bitmap` together with the offset and cell). Operations that rebuild
+//! bitmaps relative to the cell (scaling, styles, composition) first expand
+//! trimmed glyphs back to their cell with `untrim_glyph`; changing the font
+//! height only moves `OFFSET` and resizes `CELL`.
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+
+use super::color::Color;
+use super::palette::{background_char, background_color, default_background_char, pixel_color};
+use super::resize::DEFAULT_FILL;
+use super::types::{Glyph, GtfDocument, Offset, Size};
+
+/// Smallest rectangle of the bitmap containing every ink pixel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoundingBox {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlyphBounds {
+    pub name: String,
+    /// Bitmap size before trimming.
+    pub size: Size,
+    /// `None` for glyphs without ink (e.g. space).
+    pub ink: Option<BoundingBox>,
+    /// Blank pixels a trim would remove.
+    pub trimmable: u32,
+}
+
+/// Background character of `glyph` for the font `background` color.
+fn glyph_background(glyph: &Glyph, doc: &GtfDocument, background: Option<Color>) -> char {
+    background
+        .and_then(|background| background_char(glyph, doc, background))
+        .unwrap_or(DEFAULT_FILL)
+}
+
+/// Ink bounding box of `glyph`: pixels whose color is not the font
+/// `background` color. `None` when the bitmap has no ink.
+pub fn ink_bounds(
+    glyph: &Glyph,
+    doc: &GtfDocument,
+    background: Option<Color>,
+) -> Option<BoundingBox> {
+    let mut rows = (u32::MAX, 0);
+    let mut cols = (u32::MAX, 0);
+    for (r, row) in glyph.bitmap.iter().enumerate() {
+        for (c, ch) in row.chars().enumerate() {
+            if pixel_color(ch, glyph, doc) != background {
+                rows = (rows.0.min(r as u32), rows.1.max(r as u32));
+                cols = (cols.0.min(c as u32), cols.1.max(c as u32));
+            }
+        }
+    }
+    (rows.0 != u32::MAX).then(|| BoundingBox {
+        x: cols.0,
+        y: rows.0,
+        width: cols.1 - cols.0 + 1,
+        height: rows.1 - rows.0 + 1,
+    })
+}
+
+fn bitmap_size(glyph: &Glyph) -> Size {
+    Size {
+        width: glyph
+            .bitmap
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0) as u32,
+        height: glyph.bitmap.len() as u32,
+    }
+}
+
+/// Bounding boxes of all glyphs in document order.
+pub fn glyph_bounds(doc: &GtfDocument) -> Vec<GlyphBounds> {
+    let background = background_color(doc);
+    doc.glyphs
+        .iter()
+        .map(|glyph| {
+            let size = bitmap_size(glyph);
+            let ink = ink_bounds(glyph, doc, background);
+            let kept = ink.map_or(size.width * size.height, |b| b.width * b.height);
+            GlyphBounds {
+                name: glyph.name.clone(),
+                trimmable: size.width * size.height - kept,
+                size,
+                ink,
+            }
+        })
+        .collect()
+}
+
+/// Rows of `glyph` inside `bounds`; short rows are padded with `fill`.
+pub fn ink_bitmap(glyph: &Glyph, bounds: &BoundingBox, fill: char) -> Vec<String> {
+    glyph
+        .bitmap
+        .iter()
+        .skip(bounds.y as usize)
+        .take(bounds.height as usize)
+        .map(|row| {
+            let mut cropped: String = row
+                .chars()
+                .skip(bounds.x as usize)
+                .take(bounds.width as usize)
+                .collect();
+            let missing = bounds.width as usize - cropped.chars().count();
+            cropped.extend(std::iter::repeat_n(fill, missing));
+            cropped
+        })
+        .collect()
+}
+
+/// `BACKGROUND` role to record before trimming glyphs of `doc`, `None` when
+/// the font already has one (or has no colors at all).
+pub fn background_role_for_trim(doc: &GtfDocument) -> Option<char> {
+    let ch = default_background_char(doc)?;
+    (doc.header.roles.background != Some(ch)).then_some(ch)
+}
+
+/// Copy of `glyph` cropped to its ink with the removed border added to
+/// `offset` and the cell size kept in `cell`. `None` when there is nothing to trim or the glyph has no ink.
+/// `background` is the font's `background_color`.
+pub fn trim_glyph(glyph: &Glyph, doc: &GtfDocument, background: Option<Color>) -> Option<Glyph> {
+    let fill = glyph_background(glyph, doc, background);
+    let bounds = ink_bounds(glyph, doc, background)?;
+    let size = bitmap_size(glyph);
+    if bounds.width == size.width && bounds.height == size.height {
+        return None;
+    }
+    let offset = glyph.offset.unwrap_or_default();
+    let mut trimmed = glyph.clone();
+    trimmed.cell = Some(glyph.cell_size());
+    trimmed.bitmap = ink_bitmap(glyph, &bounds, fill);
+    trimmed.size = Some(Size {
+        width: bounds.width,
+        height: bounds.height,
+    });
+    trimmed.offset = Some(Offset {
+        x: offset.x + bounds.x as i32,
+        y: offset.y + bounds.y as i32,
+    });
+    Some(trimmed)
+}
+
+/// Bitmap of the whole glyph cell with the bitmap drawn at `OFFSET`. Pixels
+/// outside the cell are clipped and uncovered pixels are `fill`.
+pub fn cell_bitmap(glyph: &Glyph, fill: char) -> Vec<String> {
+    let cell = glyph.cell_size();
+    let offset = glyph.offset.unwrap_or_default();
+    let mut rows = vec![vec![fill; cell.width as usize]; cell.height as usize];
+    for (r, row) in glyph.bitmap.iter().enumerate() {
+        let y = offset.y + r as i32;
+        if y < 0 || y >= cell.height as i32 {
+            continue;
+        }
+        for (c, ch) in row.chars().enumerate() {
+            let x = offset.x + c as i32;
+            if x >= 0 && x < cell.width as i32 {
+                rows[y as usize][x as usize] = ch;
+            }
+        }
+    }
+    rows.into_iter().map(String::from_iter).collect()
+}
+
+/// `glyph` expanded back to its full cell (see `cell_bitmap`), borrowed when
+/// it has no `OFFSET` or `CELL`.
+pub fn untrim_glyph(glyph: &Glyph, fill: char) -> Cow<'_, Glyph> {
+    if glyph.offset.is_none() && glyph.cell.is_none() {
+        return Cow::Borrowed(glyph);
+    }
+    let mut untrimmed = glyph.clone();
+    untrimmed.bitmap = cell_bitmap(glyph, fill);
+    untrimmed.size = Some(glyph.cell_size());
+    untrimmed.offset = None;
+    untrimmed.cell = None;
+    Cow::Owned(untrimmed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gtf::test_util::{color, document, glyph, palette};
+
+    #[test]
+    fn trimmed_glyphs_keep_their_cell() {
+        let full = glyph("F", &["....", ".#..", ".##.", "....", "...."]);
+        let doc = document(vec![full.clone()]);
+        let trimmed = trim_glyph(&full, &doc, Some(color("#000000"))).unwrap();
+        assert_eq!(trimmed.bitmap, vec!["#.", "##"]);
+        assert_eq!(trimmed.offset, Some(Offset { x: 1, y: 1 }));
+        assert_eq!(
+            trimmed.cell,
+            Some(Size {
+                width: 4,
+                height: 5
+            })
+        );
+
+        assert_eq!(cell_bitmap(&trimmed, '.'), full.bitmap);
+        assert_eq!(untrim_glyph(&trimmed, '.').into_owned(), full);
+        assert!(matches!(untrim_glyph(&full, '.'), Cow::Borrowed(_)));
+        assert!(trim_glyph(&trimmed, &doc, Some(color("#000000"))).is_none());
+    }
+
+    #[test]
+    fn retrimming_keeps_the_original_cell() {
+        let mut trimmed = glyph("T", &["#..", "#.."]);
+        trimmed.offset = Some(Offset { x: 2, y: 1 });
+        trimmed.cell = Some(Size {
+            width: 8,
+            height: 8,
+        });
+        let doc = document(vec![trimmed.clone()]);
+        let retrimmed = trim_glyph(&trimmed, &doc, Some(color("#000000"))).unwrap();
+        assert_eq!(retrimmed.bitmap, vec!["#", "#"]);
+        assert_eq!(retrimmed.offset, trimmed.offset);
+        assert_eq!(retrimmed.cell, trimmed.cell);
+        assert_eq!(cell_bitmap(&retrimmed, '.'), cell_bitmap(&trimmed, '.'));
+    }
+
+    #[test]
+    fn blank_glyphs_are_not_trimmed() {
+        let space = glyph("space", &["...", "..."]);
+        let doc = document(vec![space.clone()]);
+        assert_eq!(ink_bounds(&space, &doc, Some(color("#000000"))), None);
+        assert!(trim_glyph(&space, &doc, Some(color("#000000"))).is_none());
+        assert_eq!(glyph_bounds(&doc)[0].trimmable, 0);
+    }
+
+    #[test]
+    fn every_background_colored_character_is_blank() {
+        let mut doc = document(vec![glyph("A", &["-..", ".#-", "..-"])]);
+        doc.header.default_palette = Some(palette(&[
+            ('.', "#000000"),
+            ('-', "#000000"),
+            ('#', "#FFFFFF"),
+        ]));
+        let background = background_color(&doc);
+        let glyph = &doc.glyphs[0];
+
+        assert_eq!(
+            ink_bounds(glyph, &doc, background),
+            Some(BoundingBox {
+                x: 1,
+                y: 1,
+                width: 1,
+                height: 1
+            })
+        );
+        let trimmed = trim_glyph(glyph, &doc, background).unwrap();
+        assert_eq!(trimmed.bitmap, vec!["#"]);
+        assert_eq!(trimmed.offset, Some(Offset { x: 1, y: 1 }));
+        assert_eq!(glyph_bounds(&doc)[0].trimmable, 8);
+    }
+}
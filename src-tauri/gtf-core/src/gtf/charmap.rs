@@ -0,0 +1,696 @@
+//! Character mapping of glyphs (`CHAR` / `UNICODE`) and glyph naming.
+//!
+//! Imported fonts often have only one of the two fields, or fields that
+//! disagree. `normalize_charmap` fills the missing half from the other one,
+//! writes `UNICODE` in canonical form (`U+0041`) and optionally renames
+//! glyphs after their codepoint. Disagreements are never guessed: they are
+//! reported as conflicts and left for manual resolution.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use super::types::{Glyph, GtfDocument, Palette, Size};
+
+/// `UNICODE` value for a character, e.g. `U+0041`.
+pub fn unicode_of(ch: char) -> String {
+    format!("U+{:04X}", ch as u32)
+}
+
+/// Empty glyph for `ch` that can be appended to `doc`: `DEFAULT_SIZE`
+/// (5x7 without one) filled with `fill`, no palette overrides and a name not
+/// yet used in `doc`. `fill` is normally `palette::default_background_char`,
+/// looked up once by callers adding many glyphs.
+pub fn blank_glyph_for_char(doc: &GtfDocument, ch: char, fill: char) -> Glyph {
+    let base_name = if ch.is_alphanumeric() {
+        ch.to_string()
+    } else {
+        "Glyph".to_string()
+    };
+    let taken = |name: &str| doc.glyphs.iter().any(|g| g.name == name);
+    let mut name = base_name.clone();
+    let mut counter = 1;
+    while taken(&name) {
+        name = format!("{}{}", base_name, counter);
+        counter += 1;
+    }
+
+    let size = doc.header.default_size.clone().unwrap_or(Size {
+        width: 5,
+        height: 7,
+    });
+    Glyph {
+        name,
+        unicode: Some(unicode_of(ch)),
+        char_repr: Some(ch),
+        bitmap: vec![fill.to_string().repeat(size.width as usize); size.height as usize],
+        size: Some(size),
+        offset: None,
+        cell: None,
+        palette: Some(Palette::default()),
+        validation_warnings: None,
+    }
+}
+
+/// How glyphs are named after their codepoint.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NamingConvention {
+    /// Names are left as they are.
+    #[default]
+    Keep,
+    /// `uni0041`, or `u1F600` outside the Basic Multilingual Plane.
+    Uni,
+    /// Adobe Glyph List names (`A`, `space`, `ecaron`), `Uni` for the rest.
+    Agl,
+}
+
+impl NamingConvention {
+    pub fn glyph_name(self, ch: char) -> Option<String> {
+        match self {
+            NamingConvention::Keep => None,
+            NamingConvention::Uni => Some(uni_name(ch)),
+            NamingConvention::Agl => Some(agl_name(ch).unwrap_or_else(|| uni_name(ch))),
+        }
+    }
+}
+
+fn uni_name(ch: char) -> String {
+    let code = ch as u32;
+    if code <= 0xFFFF {
+        format!("uni{:04X}", code)
+    } else {
+        format!("u{:X}", code)
+    }
+}
+
+/// Adobe Glyph List name for ASCII, Latin-1, Latin Extended-A and common punctuation.
+pub fn agl_name(ch: char) -> Option<String> {
+    if ch.is_ascii_alphabetic() {
+        return Some(ch.to_string());
+    }
+    let code = ch as u32;
+    AGL_NAMES
+        .binary_search_by_key(&code, |(c, _)| *c)
+        .ok()
+        .map(|i| AGL_NAMES[i].1.to_string())
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NormalizeOptions {
+    /// Set `UNICODE` of glyphs that only have `CHAR`.
+    pub fill_unicode: bool,
+    /// Set `CHAR` of glyphs that only have `UNICODE` (control characters are skipped).
+    pub fill_char: bool,
+    pub naming: NamingConvention,
+}
+
+impl Default for NormalizeOptions {
+    fn default() -> Self {
+        Self {
+            fill_unicode: true,
+            fill_char: true,
+            naming: NamingConvention::Keep,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GlyphRename {
+    pub from: String,
+    pub to: String,
+}
+
+/// A glyph that needs a manual decision; it was left unchanged.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CharmapConflict {
+    pub glyph: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct NormalizeReport {
+    /// Glyphs that got `UNICODE` from `CHAR`.
+    pub unicode_filled: Vec<String>,
+    /// Glyphs that got `CHAR` from `UNICODE`.
+    pub char_filled: Vec<String>,
+    /// Glyphs whose `UNICODE` was rewritten to canonical form (`U+41` -> `U+0041`).
+    pub unicode_reformatted: Vec<String>,
+    pub renamed: Vec<GlyphRename>,
+    pub conflicts: Vec<CharmapConflict>,
+}
+
+impl NormalizeReport {
+    pub fn changed(&self) -> bool {
+        !(self.unicode_filled.is_empty()
+            && self.char_filled.is_empty()
+            && self.unicode_reformatted.is_empty()
+            && self.renamed.is_empty())
+    }
+}
+
+/// Fills and canonicalizes `CHAR` / `UNICODE` and renames glyphs according
+/// to `options`. Glyphs with conflicting or invalid values are reported and
+/// not touched.
+pub fn normalize_charmap(doc: &mut GtfDocument, options: &NormalizeOptions) -> NormalizeReport {
+    let mut report = NormalizeReport::default();
+    let mut conflicted: HashSet<usize> = HashSet::new();
+
+    for (index, glyph) in doc.glyphs.iter_mut().enumerate() {
+        match (glyph.unicode.clone(), glyph.codepoint(), glyph.char_repr) {
+            (Some(unicode), None, _) => {
+                report.conflicts.push(CharmapConflict {
+                    glyph: glyph.name.clone(),
+                    message: format!("UNICODE '{}' is not a valid codepoint", unicode),
+                });
+                conflicted.insert(index);
+            }
+            (Some(_), Some(code), Some(ch)) if code != ch => {
+                report.conflicts.push(CharmapConflict {
+                    glyph: glyph.name.clone(),
+                    message: format!(
+                        "CHAR {:?} and UNICODE {} ({:?}) disagree",
+                        ch,
+                        unicode_of(code),
+                        code
+                    ),
+                });
+                conflicted.insert(index);
+            }
+            (Some(unicode), Some(code), char_repr) => {
+                if unicode != unicode_of(code) {
+                    glyph.unicode = Some(unicode_of(code));
+                    report.unicode_reformatted.push(glyph.name.clone());
+                }
+                if char_repr.is_none() && options.fill_char && !code.is_control() {
+                    glyph.char_repr = Some(code);
+                    report.char_filled.push(glyph.name.clone());
+                }
+            }
+            (None, _, Some(ch)) => {
+                if options.fill_unicode {
+                    glyph.unicode = Some(unicode_of(ch));
+                    report.unicode_filled.push(glyph.name.clone());
+                }
+            }
+            (None, _, None) => {}
+        }
+    }
+
+    if options.naming != NamingConvention::Keep {
+        rename_glyphs(doc, options.naming, &conflicted, &mut report);
+    }
+    report
+}
+
+fn rename_glyphs(
+    doc: &mut GtfDocument,
+    naming: NamingConvention,
+    conflicted: &HashSet<usize>,
+    report: &mut NormalizeReport,
+) {
+    let targets: Vec<Option<String>> = doc
+        .glyphs
+        .iter()
+        .enumerate()
+        .map(|(index, glyph)| {
+            if conflicted.contains(&index) {
+                return None;
+            }
+            mapped_char(glyph).and_then(|ch| naming.glyph_name(ch))
+        })
+        .collect();
+
+    // A target is rejected when a glyph keeping its name or an earlier glyph
+    // already has it. Rejected glyphs keep their name, which may in turn
+    // block another target, so repeat until nothing changes.
+    let mut rejected = vec![false; targets.len()];
+    loop {
+        let kept: HashSet<&str> = doc
+            .glyphs
+            .iter()
+            .zip(&targets)
+            .zip(&rejected)
+            .filter(|((_, target), rejected)| target.is_none() || **rejected)
+            .map(|((glyph, _), _)| glyph.name.as_str())
+            .collect();
+        let mut assigned: HashSet<&str> = HashSet::new();
+        let mut changed = false;
+        for (index, target) in targets.iter().enumerate() {
+            let Some(target) = target.as_deref() else {
+                continue;
+            };
+            if !rejected[index] && (kept.contains(target) || !assigned.insert(target)) {
+                rejected[index] = true;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    for ((glyph, target), rejected) in doc.glyphs.iter_mut().zip(targets).zip(rejected) {
+        let Some(target) = target else {
+            continue;
+        };
+        if rejected {
+            report.conflicts.push(CharmapConflict {
+                glyph: glyph.name.clone(),
+                message: format!("Cannot rename to '{}', the name is already used", target),
+            });
+        } else if glyph.name != target {
+            report.renamed.push(GlyphRename {
+                from: std::mem::replace(&mut glyph.name, target.clone()),
+                to: target,
+            });
+        }
+    }
+}
+
+fn mapped_char(glyph: &Glyph) -> Option<char> {
+    glyph.codepoint().or(glyph.char_repr)
+}
+
+/// Sorted by codepoint. ASCII letters are named after themselves and are not listed.
+const AGL_NAMES: &[(u32, &str)] = &[
+    (0x0020, "space"),
+    (0x0021, "exclam"),
+    (0x0022, "quotedbl"),
+    (0x0023, "numbersign"),
+    (0x0024, "dollar"),
+    (0x0025, "percent"),
+    (0x0026, "ampersand"),
+    (0x0027, "quotesingle"),
+    (0x0028, "parenleft"),
+    (0x0029, "parenright"),
+    (0x002A, "asterisk"),
+    (0x002B, "plus"),
+    (0x002C, "comma"),
+    (0x002D, "hyphen"),
+    (0x002E, "period"),
+    (0x002F, "slash"),
+    (0x0030, "zero"),
+    (0x0031, "one"),
+    (0x0032, "two"),
+    (0x0033, "three"),
+    (0x0034, "four"),
+    (0x0035, "five"),
+    (0x0036, "six"),
+    (0x0037, "seven"),
+    (0x0038, "eight"),
+    (0x0039, "nine"),
+    (0x003A, "colon"),
+    (0x003B, "semicolon"),
+    (0x003C, "less"),
+    (0x003D, "equal"),
+    (0x003E, "greater"),
+    (0x003F, "question"),
+    (0x0040, "at"),
+    (0x005B, "bracketleft"),
+    (0x005C, "backslash"),
+    (0x005D, "bracketright"),
+    (0x005E, "asciicircum"),
+    (0x005F, "underscore"),
+    (0x0060, "grave"),
+    (0x007B, "braceleft"),
+    (0x007C, "bar"),
+    (0x007D, "braceright"),
+    (0x007E, "asciitilde"),
+    (0x00A1, "exclamdown"),
+    (0x00A2, "cent"),
+    (0x00A3, "sterling"),
+    (0x00A4, "currency"),
+    (0x00A5, "yen"),
+    (0x00A6, "brokenbar"),
+    (0x00A7, "section"),
+    (0x00A8, "dieresis"),
+    (0x00A9, "copyright"),
+    (0x00AA, "ordfeminine"),
+    (0x00AB, "guillemotleft"),
+    (0x00AC, "logicalnot"),
+    (0x00AE, "registered"),
+    (0x00AF, "macron"),
+    (0x00B0, "degree"),
+    (0x00B1, "plusminus"),
+    (0x00B2, "twosuperior"),
+    (0x00B3, "threesuperior"),
+    (0x00B4, "acute"),
+    (0x00B5, "mu"),
+    (0x00B6, "paragraph"),
+    (0x00B7, "periodcentered"),
+    (0x00B8, "cedilla"),
+    (0x00B9, "onesuperior"),
+    (0x00BA, "ordmasculine"),
+    (0x00BB, "guillemotright"),
+    (0x00BC, "onequarter"),
+    (0x00BD, "onehalf"),
+    (0x00BE, "threequarters"),
+    (0x00BF, "questiondown"),
+    (0x00C0, "Agrave"),
+    (0x00C1, "Aacute"),
+    (0x00C2, "Acircumflex"),
+    (0x00C3, "Atilde"),
+    (0x00C4, "Adieresis"),
+    (0x00C5, "Aring"),
+    (0x00C6, "AE"),
+    (0x00C7, "Ccedilla"),
+    (0x00C8, "Egrave"),
+    (0x00C9, "Eacute"),
+    (0x00CA, "Ecircumflex"),
+    (0x00CB, "Edieresis"),
+    (0x00CC, "Igrave"),
+    (0x00CD, "Iacute"),
+    (0x00CE, "Icircumflex"),
+    (0x00CF, "Idieresis"),
+    (0x00D0, "Eth"),
+    (0x00D1, "Ntilde"),
+    (0x00D2, "Ograve"),
+    (0x00D3, "Oacute"),
+    (0x00D4, "Ocircumflex"),
+    (0x00D5, "Otilde"),
+    (0x00D6, "Odieresis"),
+    (0x00D7, "multiply"),
+    (0x00D8, "Oslash"),
+    (0x00D9, "Ugrave"),
+    (0x00DA, "Uacute"),
+    (0x00DB, "Ucircumflex"),
+    (0x00DC, "Udieresis"),
+    (0x00DD, "Yacute"),
+    (0x00DE, "Thorn"),
+    (0x00DF, "germandbls"),
+    (0x00E0, "agrave"),
+    (0x00E1, "aacute"),
+    (0x00E2, "acircumflex"),
+    (0x00E3, "atilde"),
+    (0x00E4, "adieresis"),
+    (0x00E5, "aring"),
+    (0x00E6, "ae"),
+    (0x00E7, "ccedilla"),
+    (0x00E8, "egrave"),
+    (0x00E9, "eacute"),
+    (0x00EA, "ecircumflex"),
+    (0x00EB, "edieresis"),
+    (0x00EC, "igrave"),
+    (0x00ED, "iacute"),
+    (0x00EE, "icircumflex"),
+    (0x00EF, "idieresis"),
+    (0x00F0, "eth"),
+    (0x00F1, "ntilde"),
+    (0x00F2, "ograve"),
+    (0x00F3, "oacute"),
+    (0x00F4, "ocircumflex"),
+    (0x00F5, "otilde"),
+    (0x00F6, "odieresis"),
+    (0x00F7, "divide"),
+    (0x00F8, "oslash"),
+    (0x00F9, "ugrave"),
+    (0x00FA, "uacute"),
+    (0x00FB, "ucircumflex"),
+    (0x00FC, "udieresis"),
+    (0x00FD, "yacute"),
+    (0x00FE, "thorn"),
+    (0x00FF, "ydieresis"),
+    (0x0100, "Amacron"),
+    (0x0101, "amacron"),
+    (0x0102, "Abreve"),
+    (0x0103, "abreve"),
+    (0x0104, "Aogonek"),
+    (0x0105, "aogonek"),
+    (0x0106, "Cacute"),
+    (0x0107, "cacute"),
+    (0x0108, "Ccircumflex"),
+    (0x0109, "ccircumflex"),
+    (0x010A, "Cdotaccent"),
+    (0x010B, "cdotaccent"),
+    (0x010C, "Ccaron"),
+    (0x010D, "ccaron"),
+    (0x010E, "Dcaron"),
+    (0x010F, "dcaron"),
+    (0x0110, "Dcroat"),
+    (0x0111, "dcroat"),
+    (0x0112, "Emacron"),
+    (0x0113, "emacron"),
+    (0x0114, "Ebreve"),
+    (0x0115, "ebreve"),
+    (0x0116, "Edotaccent"),
+    (0x0117, "edotaccent"),
+    (0x0118, "Eogonek"),
+    (0x0119, "eogonek"),
+    (0x011A, "Ecaron"),
+    (0x011B, "ecaron"),
+    (0x011C, "Gcircumflex"),
+    (0x011D, "gcircumflex"),
+    (0x011E, "Gbreve"),
+    (0x011F, "gbreve"),
+    (0x0120, "Gdotaccent"),
+    (0x0121, "gdotaccent"),
+    (0x0122, "Gcommaaccent"),
+    (0x0123, "gcommaaccent"),
+    (0x0124, "Hcircumflex"),
+    (0x0125, "hcircumflex"),
+    (0x0126, "Hbar"),
+    (0x0127, "hbar"),
+    (0x0128, "Itilde"),
+    (0x0129, "itilde"),
+    (0x012A, "Imacron"),
+    (0x012B, "imacron"),
+    (0x012C, "Ibreve"),
+    (0x012D, "ibreve"),
+    (0x012E, "Iogonek"),
+    (0x012F, "iogonek"),
+    (0x0130, "Idotaccent"),
+    (0x0131, "dotlessi"),
+    (0x0132, "IJ"),
+    (0x0133, "ij"),
+    (0x0134, "Jcircumflex"),
+    (0x0135, "jcircumflex"),
+    (0x0136, "Kcommaaccent"),
+    (0x0137, "kcommaaccent"),
+    (0x0138, "kgreenlandic"),
+    (0x0139, "Lacute"),
+    (0x013A, "lacute"),
+    (0x013B, "Lcommaaccent"),
+    (0x013C, "lcommaaccent"),
+    (0x013D, "Lcaron"),
+    (0x013E, "lcaron"),
+    (0x013F, "Ldot"),
+    (0x0140, "ldot"),
+    (0x0141, "Lslash"),
+    (0x0142, "lslash"),
+    (0x0143, "Nacute"),
+    (0x0144, "nacute"),
+    (0x0145, "Ncommaaccent"),
+    (0x0146, "ncommaaccent"),
+    (0x0147, "Ncaron"),
+    (0x0148, "ncaron"),
+    (0x0149, "napostrophe"),
+    (0x014A, "Eng"),
+    (0x014B, "eng"),
+    (0x014C, "Omacron"),
+    (0x014D, "omacron"),
+    (0x014E, "Obreve"),
+    (0x014F, "obreve"),
+    (0x0150, "Ohungarumlaut"),
+    (0x0151, "ohungarumlaut"),
+    (0x0152, "OE"),
+    (0x0153, "oe"),
+    (0x0154, "Racute"),
+    (0x0155, "racute"),
+    (0x0156, "Rcommaaccent"),
+    (0x0157, "rcommaaccent"),
+    (0x0158, "Rcaron"),
+    (0x0159, "rcaron"),
+    (0x015A, "Sacute"),
+    (0x015B, "sacute"),
+    (0x015C, "Scircumflex"),
+    (0x015D, "scircumflex"),
+    (0x015E, "Scedilla"),
+    (0x015F, "scedilla"),
+    (0x0160, "Scaron"),
+    (0x0161, "scaron"),
+    (0x0162, "Tcommaaccent"),
+    (0x0163, "tcommaaccent"),
+    (0x0164, "Tcaron"),
+    (0x0165, "tcaron"),
+    (0x0166, "Tbar"),
+    (0x0167, "tbar"),
+    (0x0168, "Utilde"),
+    (0x0169, "utilde"),
+    (0x016A, "Umacron"),
+    (0x016B, "umacron"),
+    (0x016C, "Ubreve"),
+    (0x016D, "ubreve"),
+    (0x016E, "Uring"),
+    (0x016F, "uring"),
+    (0x0170, "Uhungarumlaut"),
+    (0x0171, "uhungarumlaut"),
+    (0x0172, "Uogonek"),
+    (0x0173, "uogonek"),
+    (0x0174, "Wcircumflex"),
+    (0x0175, "wcircumflex"),
+    (0x0176, "Ycircumflex"),
+    (0x0177, "ycircumflex"),
+    (0x0178, "Ydieresis"),
+    (0x0179, "Zacute"),
+    (0x017A, "zacute"),
+    (0x017B, "Zdotaccent"),
+    (0x017C, "zdotaccent"),
+    (0x017D, "Zcaron"),
+    (0x017E, "zcaron"),
+    (0x017F, "longs"),
+    (0x02C6, "circumflex"),
+    (0x02C7, "caron"),
+    (0x02D8, "breve"),
+    (0x02D9, "dotaccent"),
+    (0x02DA, "ring"),
+    (0x02DB, "ogonek"),
+    (0x02DC, "tilde"),
+    (0x02DD, "hungarumlaut"),
+    (0x2013, "endash"),
+    (0x2014, "emdash"),
+    (0x2018, "quoteleft"),
+    (0x2019, "quoteright"),
+    (0x201A, "quotesinglbase"),
+    (0x201C, "quotedblleft"),
+    (0x201D, "quotedblright"),
+    (0x201E, "quotedblbase"),
+    (0x2020, "dagger"),
+    (0x2021, "daggerdbl"),
+    (0x2022, "bullet"),
+    (0x2026, "ellipsis"),
+    (0x2030, "perthousand"),
+    (0x2039, "guilsinglleft"),
+    (0x203A, "guilsinglright"),
+    (0x20AC, "Euro"),
+    (0x2122, "trademark"),
+    (0x2190, "arrowleft"),
+    (0x2191, "arrowup"),
+    (0x2192, "arrowright"),
+    (0x2193, "arrowdown"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gtf::palette::default_background_char;
+    use crate::gtf::test_util::{document, glyph, palette};
+
+    fn mapped(name: &str, unicode: Option<&str>, char_repr: Option<char>) -> Glyph {
+        Glyph {
+            unicode: unicode.map(str::to_string),
+            char_repr,
+            ..glyph(name, &["#"])
+        }
+    }
+
+    #[test]
+    fn fills_and_canonicalizes_the_missing_half() {
+        let mut doc = document(vec![
+            mapped("a", None, Some('a')),
+            mapped("b", Some("U+62"), None),
+            mapped("nul", Some("U+0000"), None),
+        ]);
+        let report = normalize_charmap(&mut doc, &NormalizeOptions::default());
+
+        assert_eq!(report.unicode_filled, vec!["a"]);
+        assert_eq!(report.char_filled, vec!["b"]);
+        assert_eq!(report.unicode_reformatted, vec!["b"]);
+        assert!(report.conflicts.is_empty());
+        assert_eq!(doc.glyphs[0].unicode.as_deref(), Some("U+0061"));
+        assert_eq!(doc.glyphs[1].unicode.as_deref(), Some("U+0062"));
+        assert_eq!(doc.glyphs[1].char_repr, Some('b'));
+        // Control characters get no CHAR
+        assert_eq!(doc.glyphs[2].char_repr, None);
+    }
+
+    #[test]
+    fn conflicts_are_reported_and_left_alone() {
+        let mut doc = document(vec![
+            mapped("x", Some("U+0041"), Some('B')),
+            mapped("y", Some("bogus"), None),
+        ]);
+        let before = doc.clone();
+        let options = NormalizeOptions {
+            naming: NamingConvention::Agl,
+            ..Default::default()
+        };
+        let report = normalize_charmap(&mut doc, &options);
+
+        assert_eq!(doc, before);
+        assert!(!report.changed());
+        let glyphs: Vec<&str> = report.conflicts.iter().map(|c| c.glyph.as_str()).collect();
+        assert_eq!(glyphs, vec!["x", "y"]);
+    }
+
+    #[test]
+    fn renames_after_the_codepoint_unless_the_name_is_taken() {
+        let mut doc = document(vec![
+            mapped("first", Some("U+0020"), None),
+            mapped("second", Some("U+00E9"), None),
+            mapped("third", Some("U+1F600"), None),
+            // Keeps its name, which blocks the glyph for 'A'
+            mapped("A", None, None),
+            mapped("capital", Some("U+0041"), None),
+        ]);
+        let options = NormalizeOptions {
+            naming: NamingConvention::Agl,
+            ..Default::default()
+        };
+        let report = normalize_charmap(&mut doc, &options);
+
+        let names: Vec<&str> = doc.glyphs.iter().map(|g| g.name.as_str()).collect();
+        assert_eq!(names, vec!["space", "eacute", "u1F600", "A", "capital"]);
+        assert_eq!(report.renamed.len(), 3);
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(report.conflicts[0].glyph, "capital");
+    }
+
+    #[test]
+    fn naming_conventions() {
+        assert_eq!(NamingConvention::Keep.glyph_name('A'), None);
+        assert_eq!(NamingConvention::Uni.glyph_name('A').unwrap(), "uni0041");
+        assert_eq!(NamingConvention::Agl.glyph_name('ř').unwrap(), "rcaron");
+        assert_eq!(NamingConvention::Agl.glyph_name('Ж').unwrap(), "uni0416");
+        assert!(AGL_NAMES.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn blank_glyph_gets_an_unused_name_and_the_default_size() {
+        let doc = document(vec![glyph("A", &["#.#", "..."]), glyph("A1", &["#"])]);
+        let blank = blank_glyph_for_char(&doc, 'A', '.');
+
+        assert_eq!(blank.name, "A2");
+        assert_eq!(blank.unicode.as_deref(), Some("U+0041"));
+        assert_eq!(
+            blank.size,
+            Some(Size {
+                width: 3,
+                height: 2
+            })
+        );
+        assert_eq!(blank.bitmap, vec!["...", "..."]);
+        assert_eq!(blank_glyph_for_char(&doc, '!', '.').name, "Glyph");
+    }
+
+    #[test]
+    fn blank_glyph_is_filled_with_the_font_background() {
+        let mut doc = document(vec![glyph("A", &["-#-", "-#-"])]);
+        doc.header.default_palette = Some(palette(&[
+            ('.', "#FFFFFF"),
+            ('-', "#000000"),
+            ('#', "#FFFFFF"),
+        ]));
+        let fill = default_background_char(&doc).unwrap();
+        assert_eq!(fill, '-');
+        assert_eq!(
+            blank_glyph_for_char(&doc, 'B', fill).bitmap,
+            vec!["---", "---"]
+        );
+
+        // An explicit role wins over pixel usage
+        doc.header.roles.background = Some('.');
+        assert_eq!(default_background_char(&doc), Some('.'));
+    }
+}
@@ -0,0 +1,295 @@
+//! Character sets and how well a font covers them.
+//!
+//! Built-in sets cover the usual 8-bit encodings (ISO-8859-x, Windows-125x),
+//! alphabets of European languages, Cyrillic, Greek and box drawing. Encodings
+//! and alphabets include printable ASCII, so a set answers "can this font
+//! render text in X" on its own. Users can define further sets as plain
+//! strings of characters.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use super::charmap::blank_glyph_for_char;
+use super::palette::default_background_char;
+use super::resize::DEFAULT_FILL;
+use super::types::GtfDocument;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CharacterSet {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub category: String,
+    /// Characters in display order; duplicates are ignored.
+    pub chars: String,
+}
+
+impl CharacterSet {
+    /// Characters of the set without duplicates, in order.
+    pub fn chars(&self) -> Vec<char> {
+        let mut seen = HashSet::new();
+        self.chars.chars().filter(|c| seen.insert(*c)).collect()
+    }
+}
+
+/// Printable ASCII (U+0020..U+007E), letters and digits first.
+const ASCII: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789 !\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+
+struct Builtin {
+    id: &'static str,
+    name: &'static str,
+    category: &'static str,
+    /// Whether printable ASCII is part of the set.
+    ascii: bool,
+    chars: &'static str,
+}
+
+const fn encoding(id: &'static str, name: &'static str, upper_half: &'static str) -> Builtin {
+    Builtin {
+        id,
+        name,
+        category: "Encoding",
+        ascii: true,
+        chars: upper_half,
+    }
+}
+
+const fn alphabet(id: &'static str, name: &'static str, letters: &'static str) -> Builtin {
+    Builtin {
+        id,
+        name,
+        category: "Alphabet",
+        ascii: true,
+        chars: letters,
+    }
+}
+
+const fn script(
+    id: &'static str,
+    name: &'static str,
+    category: &'static str,
+    chars: &'static str,
+) -> Builtin {
+    Builtin {
+        id,
+        name,
+        category,
+        ascii: false,
+        chars,
+    }
+}
+
+const BUILTIN: &[Builtin] = &[
+    Builtin {
+        id: "ascii",
+        name: "Basic Latin + Digits",
+        category: "Alphabet",
+        ascii: true,
+        chars: "",
+    },
+    alphabet("czech", "Czech", "áčďéěíňóřšťúůýžÁČĎÉĚÍŇÓŘŠŤÚŮÝŽ"),
+    alphabet("slovak", "Slovak", "áäčďéíĺľňóôŕšťúýžÁÄČĎÉÍĹĽŇÓÔŔŠŤÚÝŽ"),
+    alphabet("polish", "Polish", "ąćęłńóśźżĄĆĘŁŃÓŚŹŻ"),
+    alphabet("german", "German", "äöüßÄÖÜẞ"),
+    alphabet(
+        "nordic",
+        "Nordic (Danish, Norwegian, Swedish, Finnish, Icelandic)",
+        "åäæöøáðéíóúýþÅÄÆÖØÁÐÉÍÓÚÝÞ",
+    ),
+    alphabet("romanian", "Romanian", "ăâîșțĂÂÎȘȚ"),
+    alphabet("hungarian", "Hungarian", "áéíóöőúüűÁÉÍÓÖŐÚÜŰ"),
+    alphabet("estonian", "Estonian", "äõöüšžÄÕÖÜŠŽ"),
+    encoding("iso-8859-1", "ISO-8859-1 (Western European)", "\u{00A0}¡¢£¤¥¦§¨©ª«¬\u{00AD}®¯°±²³´µ¶·¸¹º»¼½¾¿ÀÁÂÃÄÅÆÇÈÉÊËÌÍÎÏÐÑÒÓÔÕÖ×ØÙÚÛÜÝÞßàáâãäåæçèéêëìíîïðñòóôõö÷øùúûüýþÿ"),
+    encoding("iso-8859-2", "ISO-8859-2 (Central European)", "\u{00A0}Ą˘Ł¤ĽŚ§¨ŠŞŤŹ\u{00AD}ŽŻ°ą˛ł´ľśˇ¸šşťź˝žżŔÁÂĂÄĹĆÇČÉĘËĚÍÎĎĐŃŇÓÔŐÖ×ŘŮÚŰÜÝŢßŕáâăäĺćçčéęëěíîďđńňóôőö÷řůúűüýţ˙"),
+    encoding("iso-8859-3", "ISO-8859-3 (South European)", "\u{00A0}Ħ˘£¤Ĥ§¨İŞĞĴ\u{00AD}Ż°ħ²³´µĥ·¸ışğĵ½żÀÁÂÄĊĈÇÈÉÊËÌÍÎÏÑÒÓÔĠÖ×ĜÙÚÛÜŬŜßàáâäċĉçèéêëìíîïñòóôġö÷ĝùúûüŭŝ˙"),
+    encoding("iso-8859-4", "ISO-8859-4 (North European)", "\u{00A0}ĄĸŖ¤ĨĻ§¨ŠĒĢŦ\u{00AD}Ž¯°ą˛ŗ´ĩļˇ¸šēģŧŊžŋĀÁÂÃÄÅÆĮČÉĘËĖÍÎĪĐŅŌĶÔÕÖ×ØŲÚÛÜŨŪßāáâãäåæįčéęëėíîīđņōķôõö÷øųúûüũū˙"),
+    encoding("iso-8859-5", "ISO-8859-5 (Cyrillic)", "\u{00A0}ЁЂЃЄЅІЇЈЉЊЋЌ\u{00AD}ЎЏАБВГДЕЖЗИЙКЛМНОПРСТУФХЦЧШЩЪЫЬЭЮЯабвгдежзийклмнопрстуфхцчшщъыьэюя№ёђѓєѕіїјљњћќ§ўџ"),
+    encoding("iso-8859-6", "ISO-8859-6 (Arabic)", "\u{00A0}¤،\u{00AD}؛؟ءآأؤإئابةتثجحخدذرزسشصضطظعغـفقكلمنهوىي\u{064B}\u{064C}\u{064D}\u{064E}\u{064F}\u{0650}\u{0651}\u{0652}"),
+    encoding("iso-8859-7", "ISO-8859-7 (Greek)", "\u{00A0}‘’£€₯¦§¨©ͺ«¬\u{00AD}―°±²³΄΅Ά·ΈΉΊ»Ό½ΎΏΐΑΒΓΔΕΖΗΘΙΚΛΜΝΞΟΠΡΣΤΥΦΧΨΩΪΫάέήίΰαβγδεζηθικλμνξοπρςστυφχψωϊϋόύώ"),
+    encoding("iso-8859-8", "ISO-8859-8 (Hebrew)", "\u{00A0}¢£¤¥¦§¨©×«¬\u{00AD}®¯°±²³´µ¶·¸¹÷»¼½¾‗אבגדהוזחטיךכלםמןנסעףפץצקרשת\u{200E}\u{200F}"),
+    encoding("iso-8859-9", "ISO-8859-9 (Turkish)", "\u{00A0}¡¢£¤¥¦§¨©ª«¬\u{00AD}®¯°±²³´µ¶·¸¹º»¼½¾¿ÀÁÂÃÄÅÆÇÈÉÊËÌÍÎÏĞÑÒÓÔÕÖ×ØÙÚÛÜİŞßàáâãäåæçèéêëìíîïğñòóôõö÷øùúûüışÿ"),
+    encoding("iso-8859-10", "ISO-8859-10 (Nordic)", "\u{00A0}ĄĒĢĪĨĶ§ĻĐŠŦŽ\u{00AD}ŪŊ°ąēģīĩķ·ļđšŧž―ūŋĀÁÂÃÄÅÆĮČÉĘËĖÍÎÏÐŅŌÓÔÕÖŨØŲÚÛÜÝÞßāáâãäåæįčéęëėíîïðņōóôõöũøųúûüýþĸ"),
+    encoding("iso-8859-11", "ISO-8859-11 (Thai)", "\u{00A0}กขฃคฅฆงจฉชซฌญฎฏฐฑฒณดตถทธนบปผฝพฟภมยรฤลฦวศษสหฬอฮฯะ\u{0E31}าำ\u{0E34}\u{0E35}\u{0E36}\u{0E37}\u{0E38}\u{0E39}\u{0E3A}฿เแโใไๅๆ\u{0E47}\u{0E48}\u{0E49}\u{0E4A}\u{0E4B}\u{0E4C}\u{0E4D}\u{0E4E}๏๐๑๒๓๔๕๖๗๘๙๚๛"),
+    encoding("iso-8859-13", "ISO-8859-13 (Baltic Rim)", "\u{00A0}”¢£¤„¦§Ø©Ŗ«¬\u{00AD}®Æ°±²³“µ¶·ø¹ŗ»¼½¾æĄĮĀĆÄÅĘĒČÉŹĖĢĶĪĻŠŃŅÓŌÕÖ×ŲŁŚŪÜŻŽßąįāćäåęēčéźėģķīļšńņóōõö÷ųłśūüżž’"),
+    encoding("iso-8859-14", "ISO-8859-14 (Celtic)", "\u{00A0}Ḃḃ£ĊċḊ§Ẁ©ẂḋỲ\u{00AD}®ŸḞḟĠġṀṁ¶ṖẁṗẃṠỳẄẅṡÀÁÂÃÄÅÆÇÈÉÊËÌÍÎÏŴÑÒÓÔÕÖṪØÙÚÛÜÝŶßàáâãäåæçèéêëìíîïŵñòóôõöṫøùúûüýŷÿ"),
+    encoding("iso-8859-15", "ISO-8859-15 (Western European (Euro))", "\u{00A0}¡¢£€¥Š§š©ª«¬\u{00AD}®¯°±²³Žµ¶·ž¹º»ŒœŸ¿ÀÁÂÃÄÅÆÇÈÉÊËÌÍÎÏÐÑÒÓÔÕÖ×ØÙÚÛÜÝÞßàáâãäåæçèéêëìíîïðñòóôõö÷øùúûüýþÿ"),
+    encoding("iso-8859-16", "ISO-8859-16 (South-Eastern European)", "\u{00A0}ĄąŁ€„Š§š©Ș«Ź\u{00AD}źŻ°±ČłŽ”¶·žčș»ŒœŸżÀÁÂĂÄĆÆÇÈÉÊËÌÍÎÏĐŃÒÓÔŐÖŚŰÙÚÛÜĘȚßàáâăäćæçèéêëìíîïđńòóôőöśűùúûüęțÿ"),
+    encoding("windows-1250", "Windows-1250 (Central European)", "€‚„…†‡‰Š‹ŚŤŽŹ‘’“”•–—™š›śťžź\u{00A0}ˇ˘Ł¤Ą¦§¨©Ş«¬\u{00AD}®Ż°±˛ł´µ¶·¸ąş»Ľ˝ľżŔÁÂĂÄĹĆÇČÉĘËĚÍÎĎĐŃŇÓÔŐÖ×ŘŮÚŰÜÝŢßŕáâăäĺćçčéęëěíîďđńňóôőö÷řůúűüýţ˙"),
+    encoding("windows-1251", "Windows-1251 (Cyrillic)", "ЂЃ‚ѓ„…†‡€‰Љ‹ЊЌЋЏђ‘’“”•–—™љ›њќћџ\u{00A0}ЎўЈ¤Ґ¦§Ё©Є«¬\u{00AD}®Ї°±Ііґµ¶·ё№є»јЅѕїАБВГДЕЖЗИЙКЛМНОПРСТУФХЦЧШЩЪЫЬЭЮЯабвгдежзийклмнопрстуфхцчшщъыьэюя"),
+    encoding("windows-1252", "Windows-1252 (Western European)", "€‚ƒ„…†‡ˆ‰Š‹ŒŽ‘’“”•–—˜™š›œžŸ\u{00A0}¡¢£¤¥¦§¨©ª«¬\u{00AD}®¯°±²³´µ¶·¸¹º»¼½¾¿ÀÁÂÃÄÅÆÇÈÉÊËÌÍÎÏÐÑÒÓÔÕÖ×ØÙÚÛÜÝÞßàáâãäåæçèéêëìíîïðñòóôõö÷øùúûüýþÿ"),
+    encoding("windows-1253", "Windows-1253 (Greek)", "€‚ƒ„…†‡‰‹‘’“”•–—™›\u{00A0}΅Ά£¤¥¦§¨©«¬\u{00AD}®―°±²³΄µ¶·ΈΉΊ»Ό½ΎΏΐΑΒΓΔΕΖΗΘΙΚΛΜΝΞΟΠΡΣΤΥΦΧΨΩΪΫάέήίΰαβγδεζηθικλμνξοπρςστυφχψωϊϋόύώ"),
+    encoding("windows-1254", "Windows-1254 (Turkish)", "€‚ƒ„…†‡ˆ‰Š‹Œ‘’“”•–—˜™š›œŸ\u{00A0}¡¢£¤¥¦§¨©ª«¬\u{00AD}®¯°±²³´µ¶·¸¹º»¼½¾¿ÀÁÂÃÄÅÆÇÈÉÊËÌÍÎÏĞÑÒÓÔÕÖ×ØÙÚÛÜİŞßàáâãäåæçèéêëìíîïğñòóôõö÷øùúûüışÿ"),
+    encoding("windows-1255", "Windows-1255 (Hebrew)", "€‚ƒ„…†‡ˆ‰‹‘’“”•–—˜™›\u{00A0}¡¢£₪¥¦§¨©×«¬\u{00AD}®¯°±²³´µ¶·¸¹÷»¼½¾¿\u{05B0}\u{05B1}\u{05B2}\u{05B3}\u{05B4}\u{05B5}\u{05B6}\u{05B7}\u{05B8}\u{05B9}\u{05BB}\u{05BC}\u{05BD}־\u{05BF}׀\u{05C1}\u{05C2}׃װױײ׳״אבגדהוזחטיךכלםמןנסעףפץצקרשת\u{200E}\u{200F}"),
+    encoding("windows-1256", "Windows-1256 (Arabic)", "€پ‚ƒ„…†‡ˆ‰ٹ‹Œچژڈگ‘’“”•–—ک™ڑ›œ\u{200C}\u{200D}ں\u{00A0}،¢£¤¥¦§¨©ھ«¬\u{00AD}®¯°±²³´µ¶·¸¹؛»¼½¾؟ہءآأؤإئابةتثجحخدذرزسشصض×طظعغـفقكàلâمنهوçèéêëىيîï\u{064B}\u{064C}\u{064D}\u{064E}ô\u{064F}\u{0650}÷\u{0651}ù\u{0652}ûü\u{200E}\u{200F}ے"),
+    encoding("windows-1257", "Windows-1257 (Baltic)", "€‚„…†‡‰‹¨ˇ¸‘’“”•–—™›¯˛\u{00A0}¢£¤¦§Ø©Ŗ«¬\u{00AD}®Æ°±²³´µ¶·ø¹ŗ»¼½¾æĄĮĀĆÄÅĘĒČÉŹĖĢĶĪĻŠŃŅÓŌÕÖ×ŲŁŚŪÜŻŽßąįāćäåęēčéźėģķīļšńņóōõö÷ųłśūüżž˙"),
+    encoding("windows-1258", "Windows-1258 (Vietnamese)", "€‚ƒ„…†‡ˆ‰‹Œ‘’“”•–—˜™›œŸ\u{00A0}¡¢£¤¥¦§¨©ª«¬\u{00AD}®¯°±²³´µ¶·¸¹º»¼½¾¿ÀÁÂĂÄÅÆÇÈÉÊË\u{0300}ÍÎÏĐÑ\u{0309}ÓÔƠÖ×ØÙÚÛÜƯ\u{0303}ßàáâăäåæçèéêë\u{0301}íîïđñ\u{0323}óôơö÷øùúûüư₫ÿ"),
+    script("cyrillic", "Cyrillic", "Script", "ЀЁЂЃЄЅІЇЈЉЊЋЌЍЎЏАБВГДЕЖЗИЙКЛМНОПРСТУФХЦЧШЩЪЫЬЭЮЯабвгдежзийклмнопрстуфхцчшщъыьэюяѐёђѓєѕіїјљњћќѝўџҐґ"),
+    script("greek", "Greek", "Script", "ΆΈΉΊΌΎΏΐΑΒΓΔΕΖΗΘΙΚΛΜΝΞΟΠΡΣΤΥΦΧΨΩΪΫάέήίΰαβγδεζηθικλμνξοπρςστυφχψωϊϋόύώ"),
+    script(
+        "box-drawing",
+        "Box Drawing",
+        "Symbols",
+        "─━│┃┄┅┆┇┈┉┊┋┌┍┎┏┐┑┒┓└┕┖┗┘┙┚┛├┝┞┟┠┡┢┣┤┥┦┧┨┩┪┫┬┭┮┯┰┱┲┳┴┵┶┷┸┹┺┻┼┽┾┿╀╁╂╃╄╅╆╇╈╉╊╋╌╍╎╏═║╒╓╔╕╖╗╘╙╚╛╜╝╞╟╠╡╢╣╤╥╦╧╨╩╪╫╬╭╮╯╰╱╲╳╴╵╶╷╸╹╺╻╼╽╾╿",
+    ),
+    script("block-elements", "Block Elements", "Symbols", "▀▁▂▃▄▅▆▇█▉▊▋▌▍▎▏▐░▒▓▔▕▖▗▘▙▚▛▜▝▞▟"),
+];
+
+/// All built-in sets, in display order.
+pub fn builtin_sets() -> Vec<CharacterSet> {
+    BUILTIN
+        .iter()
+        .map(|b| CharacterSet {
+            id: b.id.to_string(),
+            name: b.name.to_string(),
+            category: b.category.to_string(),
+            chars: if b.ascii {
+                format!("{}{}", ASCII, b.chars)
+            } else {
+                b.chars.to_string()
+            },
+        })
+        .collect()
+}
+
+/// Looks up a set by id, user-defined sets first.
+pub fn find_set(id: &str, custom: &[CharacterSet]) -> Option<CharacterSet> {
+    custom
+        .iter()
+        .find(|s| s.id == id)
+        .cloned()
+        .or_else(|| builtin_sets().into_iter().find(|s| s.id == id))
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CoveredChar {
+    pub char: char,
+    pub glyph: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SetCoverage {
+    pub id: String,
+    pub name: String,
+    pub total: usize,
+    pub covered: Vec<CoveredChar>,
+    pub missing: Vec<char>,
+}
+
+/// Characters of the document, by `UNICODE` and by `CHAR`, mapped to the first glyph.
+fn glyph_index(doc: &GtfDocument) -> HashMap<char, &str> {
+    let mut index = HashMap::new();
+    for glyph in &doc.glyphs {
+        for ch in [glyph.codepoint(), glyph.char_repr].into_iter().flatten() {
+            index.entry(ch).or_insert(glyph.name.as_str());
+        }
+    }
+    index
+}
+
+/// Which characters of `set` have a glyph in `doc`.
+pub fn coverage(doc: &GtfDocument, set: &CharacterSet) -> SetCoverage {
+    let index = glyph_index(doc);
+    let chars = set.chars();
+    let mut covered = Vec::new();
+    let mut missing = Vec::new();
+    for ch in &chars {
+        match index.get(ch) {
+            Some(name) => covered.push(CoveredChar {
+                char: *ch,
+                glyph: name.to_string(),
+            }),
+            None => missing.push(*ch),
+        }
+    }
+    SetCoverage {
+        id: set.id.clone(),
+        name: set.name.clone(),
+        total: chars.len(),
+        covered,
+        missing,
+    }
+}
+
+/// Appends a blank glyph for every character of `set` missing in `doc` and
+/// returns the names of the new glyphs.
+pub fn add_missing_glyphs(doc: &mut GtfDocument, set: &CharacterSet) -> Vec<String> {
+    let missing = coverage(doc, set).missing;
+    let fill = default_background_char(doc).unwrap_or(DEFAULT_FILL);
+    missing
+        .into_iter()
+        .map(|ch| {
+            let glyph = blank_glyph_for_char(doc, ch, fill);
+            let name = glyph.name.clone();
+            doc.glyphs.push(glyph);
+            name
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gtf::test_util::{char_glyph, document, glyph};
+
+    fn custom(id: &str, chars: &str) -> CharacterSet {
+        CharacterSet {
+            id: id.to_string(),
+            name: id.to_string(),
+            category: String::new(),
+            chars: chars.to_string(),
+        }
+    }
+
+    #[test]
+    fn coverage_of_a_partial_latin_2_font() {
+        // `š` only by UNICODE, `Č` only by CHAR
+        let mut s_caron = glyph("scaron", &["#"]);
+        s_caron.unicode = Some("U+0161".to_string());
+        let mut c_caron = glyph("Ccaron", &["#"]);
+        c_caron.char_repr = Some('Č');
+        let doc = document(vec![
+            char_glyph("A", 'A', &["#"]),
+            s_caron,
+            c_caron,
+            char_glyph("alpha", 'α', &["#"]),
+        ]);
+        let set = find_set("iso-8859-2", &[]).unwrap();
+
+        let result = coverage(&doc, &set);
+        assert_eq!(result.total, 95 + 96);
+        let covered: Vec<(char, &str)> = result
+            .covered
+            .iter()
+            .map(|c| (c.char, c.glyph.as_str()))
+            .collect();
+        // In set order: `š` (0xB9) comes before `Č` (0xC8)
+        assert_eq!(covered, vec![('A', "A"), ('š', "scaron"), ('Č', "Ccaron")]);
+        assert_eq!(result.missing.len(), result.total - 3);
+        assert!(!result.missing.contains(&'Č'));
+        assert!(result.missing.contains(&'Ł'));
+    }
+
+    #[test]
+    fn missing_glyphs_get_the_background_and_their_character() {
+        // White is the background here, so new glyphs are filled with '#'
+        let mut doc = document(vec![char_glyph("A", 'A', &["##", "#."])]);
+        let added = add_missing_glyphs(&mut doc, &custom("set", "AĽ?"));
+
+        assert_eq!(added, vec!["Ľ", "Glyph"]);
+        let l_caron = &doc.glyphs[1];
+        assert_eq!(l_caron.unicode.as_deref(), Some("U+013D"));
+        assert_eq!(l_caron.char_repr, Some('Ľ'));
+        assert_eq!(l_caron.bitmap, vec!["##", "##"]);
+        assert_eq!(doc.glyphs[2].char_repr, Some('?'));
+        assert!(add_missing_glyphs(&mut doc, &custom("set", "AĽ?")).is_empty());
+    }
+
+    #[test]
+    fn custom_sets_shadow_builtin_ones() {
+        let sets = [custom("czech", "č")];
+        assert_eq!(find_set("czech", &sets).unwrap().chars, "č");
+        assert_eq!(find_set("polish", &sets).unwrap().name, "Polish");
+        assert!(find_set("klingon", &sets).is_none());
+    }
+}
@@ -0,0 +1,199 @@
+//! Canonical palette color.
+//!
+//! Palette entries are parsed once on load into `Color`, so the rest of the
+//! code compares values instead of `#RGB` / `#RRGGBB` strings in any case.
+//! Colors are written back (and sent to the UI) as upper-case `#RRGGBB`, or
+//! `#RRGGBBAA` when the color is not fully opaque. `transparent` is a keyword
+//! for `#00000000`. Files using only opaque colors keep the `#RRGGBB` form, but
+//! `#RGB` and lower-case hex are rewritten.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    /// 255 is opaque, 0 fully transparent.
+    pub a: u8,
+}
+
+impl Color {
+    pub const BLACK: Color = Color::rgb(0, 0, 0);
+    pub const WHITE: Color = Color::rgb(255, 255, 255);
+    pub const TRANSPARENT: Color = Color::rgba(0, 0, 0, 0);
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Color { r, g, b, a: 255 }
+    }
+
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Color { r, g, b, a }
+    }
+
+    pub fn is_opaque(&self) -> bool {
+        self.a == 255
+    }
+
+    pub fn is_transparent(&self) -> bool {
+        self.a == 0
+    }
+
+    /// `self` drawn over `below` (source-over alpha compositing).
+    pub fn over(self, below: Color) -> Color {
+        match self.a {
+            255 => return self,
+            0 => return below,
+            _ => {}
+        }
+        let src_a = self.a as u32;
+        let dst_a = (below.a as u32 * (255 - src_a) + 127) / 255;
+        let out_a = src_a + dst_a;
+        let channel = |src: u8, dst: u8| {
+            ((src as u32 * src_a + dst as u32 * dst_a + out_a / 2) / out_a) as u8
+        };
+        Color::rgba(
+            channel(self.r, below.r),
+            channel(self.g, below.g),
+            channel(self.b, below.b),
+            out_a as u8,
+        )
+    }
+
+    /// `[r, g, b, a]` for image buffers.
+    pub fn to_rgba(&self) -> [u8; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+
+    pub fn from_rgba([r, g, b, a]: [u8; 4]) -> Self {
+        Color { r, g, b, a }
+    }
+}
+
+impl Default for Color {
+    fn default() -> Self {
+        Color::BLACK
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if *self == Color::TRANSPARENT {
+            return f.write_str("transparent");
+        }
+        write!(f, "#{:02X}{:02X}{:02X}", self.r, self.g, self.b)?;
+        if !self.is_opaque() {
+            write!(f, "{:02X}", self.a)?;
+        }
+        Ok(())
+    }
+}
+
+/// Accepts `#RGB`, `#RGBA`, `#RRGGBB`, `#RRGGBBAA` and `transparent` in any case.
+impl FromStr for Color {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "Invalid color '{}'. Expected '#RGB', '#RGBA', '#RRGGBB', '#RRGGBBAA' or 'transparent'.",
+                s
+            )
+        };
+        if s.trim().eq_ignore_ascii_case("transparent") {
+            return Ok(Color::TRANSPARENT);
+        }
+        let hex = s.trim().strip_prefix('#').ok_or_else(invalid)?;
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+        let digits: Vec<u8> = match hex.len() {
+            // Short forms repeat every digit: #F80 == #FF8800
+            3 | 4 => hex
+                .chars()
+                .map(|c| c.to_digit(16).unwrap() as u8 * 17)
+                .collect(),
+            6 | 8 => (0..hex.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+                .collect(),
+            _ => return Err(invalid()),
+        };
+        Ok(Color::rgba(
+            digits[0],
+            digits[1],
+            digits[2],
+            digits.get(3).copied().unwrap_or(255),
+        ))
+    }
+}
+
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Color {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_all_forms() {
+        assert_eq!(parse("#f80"), Color::rgb(255, 136, 0));
+        assert_eq!(parse("#F808"), Color::rgba(255, 136, 0, 136));
+        assert_eq!(parse("#12345678"), Color::rgba(0x12, 0x34, 0x56, 0x78));
+        assert_eq!(parse("#123456"), Color::rgb(0x12, 0x34, 0x56));
+        assert_eq!(parse(" Transparent "), Color::TRANSPARENT);
+        for invalid in ["123456", "#12345", "#GGGGGG", "#1234567", "none", ""] {
+            assert!(invalid.parse::<Color>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn display_uses_the_shortest_exact_form() {
+        assert_eq!(Color::rgb(255, 136, 0).to_string(), "#FF8800");
+        assert_eq!(Color::rgba(255, 136, 0, 128).to_string(), "#FF880080");
+        assert_eq!(Color::TRANSPARENT.to_string(), "transparent");
+        // Only transparent black has the keyword
+        assert_eq!(Color::rgba(255, 255, 255, 0).to_string(), "#FFFFFF00");
+    }
+
+    #[test]
+    fn parse_and_format_round_trip() {
+        for text in ["#FF8800", "#FF880080", "#00000001", "transparent"] {
+            assert_eq!(parse(text).to_string(), text);
+        }
+        assert_eq!(parse("#abc").to_string(), "#AABBCC");
+        assert_eq!(parse("#abcf").to_string(), "#AABBCC");
+        assert_eq!(parse("#00000000").to_string(), "transparent");
+    }
+
+    #[test]
+    fn over_composites_and_rounds() {
+        let half_red = Color::rgba(255, 0, 0, 128);
+        assert_eq!(half_red.over(Color::BLACK), Color::rgb(128, 0, 0));
+        assert_eq!(half_red.over(Color::WHITE), Color::rgb(255, 127, 127));
+        assert_eq!(Color::WHITE.over(half_red), Color::WHITE);
+        assert_eq!(Color::TRANSPARENT.over(half_red), half_red);
+
+        // Over a transparent pixel the color stays as it is
+        assert_eq!(half_red.over(Color::TRANSPARENT), half_red);
+        let quarter_blue = Color::rgba(0, 0, 255, 64);
+        assert_eq!(quarter_blue.over(half_red), Color::rgba(153, 0, 102, 160));
+    }
+}
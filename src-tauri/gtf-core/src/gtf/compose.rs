@@ -0,0 +1,867 @@
+//! Accented glyphs composed from a base letter and a diacritic glyph.
+//!
+//! Unicode decomposes `Č` into `C` + combining caron (U+030C). The diacritic
+//! glyph is looked up by the combining codepoint or by its spacing form
+//! (`ˇ` U+02C7), so fonts that draw accents as ordinary characters work as
+//! they are. Placement uses the ink (non-background pixels) of both glyphs:
+//! the diacritic goes above or below the base letter with a configurable gap
+//! and is aligned to the center or an edge of the base. The caron of `ď`, `ť`,
+//! `ľ` and `Ľ` is attached to the right of the letter, as it is usually drawn,
+//! using the apostrophe glyph when the font has one.
+
+use serde::{Deserialize, Serialize};
+
+use super::bounds::untrim_glyph;
+use super::charmap::{blank_glyph_for_char, unicode_of};
+use super::color::Color;
+use super::palette::{
+    background_char, background_color, effective_palette, pixel_color, reconcile_glyph_palette,
+    strip_inherited,
+};
+use super::types::{Glyph, GtfDocument, Size};
+
+/// Horizontal anchor of the diacritic on the base letter's ink.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Alignment {
+    #[default]
+    Center,
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ComposeOptions {
+    /// Background pixels between the base letter and the diacritic.
+    pub gap: usize,
+    pub alignment: Alignment,
+    /// Extra horizontal shift of the diacritic, positive to the right.
+    pub offset_x: i32,
+}
+
+impl Default for ComposeOptions {
+    fn default() -> Self {
+        Self {
+            gap: 1,
+            alignment: Alignment::Center,
+            offset_x: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Placement {
+    Above,
+    Below,
+    /// Next to the top right of the base letter (`ď`, `ľ`).
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Decomposition {
+    pub base: char,
+    /// Combining diacritic (U+0300..U+036F).
+    pub mark: char,
+    pub placement: Placement,
+}
+
+/// Precomposed Latin letters with a single diacritic: (letter, base, mark).
+/// Sorted by letter for binary search.
+const DECOMPOSITIONS: &[(char, char, char)] = &[
+    ('\u{00C0}', 'A', '\u{0300}'), // À
+    ('\u{00C1}', 'A', '\u{0301}'), // Á
+    ('\u{00C2}', 'A', '\u{0302}'), // Â
+    ('\u{00C3}', 'A', '\u{0303}'), // Ã
+    ('\u{00C4}', 'A', '\u{0308}'), // Ä
+    ('\u{00C5}', 'A', '\u{030A}'), // Å
+    ('\u{00C7}', 'C', '\u{0327}'), // Ç
+    ('\u{00C8}', 'E', '\u{0300}'), // È
+    ('\u{00C9}', 'E', '\u{0301}'), // É
+    ('\u{00CA}', 'E', '\u{0302}'), // Ê
+    ('\u{00CB}', 'E', '\u{0308}'), // Ë
+    ('\u{00CC}', 'I', '\u{0300}'), // Ì
+    ('\u{00CD}', 'I', '\u{0301}'), // Í
+    ('\u{00CE}', 'I', '\u{0302}'), // Î
+    ('\u{00CF}', 'I', '\u{0308}'), // Ï
+    ('\u{00D1}', 'N', '\u{0303}'), // Ñ
+    ('\u{00D2}', 'O', '\u{0300}'), // Ò
+    ('\u{00D3}', 'O', '\u{0301}'), // Ó
+    ('\u{00D4}', 'O', '\u{0302}'), // Ô
+    ('\u{00D5}', 'O', '\u{0303}'), // Õ
+    ('\u{00D6}', 'O', '\u{0308}'), // Ö
+    ('\u{00D9}', 'U', '\u{0300}'), // Ù
+    ('\u{00DA}', 'U', '\u{0301}'), // Ú
+    ('\u{00DB}', 'U', '\u{0302}'), // Û
+    ('\u{00DC}', 'U', '\u{0308}'), // Ü
+    ('\u{00DD}', 'Y', '\u{0301}'), // Ý
+    ('\u{00E0}', 'a', '\u{0300}'), // à
+    ('\u{00E1}', 'a', '\u{0301}'), // á
+    ('\u{00E2}', 'a', '\u{0302}'), // â
+    ('\u{00E3}', 'a', '\u{0303}'), // ã
+    ('\u{00E4}', 'a', '\u{0308}'), // ä
+    ('\u{00E5}', 'a', '\u{030A}'), // å
+    ('\u{00E7}', 'c', '\u{0327}'), // ç
+    ('\u{00E8}', 'e', '\u{0300}'), // è
+    ('\u{00E9}', 'e', '\u{0301}'), // é
+    ('\u{00EA}', 'e', '\u{0302}'), // ê
+    ('\u{00EB}', 'e', '\u{0308}'), // ë
+    ('\u{00EC}', 'i', '\u{0300}'), // ì
+    ('\u{00ED}', 'i', '\u{0301}'), // í
+    ('\u{00EE}', 'i', '\u{0302}'), // î
+    ('\u{00EF}', 'i', '\u{0308}'), // ï
+    ('\u{00F1}', 'n', '\u{0303}'), // ñ
+    ('\u{00F2}', 'o', '\u{0300}'), // ò
+    ('\u{00F3}', 'o', '\u{0301}'), // ó
+    ('\u{00F4}', 'o', '\u{0302}'), // ô
+    ('\u{00F5}', 'o', '\u{0303}'), // õ
+    ('\u{00F6}', 'o', '\u{0308}'), // ö
+    ('\u{00F9}', 'u', '\u{0300}'), // ù
+    ('\u{00FA}', 'u', '\u{0301}'), // ú
+    ('\u{00FB}', 'u', '\u{0302}'), // û
+    ('\u{00FC}', 'u', '\u{0308}'), // ü
+    ('\u{00FD}', 'y', '\u{0301}'), // ý
+    ('\u{00FF}', 'y', '\u{0308}'), // ÿ
+    ('\u{0100}', 'A', '\u{0304}'), // Ā
+    ('\u{0101}', 'a', '\u{0304}'), // ā
+    ('\u{0102}', 'A', '\u{0306}'), // Ă
+    ('\u{0103}', 'a', '\u{0306}'), // ă
+    ('\u{0104}', 'A', '\u{0328}'), // Ą
+    ('\u{0105}', 'a', '\u{0328}'), // ą
+    ('\u{0106}', 'C', '\u{0301}'), // Ć
+    ('\u{0107}', 'c', '\u{0301}'), // ć
+    ('\u{0108}', 'C', '\u{0302}'), // Ĉ
+    ('\u{0109}', 'c', '\u{0302}'), // ĉ
+    ('\u{010A}', 'C', '\u{0307}'), // Ċ
+    ('\u{010B}', 'c', '\u{0307}'), // ċ
+    ('\u{010C}', 'C', '\u{030C}'), // Č
+    ('\u{010D}', 'c', '\u{030C}'), // č
+    ('\u{010E}', 'D', '\u{030C}'), // Ď
+    ('\u{010F}', 'd', '\u{030C}'), // ď
+    ('\u{0112}', 'E', '\u{0304}'), // Ē
+    ('\u{0113}', 'e', '\u{0304}'), // ē
+    ('\u{0114}', 'E', '\u{0306}'), // Ĕ
+    ('\u{0115}', 'e', '\u{0306}'), // ĕ
+    ('\u{0116}', 'E', '\u{0307}'), // Ė
+    ('\u{0117}', 'e', '\u{0307}'), // ė
+    ('\u{0118}', 'E', '\u{0328}'), // Ę
+    ('\u{0119}', 'e', '\u{0328}'), // ę
+    ('\u{011A}', 'E', '\u{030C}'), // Ě
+    ('\u{011B}', 'e', '\u{030C}'), // ě
+    ('\u{011C}', 'G', '\u{0302}'), // Ĝ
+    ('\u{011D}', 'g', '\u{0302}'), // ĝ
+    ('\u{011E}', 'G', '\u{0306}'), // Ğ
+    ('\u{011F}', 'g', '\u{0306}'), // ğ
+    ('\u{0120}', 'G', '\u{0307}'), // Ġ
+    ('\u{0121}', 'g', '\u{0307}'), // ġ
+    ('\u{0122}', 'G', '\u{0327}'), // Ģ
+    ('\u{0123}', 'g', '\u{0327}'), // ģ
+    ('\u{0124}', 'H', '\u{0302}'), // Ĥ
+    ('\u{0125}', 'h', '\u{0302}'), // ĥ
+    ('\u{0128}', 'I', '\u{0303}'), // Ĩ
+    ('\u{0129}', 'i', '\u{0303}'), // ĩ
+    ('\u{012A}', 'I', '\u{0304}'), // Ī
+    ('\u{012B}', 'i', '\u{0304}'), // ī
+    ('\u{012C}', 'I', '\u{0306}'), // Ĭ
+    ('\u{012D}', 'i', '\u{0306}'), // ĭ
+    ('\u{012E}', 'I', '\u{0328}'), // Į
+    ('\u{012F}', 'i', '\u{0328}'), // į
+    ('\u{0130}', 'I', '\u{0307}'), // İ
+    ('\u{0134}', 'J', '\u{0302}'), // Ĵ
+    ('\u{0135}', 'j', '\u{0302}'), // ĵ
+    ('\u{0136}', 'K', '\u{0327}'), // Ķ
+    ('\u{0137}', 'k', '\u{0327}'), // ķ
+    ('\u{0139}', 'L', '\u{0301}'), // Ĺ
+    ('\u{013A}', 'l', '\u{0301}'), // ĺ
+    ('\u{013B}', 'L', '\u{0327}'), // Ļ
+    ('\u{013C}', 'l', '\u{0327}'), // ļ
+    ('\u{013D}', 'L', '\u{030C}'), // Ľ
+    ('\u{013E}', 'l', '\u{030C}'), // ľ
+    ('\u{0143}', 'N', '\u{0301}'), // Ń
+    ('\u{0144}', 'n', '\u{0301}'), // ń
+    ('\u{0145}', 'N', '\u{0327}'), // Ņ
+    ('\u{0146}', 'n', '\u{0327}'), // ņ
+    ('\u{0147}', 'N', '\u{030C}'), // Ň
+    ('\u{0148}', 'n', '\u{030C}'), // ň
+    ('\u{014C}', 'O', '\u{0304}'), // Ō
+    ('\u{014D}', 'o', '\u{0304}'), // ō
+    ('\u{014E}', 'O', '\u{0306}'), // Ŏ
+    ('\u{014F}', 'o', '\u{0306}'), // ŏ
+    ('\u{0150}', 'O', '\u{030B}'), // Ő
+    ('\u{0151}', 'o', '\u{030B}'), // ő
+    ('\u{0154}', 'R', '\u{0301}'), // Ŕ
+    ('\u{0155}', 'r', '\u{0301}'), // ŕ
+    ('\u{0156}', 'R', '\u{0327}'), // Ŗ
+    ('\u{0157}', 'r', '\u{0327}'), // ŗ
+    ('\u{0158}', 'R', '\u{030C}'), // Ř
+    ('\u{0159}', 'r', '\u{030C}'), // ř
+    ('\u{015A}', 'S', '\u{0301}'), // Ś
+    ('\u{015B}', 's', '\u{0301}'), // ś
+    ('\u{015C}', 'S', '\u{0302}'), // Ŝ
+    ('\u{015D}', 's', '\u{0302}'), // ŝ
+    ('\u{015E}', 'S', '\u{0327}'), // Ş
+    ('\u{015F}', 's', '\u{0327}'), // ş
+    ('\u{0160}', 'S', '\u{030C}'), // Š
+    ('\u{0161}', 's', '\u{030C}'), // š
+    ('\u{0162}', 'T', '\u{0327}'), // Ţ
+    ('\u{0163}', 't', '\u{0327}'), // ţ
+    ('\u{0164}', 'T', '\u{030C}'), // Ť
+    ('\u{0165}', 't', '\u{030C}'), // ť
+    ('\u{0168}', 'U', '\u{0303}'), // Ũ
+    ('\u{0169}', 'u', '\u{0303}'), // ũ
+    ('\u{016A}', 'U', '\u{0304}'), // Ū
+    ('\u{016B}', 'u', '\u{0304}'), // ū
+    ('\u{016C}', 'U', '\u{0306}'), // Ŭ
+    ('\u{016D}', 'u', '\u{0306}'), // ŭ
+    ('\u{016E}', 'U', '\u{030A}'), // Ů
+    ('\u{016F}', 'u', '\u{030A}'), // ů
+    ('\u{0170}', 'U', '\u{030B}'), // Ű
+    ('\u{0171}', 'u', '\u{030B}'), // ű
+    ('\u{0172}', 'U', '\u{0328}'), // Ų
+    ('\u{0173}', 'u', '\u{0328}'), // ų
+    ('\u{0174}', 'W', '\u{0302}'), // Ŵ
+    ('\u{0175}', 'w', '\u{0302}'), // ŵ
+    ('\u{0176}', 'Y', '\u{0302}'), // Ŷ
+    ('\u{0177}', 'y', '\u{0302}'), // ŷ
+    ('\u{0178}', 'Y', '\u{0308}'), // Ÿ
+    ('\u{0179}', 'Z', '\u{0301}'), // Ź
+    ('\u{017A}', 'z', '\u{0301}'), // ź
+    ('\u{017B}', 'Z', '\u{0307}'), // Ż
+    ('\u{017C}', 'z', '\u{0307}'), // ż
+    ('\u{017D}', 'Z', '\u{030C}'), // Ž
+    ('\u{017E}', 'z', '\u{030C}'), // ž
+    ('\u{01CD}', 'A', '\u{030C}'), // Ǎ
+    ('\u{01CE}', 'a', '\u{030C}'), // ǎ
+    ('\u{01CF}', 'I', '\u{030C}'), // Ǐ
+    ('\u{01D0}', 'i', '\u{030C}'), // ǐ
+    ('\u{01D1}', 'O', '\u{030C}'), // Ǒ
+    ('\u{01D2}', 'o', '\u{030C}'), // ǒ
+    ('\u{01D3}', 'U', '\u{030C}'), // Ǔ
+    ('\u{01D4}', 'u', '\u{030C}'), // ǔ
+    ('\u{01E6}', 'G', '\u{030C}'), // Ǧ
+    ('\u{01E7}', 'g', '\u{030C}'), // ǧ
+    ('\u{01E8}', 'K', '\u{030C}'), // Ǩ
+    ('\u{01E9}', 'k', '\u{030C}'), // ǩ
+    ('\u{01EA}', 'O', '\u{0328}'), // Ǫ
+    ('\u{01EB}', 'o', '\u{0328}'), // ǫ
+    ('\u{01F0}', 'j', '\u{030C}'), // ǰ
+    ('\u{01F4}', 'G', '\u{0301}'), // Ǵ
+    ('\u{01F5}', 'g', '\u{0301}'), // ǵ
+    ('\u{01F8}', 'N', '\u{0300}'), // Ǹ
+    ('\u{01F9}', 'n', '\u{0300}'), // ǹ
+    ('\u{0218}', 'S', '\u{0326}'), // Ș
+    ('\u{0219}', 's', '\u{0326}'), // ș
+    ('\u{021A}', 'T', '\u{0326}'), // Ț
+    ('\u{021B}', 't', '\u{0326}'), // ț
+    ('\u{021E}', 'H', '\u{030C}'), // Ȟ
+    ('\u{021F}', 'h', '\u{030C}'), // ȟ
+    ('\u{0226}', 'A', '\u{0307}'), // Ȧ
+    ('\u{0227}', 'a', '\u{0307}'), // ȧ
+    ('\u{0228}', 'E', '\u{0327}'), // Ȩ
+    ('\u{0229}', 'e', '\u{0327}'), // ȩ
+    ('\u{022E}', 'O', '\u{0307}'), // Ȯ
+    ('\u{022F}', 'o', '\u{0307}'), // ȯ
+    ('\u{0232}', 'Y', '\u{0304}'), // Ȳ
+    ('\u{0233}', 'y', '\u{0304}'), // ȳ
+    ('\u{1E02}', 'B', '\u{0307}'), // Ḃ
+    ('\u{1E03}', 'b', '\u{0307}'), // ḃ
+    ('\u{1E04}', 'B', '\u{0323}'), // Ḅ
+    ('\u{1E05}', 'b', '\u{0323}'), // ḅ
+    ('\u{1E0A}', 'D', '\u{0307}'), // Ḋ
+    ('\u{1E0B}', 'd', '\u{0307}'), // ḋ
+    ('\u{1E0C}', 'D', '\u{0323}'), // Ḍ
+    ('\u{1E0D}', 'd', '\u{0323}'), // ḍ
+    ('\u{1E10}', 'D', '\u{0327}'), // Ḑ
+    ('\u{1E11}', 'd', '\u{0327}'), // ḑ
+    ('\u{1E1E}', 'F', '\u{0307}'), // Ḟ
+    ('\u{1E1F}', 'f', '\u{0307}'), // ḟ
+    ('\u{1E20}', 'G', '\u{0304}'), // Ḡ
+    ('\u{1E21}', 'g', '\u{0304}'), // ḡ
+    ('\u{1E22}', 'H', '\u{0307}'), // Ḣ
+    ('\u{1E23}', 'h', '\u{0307}'), // ḣ
+    ('\u{1E24}', 'H', '\u{0323}'), // Ḥ
+    ('\u{1E25}', 'h', '\u{0323}'), // ḥ
+    ('\u{1E26}', 'H', '\u{0308}'), // Ḧ
+    ('\u{1E27}', 'h', '\u{0308}'), // ḧ
+    ('\u{1E28}', 'H', '\u{0327}'), // Ḩ
+    ('\u{1E29}', 'h', '\u{0327}'), // ḩ
+    ('\u{1E30}', 'K', '\u{0301}'), // Ḱ
+    ('\u{1E31}', 'k', '\u{0301}'), // ḱ
+    ('\u{1E32}', 'K', '\u{0323}'), // Ḳ
+    ('\u{1E33}', 'k', '\u{0323}'), // ḳ
+    ('\u{1E36}', 'L', '\u{0323}'), // Ḷ
+    ('\u{1E37}', 'l', '\u{0323}'), // ḷ
+    ('\u{1E3E}', 'M', '\u{0301}'), // Ḿ
+    ('\u{1E3F}', 'm', '\u{0301}'), // ḿ
+    ('\u{1E40}', 'M', '\u{0307}'), // Ṁ
+    ('\u{1E41}', 'm', '\u{0307}'), // ṁ
+    ('\u{1E42}', 'M', '\u{0323}'), // Ṃ
+    ('\u{1E43}', 'm', '\u{0323}'), // ṃ
+    ('\u{1E44}', 'N', '\u{0307}'), // Ṅ
+    ('\u{1E45}', 'n', '\u{0307}'), // ṅ
+    ('\u{1E46}', 'N', '\u{0323}'), // Ṇ
+    ('\u{1E47}', 'n', '\u{0323}'), // ṇ
+    ('\u{1E54}', 'P', '\u{0301}'), // Ṕ
+    ('\u{1E55}', 'p', '\u{0301}'), // ṕ
+    ('\u{1E56}', 'P', '\u{0307}'), // Ṗ
+    ('\u{1E57}', 'p', '\u{0307}'), // ṗ
+    ('\u{1E58}', 'R', '\u{0307}'), // Ṙ
+    ('\u{1E59}', 'r', '\u{0307}'), // ṙ
+    ('\u{1E5A}', 'R', '\u{0323}'), // Ṛ
+    ('\u{1E5B}', 'r', '\u{0323}'), // ṛ
+    ('\u{1E60}', 'S', '\u{0307}'), // Ṡ
+    ('\u{1E61}', 's', '\u{0307}'), // ṡ
+    ('\u{1E62}', 'S', '\u{0323}'), // Ṣ
+    ('\u{1E63}', 's', '\u{0323}'), // ṣ
+    ('\u{1E6A}', 'T', '\u{0307}'), // Ṫ
+    ('\u{1E6B}', 't', '\u{0307}'), // ṫ
+    ('\u{1E6C}', 'T', '\u{0323}'), // Ṭ
+    ('\u{1E6D}', 't', '\u{0323}'), // ṭ
+    ('\u{1E7C}', 'V', '\u{0303}'), // Ṽ
+    ('\u{1E7D}', 'v', '\u{0303}'), // ṽ
+    ('\u{1E7E}', 'V', '\u{0323}'), // Ṿ
+    ('\u{1E7F}', 'v', '\u{0323}'), // ṿ
+    ('\u{1E80}', 'W', '\u{0300}'), // Ẁ
+    ('\u{1E81}', 'w', '\u{0300}'), // ẁ
+    ('\u{1E82}', 'W', '\u{0301}'), // Ẃ
+    ('\u{1E83}', 'w', '\u{0301}'), // ẃ
+    ('\u{1E84}', 'W', '\u{0308}'), // Ẅ
+    ('\u{1E85}', 'w', '\u{0308}'), // ẅ
+    ('\u{1E86}', 'W', '\u{0307}'), // Ẇ
+    ('\u{1E87}', 'w', '\u{0307}'), // ẇ
+    ('\u{1E88}', 'W', '\u{0323}'), // Ẉ
+    ('\u{1E89}', 'w', '\u{0323}'), // ẉ
+    ('\u{1E8A}', 'X', '\u{0307}'), // Ẋ
+    ('\u{1E8B}', 'x', '\u{0307}'), // ẋ
+    ('\u{1E8C}', 'X', '\u{0308}'), // Ẍ
+    ('\u{1E8D}', 'x', '\u{0308}'), // ẍ
+    ('\u{1E8E}', 'Y', '\u{0307}'), // Ẏ
+    ('\u{1E8F}', 'y', '\u{0307}'), // ẏ
+    ('\u{1E90}', 'Z', '\u{0302}'), // Ẑ
+    ('\u{1E91}', 'z', '\u{0302}'), // ẑ
+    ('\u{1E92}', 'Z', '\u{0323}'), // Ẓ
+    ('\u{1E93}', 'z', '\u{0323}'), // ẓ
+    ('\u{1E97}', 't', '\u{0308}'), // ẗ
+    ('\u{1E98}', 'w', '\u{030A}'), // ẘ
+    ('\u{1E99}', 'y', '\u{030A}'), // ẙ
+    ('\u{1EA0}', 'A', '\u{0323}'), // Ạ
+    ('\u{1EA1}', 'a', '\u{0323}'), // ạ
+    ('\u{1EB8}', 'E', '\u{0323}'), // Ẹ
+    ('\u{1EB9}', 'e', '\u{0323}'), // ẹ
+    ('\u{1EBC}', 'E', '\u{0303}'), // Ẽ
+    ('\u{1EBD}', 'e', '\u{0303}'), // ẽ
+    ('\u{1ECA}', 'I', '\u{0323}'), // Ị
+    ('\u{1ECB}', 'i', '\u{0323}'), // ị
+    ('\u{1ECC}', 'O', '\u{0323}'), // Ọ
+    ('\u{1ECD}', 'o', '\u{0323}'), // ọ
+    ('\u{1EE4}', 'U', '\u{0323}'), // Ụ
+    ('\u{1EE5}', 'u', '\u{0323}'), // ụ
+    ('\u{1EF2}', 'Y', '\u{0300}'), // Ỳ
+    ('\u{1EF3}', 'y', '\u{0300}'), // ỳ
+    ('\u{1EF4}', 'Y', '\u{0323}'), // Ỵ
+    ('\u{1EF5}', 'y', '\u{0323}'), // ỵ
+    ('\u{1EF8}', 'Y', '\u{0303}'), // Ỹ
+    ('\u{1EF9}', 'y', '\u{0303}'), // ỹ
+];
+
+/// Spacing forms of combining diacritics, tried when the font has no glyph
+/// for the combining character itself.
+fn spacing_forms(mark: char) -> &'static [char] {
+    match mark {
+        '\u{0300}' => &['`'],
+        '\u{0301}' => &['\u{00B4}'],
+        '\u{0302}' => &['\u{02C6}', '^'],
+        '\u{0303}' => &['\u{02DC}', '~'],
+        '\u{0304}' => &['\u{00AF}', '\u{02C9}'],
+        '\u{0306}' => &['\u{02D8}'],
+        '\u{0307}' => &['\u{02D9}'],
+        '\u{0308}' => &['\u{00A8}'],
+        '\u{030A}' => &['\u{02DA}'],
+        '\u{030B}' => &['\u{02DD}'],
+        '\u{030C}' => &['\u{02C7}'],
+        '\u{0326}' => &[','],
+        '\u{0327}' => &['\u{00B8}'],
+        '\u{0328}' => &['\u{02DB}'],
+        _ => &[],
+    }
+}
+
+pub fn decompose(ch: char) -> Option<Decomposition> {
+    let index = DECOMPOSITIONS
+        .binary_search_by_key(&ch, |(letter, _, _)| *letter)
+        .ok()?;
+    let (_, base, mark) = DECOMPOSITIONS[index];
+    let placement = match mark {
+        '\u{0323}' | '\u{0326}' | '\u{0327}' | '\u{0328}' => Placement::Below,
+        '\u{030C}' if matches!(base, 'd' | 't' | 'l' | 'L') => Placement::Right,
+        _ => Placement::Above,
+    };
+    Some(Decomposition {
+        base,
+        mark,
+        placement,
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ComposedGlyph {
+    pub glyph: Glyph,
+    /// Names of the glyphs the result was built from.
+    pub base: String,
+    pub mark: String,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ComposeFailure {
+    pub char: char,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ComposeBatch {
+    pub composed: Vec<ComposedGlyph>,
+    pub failed: Vec<ComposeFailure>,
+}
+
+fn find_glyph(doc: &GtfDocument, ch: char) -> Option<&Glyph> {
+    doc.glyphs
+        .iter()
+        .find(|g| g.codepoint() == Some(ch) || g.char_repr == Some(ch))
+}
+
+/// Base glyph for a decomposition; accents above `i` and `j` prefer the
+/// dotless forms when the font has them.
+fn find_base<'a>(doc: &'a GtfDocument, decomposition: &Decomposition) -> Option<&'a Glyph> {
+    let dotless = match (decomposition.base, decomposition.placement) {
+        ('i', Placement::Above) => Some('\u{0131}'),
+        ('j', Placement::Above) => Some('\u{0237}'),
+        _ => None,
+    };
+    dotless
+        .and_then(|ch| find_glyph(doc, ch))
+        .or_else(|| find_glyph(doc, decomposition.base))
+}
+
+/// Diacritic glyph: the combining character, then its spacing forms. The
+/// attached caron of `ď` and `ľ` looks like an apostrophe, so that is tried first.
+fn find_mark<'a>(doc: &'a GtfDocument, decomposition: &Decomposition) -> Option<&'a Glyph> {
+    let apostrophes: &[char] = match decomposition.placement {
+        Placement::Right => &['\u{2019}', '\''],
+        _ => &[],
+    };
+    apostrophes
+        .iter()
+        .copied()
+        .chain(std::iter::once(decomposition.mark))
+        .chain(spacing_forms(decomposition.mark).iter().copied())
+        .find_map(|ch| find_glyph(doc, ch))
+}
+
+/// Inclusive bounding box of the ink pixels of a bitmap.
+#[derive(Debug, Clone, Copy)]
+struct InkBox {
+    top: usize,
+    left: usize,
+    bottom: usize,
+    right: usize,
+}
+
+impl InkBox {
+    fn width(&self) -> usize {
+        self.right - self.left + 1
+    }
+
+    fn height(&self) -> usize {
+        self.bottom - self.top + 1
+    }
+}
+
+fn ink_box(bitmap: &[String], is_ink: impl Fn(char) -> bool) -> Option<InkBox> {
+    let mut found: Option<InkBox> = None;
+    for (row, line) in bitmap.iter().enumerate() {
+        for (col, ch) in line.chars().enumerate() {
+            if !is_ink(ch) {
+                continue;
+            }
+            found = Some(match found {
+                None => InkBox {
+                    top: row,
+                    left: col,
+                    bottom: row,
+                    right: col,
+                },
+                Some(b) => InkBox {
+                    top: b.top.min(row),
+                    left: b.left.min(col),
+                    bottom: b.bottom.max(row),
+                    right: b.right.max(col),
+                },
+            });
+        }
+    }
+    found
+}
+
+/// Builds the glyph for `ch` from its base letter and diacritic in `doc`.
+/// The glyph is not added to the document; its name is unique in `doc`.
+pub fn compose_glyph(
+    doc: &GtfDocument,
+    ch: char,
+    options: &ComposeOptions,
+) -> Result<ComposedGlyph, String> {
+    compose_with_background(doc, ch, options, background_color(doc))
+}
+
+/// `compose_glyph` with the font background looked up by the caller, once per batch.
+fn compose_with_background(
+    doc: &GtfDocument,
+    ch: char,
+    options: &ComposeOptions,
+    background: Option<Color>,
+) -> Result<ComposedGlyph, String> {
+    if let Some(existing) = find_glyph(doc, ch) {
+        return Err(format!("'{}' already has glyph '{}'", ch, existing.name));
+    }
+    let decomposition = decompose(ch)
+        .ok_or_else(|| format!("No decomposition known for '{}' ({})", ch, unicode_of(ch)))?;
+    let base = find_base(doc, &decomposition)
+        .ok_or_else(|| format!("No glyph for the base letter '{}'", decomposition.base))?;
+    let mark = find_mark(doc, &decomposition).ok_or_else(|| {
+        format!(
+            "No glyph for the diacritic {} or its spacing form",
+            unicode_of(decomposition.mark)
+        )
+    })?;
+    let background = background.ok_or("The font has no colored pixels")?;
+    let fill = background_char(base, doc, background)
+        .ok_or("No palette character for the background color")?;
+    // A trimmed base letter is composed within its whole cell
+    let untrimmed_base = untrim_glyph(base, fill);
+    let base = untrimmed_base.as_ref();
+
+    let is_base_ink = |c: char| pixel_color(c, base, doc).is_some_and(|color| color != background);
+    let is_mark_ink = |c: char| pixel_color(c, mark, doc).is_some_and(|color| color != background);
+    let base_box = ink_box(&base.bitmap, is_base_ink)
+        .ok_or_else(|| format!("Base glyph '{}' is empty", base.name))?;
+    let mark_box = ink_box(&mark.bitmap, is_mark_ink)
+        .ok_or_else(|| format!("Diacritic glyph '{}' is empty", mark.name))?;
+
+    // The result uses the base palette; diacritic colors are mapped into it
+    let base_palette = effective_palette(base, doc);
+    let (mapped_mark, _) = reconcile_glyph_palette(
+        mark,
+        doc.header.default_palette.as_ref(),
+        Some(&base_palette),
+    );
+    let mut palette = base_palette;
+    if let Some(added) = &mapped_mark.palette {
+        palette.entries.extend(added.entries.iter().map(|(k, v)| (*k, *v)));
+    }
+    let palette = strip_inherited(&palette, doc.header.default_palette.as_ref());
+
+    // Position of the diacritic ink in base bitmap coordinates
+    let gap = options.gap as i64;
+    let (mark_w, mark_h) = (mark_box.width() as i64, mark_box.height() as i64);
+    let (base_w, base_left, base_right) = (
+        base_box.width() as i64,
+        base_box.left as i64,
+        base_box.right as i64,
+    );
+    let aligned_left = match options.alignment {
+        Alignment::Center => base_left + (base_w - mark_w).div_euclid(2),
+        Alignment::Left => base_left,
+        Alignment::Right => base_right - mark_w + 1,
+    };
+    let (top, left) = match decomposition.placement {
+        Placement::Above => (base_box.top as i64 - gap - mark_h, aligned_left),
+        Placement::Below => (base_box.bottom as i64 + 1 + gap, aligned_left),
+        Placement::Right => (base_box.top as i64, base_right + 1 + gap),
+    };
+    let left = left + options.offset_x as i64;
+
+    let height = base.bitmap.len() as i64;
+    let width = base
+        .bitmap
+        .iter()
+        .map(|row| row.chars().count())
+        .max()
+        .unwrap_or(0) as i64;
+    let grow_top = (-top).max(0);
+    let grow_left = (-left).max(0);
+    let new_height = height.max(top + mark_h) + grow_top;
+    let new_width = width.max(left + mark_w) + grow_left;
+
+    let mut canvas = vec![vec![fill; new_width as usize]; new_height as usize];
+    for (row, line) in base.bitmap.iter().enumerate() {
+        for (col, c) in line.chars().enumerate() {
+            canvas[row + grow_top as usize][col + grow_left as usize] = c;
+        }
+    }
+
+    let mut overlap = 0;
+    for row in 0..mark_box.height() {
+        let source_row: Vec<char> = mark.bitmap[mark_box.top + row].chars().collect();
+        let mapped_row: Vec<char> = mapped_mark.bitmap[mark_box.top + row].chars().collect();
+        for col in 0..mark_box.width() {
+            // Rows of a ragged bitmap may end inside the ink box
+            let (Some(&source), Some(&mapped)) = (
+                source_row.get(mark_box.left + col),
+                mapped_row.get(mark_box.left + col),
+            ) else {
+                break;
+            };
+            if !is_mark_ink(source) {
+                continue;
+            }
+            let target =
+                &mut canvas[(top + grow_top) as usize + row][(left + grow_left) as usize + col];
+            if is_base_ink(*target) {
+                overlap += 1;
+            }
+            *target = mapped;
+        }
+    }
+
+    let mut warnings = Vec::new();
+    if new_width != width || new_height != height {
+        warnings.push(format!(
+            "The diacritic does not fit, glyph grows from {}x{} to {}x{}",
+            width, height, new_width, new_height
+        ));
+    }
+    if overlap > 0 {
+        warnings.push(format!(
+            "Diacritic overlaps the base letter in {} pixel(s)",
+            overlap
+        ));
+    }
+    if matches!(decomposition.base, 'i' | 'j')
+        && decomposition.placement == Placement::Above
+        && base.codepoint().or(base.char_repr) == Some(decomposition.base)
+    {
+        warnings.push(format!(
+            "No dotless glyph for '{}', the dot of the base letter is kept",
+            decomposition.base
+        ));
+    }
+
+    let mut glyph = blank_glyph_for_char(doc, ch, fill);
+    glyph.size = Some(Size {
+        width: new_width as u32,
+        height: new_height as u32,
+    });
+    glyph.palette = Some(palette);
+    glyph.bitmap = canvas
+        .into_iter()
+        .map(|row| row.into_iter().collect())
+        .collect();
+
+    Ok(ComposedGlyph {
+        glyph,
+        base: base.name.clone(),
+        mark: mark.name.clone(),
+        warnings,
+    })
+}
+
+/// Characters `doc` has no glyph for but could compose from existing glyphs.
+pub fn composable_chars(doc: &GtfDocument) -> Vec<char> {
+    DECOMPOSITIONS
+        .iter()
+        .map(|(letter, _, _)| *letter)
+        .filter(|&ch| find_glyph(doc, ch).is_none())
+        .filter(|&ch| {
+            decompose(ch)
+                .is_some_and(|d| find_base(doc, &d).is_some() && find_mark(doc, &d).is_some())
+        })
+        .collect()
+}
+
+/// Composes glyphs for `chars` in order. A character repeated in `chars` is
+/// reported instead of composed twice. Names of the composed glyphs cannot
+/// collide with each other, every one is named after its own letter.
+pub fn compose_glyphs(doc: &GtfDocument, chars: &[char], options: &ComposeOptions) -> ComposeBatch {
+    let background = background_color(doc);
+    let mut batch = ComposeBatch::default();
+    for &ch in chars {
+        let earlier = batch
+            .composed
+            .iter()
+            .find(|c| c.glyph.char_repr == Some(ch));
+        let result = match earlier {
+            Some(earlier) => Err(format!(
+                "'{}' already has glyph '{}'",
+                ch, earlier.glyph.name
+            )),
+            None => compose_with_background(doc, ch, options, background),
+        };
+        match result {
+            Ok(composed) => batch.composed.push(composed),
+            Err(message) => batch.failed.push(ComposeFailure { char: ch, message }),
+        }
+    }
+    batch
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gtf::test_util::{char_glyph, document};
+
+    fn font(acute: &[&str]) -> GtfDocument {
+        document(vec![
+            char_glyph("A", 'A', &["...", "...", ".#.", "#.#", "###"]),
+            char_glyph("acute", '\u{00B4}', acute),
+        ])
+    }
+
+    #[test]
+    fn places_the_diacritic_above_the_ink() {
+        let doc = font(&["...", ".#.", "..."]);
+        let composed = compose_glyph(&doc, 'Á', &ComposeOptions::default()).unwrap();
+
+        assert_eq!(composed.glyph.name, "Á");
+        assert_eq!(composed.glyph.unicode.as_deref(), Some("U+00C1"));
+        assert_eq!(
+            (composed.base.as_str(), composed.mark.as_str()),
+            ("A", "acute")
+        );
+        assert_eq!(
+            composed.glyph.bitmap,
+            vec![".#.", "...", ".#.", "#.#", "###"]
+        );
+        assert!(composed.warnings.is_empty());
+    }
+
+    #[test]
+    fn grows_when_the_diacritic_does_not_fit() {
+        let doc = font(&["...", ".#.", "..."]);
+        let options = ComposeOptions {
+            gap: 2,
+            ..Default::default()
+        };
+        let composed = compose_glyph(&doc, 'Á', &options).unwrap();
+
+        assert_eq!(
+            composed.glyph.bitmap,
+            vec![".#.", "...", "...", ".#.", "#.#", "###"]
+        );
+        assert_eq!(
+            composed.glyph.size,
+            Some(Size {
+                width: 3,
+                height: 6
+            })
+        );
+        assert_eq!(composed.warnings.len(), 1);
+    }
+
+    #[test]
+    fn ragged_diacritic_rows_are_not_read_past_their_end() {
+        let doc = font(&["", ".#", "#"]);
+        let composed = compose_glyph(&doc, 'Á', &ComposeOptions::default()).unwrap();
+
+        assert_eq!(
+            composed.glyph.bitmap,
+            vec![".#.", "#..", "...", ".#.", "#.#", "###"]
+        );
+    }
+
+    #[test]
+    fn caron_of_d_l_t_goes_right_of_the_letter() {
+        let mut doc = document(vec![
+            char_glyph("d", 'd', &["...#", "...#", ".###", "#..#", "#..#", ".###"]),
+            char_glyph("l", 'l', &["#...", "#...", "#...", "#...", "#...", "##.."]),
+            char_glyph("t", 't', &[".#..", "###.", ".#..", ".#..", ".#..", "..#."]),
+            char_glyph("caron", '\u{02C7}', &["#.#", ".#."]),
+            char_glyph("quotesingle", '\'', &["#", "#"]),
+        ]);
+        let options = ComposeOptions::default();
+
+        // The apostrophe wins over the caron, one gap pixel right of the ink
+        let composed = compose_glyph(&doc, 'ď', &options).unwrap();
+        assert_eq!(composed.mark, "quotesingle");
+        assert_eq!(
+            composed.glyph.bitmap,
+            vec!["...#.#", "...#.#", ".###..", "#..#..", "#..#..", ".###.."]
+        );
+
+        // A typographic apostrophe is preferred to the ASCII one
+        doc.glyphs
+            .push(char_glyph("quoteright", '\u{2019}', &["#", "#"]));
+        let composed = compose_glyph(&doc, 'ľ', &options).unwrap();
+        assert_eq!(composed.mark, "quoteright");
+        assert_eq!(
+            composed.glyph.bitmap,
+            vec!["#..#", "#..#", "#...", "#...", "#...", "##.."]
+        );
+        assert!(composed.warnings.is_empty());
+
+        // Without apostrophes the caron itself is attached to the right
+        doc.glyphs.truncate(4);
+        let composed = compose_glyph(&doc, 'ť', &options).unwrap();
+        assert_eq!(composed.mark, "caron");
+        assert_eq!(composed.glyph.bitmap[..2], [".#..#.#", "###..#."]);
+    }
+
+    #[test]
+    fn accents_on_i_and_j_use_the_dotless_forms() {
+        let mut doc = document(vec![
+            char_glyph("i", 'i', &[".#.", "...", ".#.", ".#.", ".#."]),
+            char_glyph("dotlessi", '\u{0131}', &["...", "...", ".#.", ".#.", ".#."]),
+            char_glyph("j", 'j', &["..#", "...", "..#", "..#", "#.#", ".#."]),
+            char_glyph(
+                "dotlessj",
+                '\u{0237}',
+                &["...", "...", "..#", "..#", "#.#", ".#."],
+            ),
+            char_glyph("acute", '\u{00B4}', &["..#", ".#."]),
+            char_glyph("asciicircum", '^', &[".#.", "#.#"]),
+        ]);
+        let options = ComposeOptions {
+            gap: 0,
+            ..Default::default()
+        };
+
+        let composed = compose_glyph(&doc, 'í', &options).unwrap();
+        assert_eq!(composed.base, "dotlessi");
+        assert_eq!(
+            composed.glyph.bitmap,
+            vec![".#.", "#..", ".#.", ".#.", ".#."]
+        );
+        assert!(composed.warnings.is_empty());
+
+        let composed = compose_glyph(&doc, 'ĵ', &options).unwrap();
+        assert_eq!(
+            (composed.base.as_str(), composed.mark.as_str()),
+            ("dotlessj", "asciicircum")
+        );
+        assert_eq!(
+            composed.glyph.bitmap,
+            vec![".#.", "#.#", "..#", "..#", "#.#", ".#."]
+        );
+
+        // Without the dotless form the dotted letter is used, with a warning
+        doc.glyphs.remove(1);
+        let composed = compose_glyph(&doc, 'í', &options).unwrap();
+        assert_eq!(composed.base, "i");
+        assert!(composed
+            .warnings
+            .iter()
+            .any(|w| w.contains("No dotless glyph for 'i'")));
+    }
+
+    #[test]
+    fn batch_reports_repeated_and_impossible_characters() {
+        let doc = font(&["...", ".#.", "..."]);
+        let batch = compose_glyphs(&doc, &['Á', 'Á', 'É', 'A'], &ComposeOptions::default());
+
+        assert_eq!(batch.composed.len(), 1);
+        let failed: Vec<char> = batch.failed.iter().map(|f| f.char).collect();
+        assert_eq!(failed, vec!['Á', 'É', 'A']);
+        assert_eq!(batch.failed[0].message, "'Á' already has glyph 'Á'");
+        assert_eq!(composable_chars(&doc), vec!['Á']);
+    }
+}
@@ -0,0 +1,577 @@
+//! Semantic comparison of two GTF documents.
+//!
+//! Glyphs are paired in three passes: by name, then by codepoint (`UNICODE`,
+//! a renamed glyph), then by bitmap similarity for whatever is left (a glyph
+//! renamed and re-encoded, but drawn the same). Unpaired glyphs are reported
+//! as added or removed. Paired glyphs report field, palette and per-pixel
+//! changes; unchanged pairs are left out.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+use serde::Serialize;
+
+use super::color::Color;
+use super::types::{Glyph, GtfDocument, GtfHeader, Palette};
+
+/// Minimum share of equal pixels for two glyphs to be paired by bitmap.
+pub const BITMAP_MATCH_THRESHOLD: f32 = 0.9;
+
+/// A changed scalar field (header field or glyph metadata).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+/// A palette entry that was added (`old` is `None`), removed (`new` is `None`) or recolored.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PaletteEntryChange {
+    pub char: char,
+    pub old: Option<Color>,
+    pub new: Option<Color>,
+}
+
+/// A pixel whose character differs. `None` means the pixel lies outside
+/// the bitmap on that side (the glyph was resized).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PixelChange {
+    pub row: usize,
+    pub col: usize,
+    pub old: Option<char>,
+    pub new: Option<char>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GlyphChangeKind {
+    Added,
+    Removed,
+    Modified,
+    /// Paired with a glyph of another name; may carry other changes as well.
+    Renamed,
+}
+
+/// How an old and a new glyph were paired.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GlyphMatch {
+    Name,
+    Codepoint,
+    Bitmap,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GlyphDiff {
+    pub kind: GlyphChangeKind,
+    pub old_name: Option<String>,
+    pub new_name: Option<String>,
+    pub matched_by: Option<GlyphMatch>,
+    pub fields: Vec<FieldChange>,
+    pub palette: Vec<PaletteEntryChange>,
+    pub pixels: Vec<PixelChange>,
+}
+
+impl GlyphDiff {
+    /// Name of the glyph in the new document, or in the old one if it was removed.
+    pub fn display_name(&self) -> &str {
+        self.new_name
+            .as_deref()
+            .or(self.old_name.as_deref())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DocumentDiff {
+    pub header: Vec<FieldChange>,
+    pub default_palette: Vec<PaletteEntryChange>,
+    /// Ordered by position in the new document, removed glyphs last.
+    pub glyphs: Vec<GlyphDiff>,
+}
+
+impl DocumentDiff {
+    pub fn is_empty(&self) -> bool {
+        self.header.is_empty() && self.default_palette.is_empty() && self.glyphs.is_empty()
+    }
+}
+
+/// Human-readable summary, one line per change (`+` added, `-` removed,
+/// `~` modified, `>` renamed).
+impl fmt::Display for DocumentDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |v: &Option<String>| v.clone().unwrap_or_else(|| "(none)".to_string());
+        for change in &self.header {
+            writeln!(
+                f,
+                "~ header {}: {} -> {}",
+                change.field,
+                show(&change.old),
+                show(&change.new)
+            )?;
+        }
+        for change in &self.default_palette {
+            writeln!(
+                f,
+                "~ default palette '{}': {} -> {}",
+                change.char,
+                show(&change.old.map(|c| c.to_string())),
+                show(&change.new.map(|c| c.to_string()))
+            )?;
+        }
+        for glyph in &self.glyphs {
+            match glyph.kind {
+                GlyphChangeKind::Added => writeln!(f, "+ {}", glyph.display_name())?,
+                GlyphChangeKind::Removed => writeln!(f, "- {}", glyph.display_name())?,
+                GlyphChangeKind::Modified | GlyphChangeKind::Renamed => {
+                    if glyph.kind == GlyphChangeKind::Renamed {
+                        write!(
+                            f,
+                            "> {} -> {}",
+                            show(&glyph.old_name),
+                            show(&glyph.new_name)
+                        )?;
+                    } else {
+                        write!(f, "~ {}", glyph.display_name())?;
+                    }
+                    let mut details: Vec<String> = glyph
+                        .fields
+                        .iter()
+                        .filter(|c| c.field != "name")
+                        .map(|c| format!("{}: {} -> {}", c.field, show(&c.old), show(&c.new)))
+                        .collect();
+                    if !glyph.palette.is_empty() {
+                        details.push(format!("{} palette entries", glyph.palette.len()));
+                    }
+                    if !glyph.pixels.is_empty() {
+                        details.push(format!("{} pixels", glyph.pixels.len()));
+                    }
+                    if details.is_empty() {
+                        writeln!(f)?;
+                    } else {
+                        writeln!(f, " ({})", details.join(", "))?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compares `old` with `new`.
+pub fn diff_documents(old: &GtfDocument, new: &GtfDocument) -> DocumentDiff {
+    let pairs = pair_glyphs(&old.glyphs, &new.glyphs);
+
+    let mut glyphs = Vec::new();
+    let mut paired_old: BTreeSet<usize> = BTreeSet::new();
+    for (new_index, new_glyph) in new.glyphs.iter().enumerate() {
+        match pairs.get(&new_index) {
+            Some(&(old_index, matched_by)) => {
+                paired_old.insert(old_index);
+                if let Some(diff) = diff_glyphs(&old.glyphs[old_index], new_glyph, matched_by) {
+                    glyphs.push(diff);
+                }
+            }
+            None => glyphs.push(GlyphDiff {
+                kind: GlyphChangeKind::Added,
+                old_name: None,
+                new_name: Some(new_glyph.name.clone()),
+                matched_by: None,
+                fields: Vec::new(),
+                palette: Vec::new(),
+                pixels: Vec::new(),
+            }),
+        }
+    }
+    for (old_index, old_glyph) in old.glyphs.iter().enumerate() {
+        if !paired_old.contains(&old_index) {
+            glyphs.push(GlyphDiff {
+                kind: GlyphChangeKind::Removed,
+                old_name: Some(old_glyph.name.clone()),
+                new_name: None,
+                matched_by: None,
+                fields: Vec::new(),
+                palette: Vec::new(),
+                pixels: Vec::new(),
+            });
+        }
+    }
+
+    DocumentDiff {
+        header: diff_header(&old.header, &new.header),
+        default_palette: diff_palettes(
+            old.header.default_palette.as_ref(),
+            new.header.default_palette.as_ref(),
+        ),
+        glyphs,
+    }
+}
+
+/// Compares two paired glyphs. Returns `None` when they are identical.
+pub fn diff_glyphs(old: &Glyph, new: &Glyph, matched_by: GlyphMatch) -> Option<GlyphDiff> {
+    let mut fields = Vec::new();
+    push_field(&mut fields, "name", Some(&old.name), Some(&new.name));
+    push_field(
+        &mut fields,
+        "unicode",
+        old.unicode.as_ref(),
+        new.unicode.as_ref(),
+    );
+    push_field(
+        &mut fields,
+        "char_repr",
+        old.char_repr.map(String::from).as_ref(),
+        new.char_repr.map(String::from).as_ref(),
+    );
+    push_field(
+        &mut fields,
+        "size",
+        old.size
+            .as_ref()
+            .map(|s| format!("{}x{}", s.width, s.height))
+            .as_ref(),
+        new.size
+            .as_ref()
+            .map(|s| format!("{}x{}", s.width, s.height))
+            .as_ref(),
+    );
+    push_field(
+        &mut fields,
+        "offset",
+        old.offset.map(|o| format!("{},{}", o.x, o.y)).as_ref(),
+        new.offset.map(|o| format!("{},{}", o.x, o.y)).as_ref(),
+    );
+    push_field(
+        &mut fields,
+        "cell",
+        old.cell
+            .as_ref()
+            .map(|s| format!("{}x{}", s.width, s.height))
+            .as_ref(),
+        new.cell
+            .as_ref()
+            .map(|s| format!("{}x{}", s.width, s.height))
+            .as_ref(),
+    );
+
+    let palette = diff_palettes(old.palette.as_ref(), new.palette.as_ref());
+    let pixels = diff_bitmaps(&old.bitmap, &new.bitmap);
+    if fields.is_empty() && palette.is_empty() && pixels.is_empty() {
+        return None;
+    }
+
+    let kind = if old.name != new.name {
+        GlyphChangeKind::Renamed
+    } else {
+        GlyphChangeKind::Modified
+    };
+    Some(GlyphDiff {
+        kind,
+        old_name: Some(old.name.clone()),
+        new_name: Some(new.name.clone()),
+        matched_by: Some(matched_by),
+        fields,
+        palette,
+        pixels,
+    })
+}
+
+/// Per-pixel comparison over the union of both bitmap areas.
+pub fn diff_bitmaps(old: &[String], new: &[String]) -> Vec<PixelChange> {
+    let old_rows: Vec<Vec<char>> = old.iter().map(|r| r.chars().collect()).collect();
+    let new_rows: Vec<Vec<char>> = new.iter().map(|r| r.chars().collect()).collect();
+
+    let mut changes = Vec::new();
+    for row in 0..old_rows.len().max(new_rows.len()) {
+        let old_row = old_rows.get(row).map(Vec::as_slice).unwrap_or_default();
+        let new_row = new_rows.get(row).map(Vec::as_slice).unwrap_or_default();
+        for col in 0..old_row.len().max(new_row.len()) {
+            let (old_char, new_char) = (old_row.get(col).copied(), new_row.get(col).copied());
+            if old_char != new_char {
+                changes.push(PixelChange {
+                    row,
+                    col,
+                    old: old_char,
+                    new: new_char,
+                });
+            }
+        }
+    }
+    changes
+}
+
+/// Compares palette entries by color value.
+pub fn diff_palettes(old: Option<&Palette>, new: Option<&Palette>) -> Vec<PaletteEntryChange> {
+    let old_entries = old.map(|p| &p.entries);
+    let new_entries = new.map(|p| &p.entries);
+    let chars: BTreeSet<char> = old_entries
+        .into_iter()
+        .chain(new_entries)
+        .flat_map(|entries| entries.keys().copied())
+        .collect();
+
+    chars
+        .into_iter()
+        .filter_map(|ch| {
+            let old_color = old_entries.and_then(|e| e.get(&ch));
+            let new_color = new_entries.and_then(|e| e.get(&ch));
+            (old_color != new_color).then(|| PaletteEntryChange {
+                char: ch,
+                old: old_color.copied(),
+                new: new_color.copied(),
+            })
+        })
+        .collect()
+}
+
+fn diff_header(old: &GtfHeader, new: &GtfHeader) -> Vec<FieldChange> {
+    let size = |h: &GtfHeader| {
+        h.default_size
+            .as_ref()
+            .map(|s| format!("{}x{}", s.width, s.height))
+    };
+    let mut fields = Vec::new();
+    push_field(
+        &mut fields,
+        "font_name",
+        old.font_name.as_ref(),
+        new.font_name.as_ref(),
+    );
+    push_field(
+        &mut fields,
+        "version",
+        old.version.as_ref(),
+        new.version.as_ref(),
+    );
+    push_field(
+        &mut fields,
+        "author",
+        old.author.as_ref(),
+        new.author.as_ref(),
+    );
+    push_field(
+        &mut fields,
+        "description",
+        old.description.as_ref(),
+        new.description.as_ref(),
+    );
+    push_field(
+        &mut fields,
+        "default_size",
+        size(old).as_ref(),
+        size(new).as_ref(),
+    );
+    push_field(
+        &mut fields,
+        "background",
+        old.roles.background.map(String::from).as_ref(),
+        new.roles.background.map(String::from).as_ref(),
+    );
+    push_field(
+        &mut fields,
+        "foreground",
+        old.roles.foreground.map(String::from).as_ref(),
+        new.roles.foreground.map(String::from).as_ref(),
+    );
+    fields
+}
+
+fn push_field(
+    fields: &mut Vec<FieldChange>,
+    field: &str,
+    old: Option<&String>,
+    new: Option<&String>,
+) {
+    if old != new {
+        fields.push(FieldChange {
+            field: field.to_string(),
+            old: old.cloned(),
+            new: new.cloned(),
+        });
+    }
+}
+
+/// Pairs glyphs of the new document (by index) with glyphs of the old one.
+fn pair_glyphs(old: &[Glyph], new: &[Glyph]) -> HashMap<usize, (usize, GlyphMatch)> {
+    let mut pairs: HashMap<usize, (usize, GlyphMatch)> = HashMap::new();
+    let mut old_free: BTreeSet<usize> = (0..old.len()).collect();
+
+    // 1. Same name
+    let old_by_name: HashMap<&str, usize> = old
+        .iter()
+        .enumerate()
+        .map(|(i, g)| (g.name.as_str(), i))
+        .collect();
+    for (new_index, glyph) in new.iter().enumerate() {
+        if let Some(&old_index) = old_by_name.get(glyph.name.as_str()) {
+            if old_free.remove(&old_index) {
+                pairs.insert(new_index, (old_index, GlyphMatch::Name));
+            }
+        }
+    }
+
+    // 2. Same codepoint
+    let mut old_by_codepoint: BTreeMap<String, usize> = BTreeMap::new();
+    for &old_index in &old_free {
+        if let Some(unicode) = &old[old_index].unicode {
+            old_by_codepoint
+                .entry(unicode.to_uppercase())
+                .or_insert(old_index);
+        }
+    }
+    for (new_index, glyph) in new.iter().enumerate() {
+        if pairs.contains_key(&new_index) {
+            continue;
+        }
+        let Some(unicode) = &glyph.unicode else {
+            continue;
+        };
+        if let Some(old_index) = old_by_codepoint.remove(&unicode.to_uppercase()) {
+            old_free.remove(&old_index);
+            pairs.insert(new_index, (old_index, GlyphMatch::Codepoint));
+        }
+    }
+
+    // 3. Similar bitmap, best pairs first
+    let mut candidates: Vec<(f32, usize, usize)> = Vec::new();
+    for (new_index, glyph) in new.iter().enumerate() {
+        if pairs.contains_key(&new_index) {
+            continue;
+        }
+        for &old_index in &old_free {
+            let score = bitmap_similarity(&old[old_index].bitmap, &glyph.bitmap);
+            if score >= BITMAP_MATCH_THRESHOLD {
+                candidates.push((score, new_index, old_index));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)).then(a.2.cmp(&b.2)));
+    for (_, new_index, old_index) in candidates {
+        if !pairs.contains_key(&new_index) && old_free.remove(&old_index) {
+            pairs.insert(new_index, (old_index, GlyphMatch::Bitmap));
+        }
+    }
+
+    pairs
+}
+
+/// Share of equal pixels of two bitmaps of the same size. Bitmaps of
+/// different sizes and blank bitmaps (a single character) score 0.0, otherwise
+/// every pair of empty glyphs would match.
+fn bitmap_similarity(a: &[String], b: &[String]) -> f32 {
+    if a.len() != b.len() || is_blank(a) || is_blank(b) {
+        return 0.0;
+    }
+    let mut total = 0usize;
+    let mut equal = 0usize;
+    for (row_a, row_b) in a.iter().zip(b) {
+        if row_a.chars().count() != row_b.chars().count() {
+            return 0.0;
+        }
+        for (ca, cb) in row_a.chars().zip(row_b.chars()) {
+            total += 1;
+            if ca == cb {
+                equal += 1;
+            }
+        }
+    }
+    if total == 0 {
+        0.0
+    } else {
+        equal as f32 / total as f32
+    }
+}
+
+fn is_blank(bitmap: &[String]) -> bool {
+    let mut chars = bitmap.iter().flat_map(|row| row.chars());
+    match chars.next() {
+        Some(first) => chars.all(|c| c == first),
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gtf::test_util::{char_glyph, color, document, glyph, palette};
+
+    #[test]
+    fn identical_documents_have_no_diff() {
+        let doc = document(vec![glyph("A", &["#.", ".#"])]);
+        let diff = diff_documents(&doc, &doc.clone());
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "");
+    }
+
+    #[test]
+    fn reports_changed_pixels_of_glyphs_paired_by_name() {
+        let old = document(vec![glyph("A", &["#.", ".#"])]);
+        let new = document(vec![glyph("A", &["##", ".#", "#."])]);
+        let diff = diff_documents(&old, &new);
+
+        assert_eq!(diff.glyphs.len(), 1);
+        let glyph = &diff.glyphs[0];
+        assert_eq!(glyph.kind, GlyphChangeKind::Modified);
+        assert_eq!(glyph.matched_by, Some(GlyphMatch::Name));
+        let pixels: Vec<(usize, usize, Option<char>)> =
+            glyph.pixels.iter().map(|p| (p.row, p.col, p.new)).collect();
+        assert_eq!(
+            pixels,
+            vec![(0, 1, Some('#')), (2, 0, Some('#')), (2, 1, Some('.'))]
+        );
+        assert_eq!(glyph.fields[0].field, "size");
+    }
+
+    #[test]
+    fn pairs_renamed_glyphs_by_codepoint_then_bitmap() {
+        let old = document(vec![
+            char_glyph("A", 'A', &["##", "#."]),
+            glyph("Dot", &["..", ".#"]),
+        ]);
+        let new = document(vec![
+            char_glyph("LatinA", 'A', &["##", "#."]),
+            char_glyph("period", '.', &["..", ".#"]),
+        ]);
+        let diff = diff_documents(&old, &new);
+
+        assert_eq!(diff.glyphs.len(), 2);
+        assert_eq!(diff.glyphs[0].kind, GlyphChangeKind::Renamed);
+        assert_eq!(diff.glyphs[0].matched_by, Some(GlyphMatch::Codepoint));
+        assert_eq!(diff.glyphs[1].kind, GlyphChangeKind::Renamed);
+        assert_eq!(diff.glyphs[1].matched_by, Some(GlyphMatch::Bitmap));
+        assert_eq!(diff.glyphs[1].old_name.as_deref(), Some("Dot"));
+        assert!(diff.to_string().starts_with("> A -> LatinA\n"));
+    }
+
+    #[test]
+    fn blank_glyphs_are_not_paired_by_bitmap() {
+        let old = document(vec![glyph("space", &["..", ".."])]);
+        let new = document(vec![glyph("blank", &["..", ".."])]);
+        let diff = diff_documents(&old, &new);
+
+        let kinds: Vec<GlyphChangeKind> = diff.glyphs.iter().map(|g| g.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![GlyphChangeKind::Added, GlyphChangeKind::Removed]
+        );
+        assert_eq!(diff.to_string(), "+ blank\n- space\n");
+    }
+
+    #[test]
+    fn reports_header_and_palette_changes() {
+        let old = document(vec![glyph("A", &["#"])]);
+        let mut new = old.clone();
+        new.header.author = Some("Someone".to_string());
+        new.header.default_palette = Some(palette(&[('.', "#000000"), ('#', "#FF0000")]));
+        new.glyphs[0].palette = Some(palette(&[('+', "#00FF00")]));
+        let diff = diff_documents(&old, &new);
+
+        assert_eq!(diff.header.len(), 1);
+        assert_eq!(diff.header[0].field, "author");
+        assert_eq!(diff.default_palette.len(), 1);
+        assert_eq!(diff.default_palette[0].char, '#');
+        assert_eq!(diff.default_palette[0].new, Some(color("#FF0000")));
+        assert_eq!(diff.glyphs[0].palette[0].old, None);
+    }
+}
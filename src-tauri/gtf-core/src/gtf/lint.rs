@@ -0,0 +1,826 @@
+//! Rule-based checks over a whole document.
+//!
+//! Unlike the per-line warnings collected while parsing, the rules here look
+//! at the font as a whole (duplicates across glyphs, consistency with the
+//! header, palette usage, baseline). Every rule has an id, a default severity
+//! and some have an automatic fix.
+//!
+//! Severities can be changed, or rules turned off, per project with a
+//! `.gtflint.json` file in the font directory or any of its parents:
+//!
+//! ```json
+//! { "rules": { "unused-palette-entry": "off", "height-mismatch": "error" } }
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::color::Color;
+use super::palette::{background_color, effective_color, pixel_color};
+use super::types::{Glyph, GtfDocument, Offset, Size};
+
+/// Name of the per-project configuration file.
+pub const CONFIG_FILE_NAME: &str = ".gtflint.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Off,
+    Info,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Off => "off",
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// Description of a rule for the UI and `--help` style listings.
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleInfo {
+    pub id: &'static str,
+    pub default_severity: Severity,
+    pub description: &'static str,
+    pub fixable: bool,
+}
+
+pub const RULES: &[RuleInfo] = &[
+    RuleInfo {
+        id: "duplicate-name",
+        default_severity: Severity::Error,
+        description: "Two or more glyphs share a name",
+        fixable: true,
+    },
+    RuleInfo {
+        id: "duplicate-codepoint",
+        default_severity: Severity::Warning,
+        description: "Two or more glyphs are mapped to the same UNICODE codepoint",
+        fixable: false,
+    },
+    RuleInfo {
+        id: "char-unicode-mismatch",
+        default_severity: Severity::Error,
+        description: "CHAR is not the character encoded by UNICODE, or UNICODE is invalid",
+        fixable: true,
+    },
+    RuleInfo {
+        id: "height-mismatch",
+        default_severity: Severity::Warning,
+        description: "Glyph cell height differs from the header DEFAULT_SIZE",
+        fixable: false,
+    },
+    RuleInfo {
+        id: "size-mismatch",
+        default_severity: Severity::Warning,
+        description: "Glyph SIZE differs from the dimensions of its bitmap",
+        fixable: true,
+    },
+    RuleInfo {
+        id: "unused-palette-entry",
+        default_severity: Severity::Info,
+        description: "Palette entry is not used by any bitmap pixel or repeats the default palette",
+        fixable: true,
+    },
+    RuleInfo {
+        id: "undefined-palette-char",
+        default_severity: Severity::Error,
+        description: "Bitmap uses a character with no color in the glyph or default palette",
+        fixable: false,
+    },
+    RuleInfo {
+        id: "empty-glyph",
+        default_severity: Severity::Warning,
+        description: "Glyph has no pixels, or a non-space glyph uses a single character",
+        fixable: false,
+    },
+    RuleInfo {
+        id: "inconsistent-baseline",
+        default_severity: Severity::Warning,
+        description: "Capital letter or digit ends at a different row than most others",
+        fixable: false,
+    },
+];
+
+pub fn rule_info(id: &str) -> Option<&'static RuleInfo> {
+    RULES.iter().find(|r| r.id == id)
+}
+
+/// Per-project rule settings. Rules that are not listed keep their default severity.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LintConfig {
+    pub rules: BTreeMap<String, Severity>,
+}
+
+impl LintConfig {
+    pub fn severity(&self, rule: &str) -> Severity {
+        self.rules
+            .get(rule)
+            .copied()
+            .or_else(|| rule_info(rule).map(|r| r.default_severity))
+            .unwrap_or(Severity::Off)
+    }
+
+    /// Reads a configuration file. Unknown rule ids are an error, so typos do
+    /// not silently leave a rule enabled.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read file '{}': {}", path.display(), e))?;
+        let config: LintConfig = serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid lint configuration '{}': {}", path.display(), e))?;
+        config.check_rule_ids()?;
+        Ok(config)
+    }
+
+    /// Finds the nearest `.gtflint.json` for a font file, starting in its directory.
+    pub fn discover(font_path: &Path) -> Result<Option<(PathBuf, Self)>, String> {
+        for dir in font_path.ancestors().skip(1) {
+            let candidate = dir.join(CONFIG_FILE_NAME);
+            if candidate.is_file() {
+                return Ok(Some((candidate.clone(), Self::load(&candidate)?)));
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn check_rule_ids(&self) -> Result<(), String> {
+        match self.rules.keys().find(|id| rule_info(id).is_none()) {
+            Some(id) => Err(format!("Unknown lint rule: '{}'", id)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LintFinding {
+    pub rule: String,
+    pub severity: Severity,
+    /// Affected glyph, `None` for findings about the header.
+    pub glyph: Option<String>,
+    pub message: String,
+    /// Whether `fix_document` can resolve this finding.
+    pub fixable: bool,
+}
+
+impl fmt::Display for LintFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}[{}]", self.severity, self.rule)?;
+        if let Some(glyph) = &self.glyph {
+            write!(f, " glyph '{}'", glyph)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// Collects the findings of one rule.
+struct Collector<'a> {
+    rule: &'static str,
+    severity: Severity,
+    findings: &'a mut Vec<LintFinding>,
+}
+
+impl Collector<'_> {
+    fn report(&mut self, glyph: Option<&str>, message: String, fixable: bool) {
+        self.findings.push(LintFinding {
+            rule: self.rule.to_string(),
+            severity: self.severity,
+            glyph: glyph.map(str::to_string),
+            message,
+            fixable,
+        });
+    }
+}
+
+type Check = fn(&GtfDocument, &mut Collector);
+type Fix = fn(&mut GtfDocument);
+
+/// Every rule with its check and, for fixable rules, the fix.
+const CHECKS: &[(&str, Check, Option<Fix>)] = &[
+    (
+        "duplicate-name",
+        check_duplicate_names,
+        Some(fix_duplicate_names),
+    ),
+    ("duplicate-codepoint", check_duplicate_codepoints, None),
+    (
+        "char-unicode-mismatch",
+        check_char_unicode,
+        Some(fix_char_unicode),
+    ),
+    ("height-mismatch", check_height, None),
+    ("size-mismatch", check_size, Some(fix_size)),
+    (
+        "unused-palette-entry",
+        check_unused_palette_entries,
+        Some(fix_unused_palette_entries),
+    ),
+    (
+        "undefined-palette-char",
+        check_undefined_palette_chars,
+        None,
+    ),
+    ("empty-glyph", check_empty_glyphs, None),
+    ("inconsistent-baseline", check_baseline, None),
+];
+
+/// Runs all enabled rules and returns their findings, rule by rule.
+pub fn lint_document(doc: &GtfDocument, config: &LintConfig) -> Vec<LintFinding> {
+    let mut findings = Vec::new();
+    for (rule, check, _) in CHECKS {
+        let severity = config.severity(rule);
+        if severity == Severity::Off {
+            continue;
+        }
+        check(
+            doc,
+            &mut Collector {
+                rule,
+                severity,
+                findings: &mut findings,
+            },
+        );
+    }
+    findings
+}
+
+/// Applies the automatic fixes of the enabled rules (all of them, or only
+/// those in `rules`) and returns the findings that were fixed.
+pub fn fix_document(
+    doc: &mut GtfDocument,
+    config: &LintConfig,
+    rules: Option<&[String]>,
+) -> Vec<LintFinding> {
+    let selected = |rule: &str| rules.is_none_or(|r| r.iter().any(|id| id == rule));
+    let mut fixed = Vec::new();
+    for (rule, check, fix) in CHECKS {
+        let severity = config.severity(rule);
+        let Some(fix) = fix else {
+            continue;
+        };
+        if severity == Severity::Off || !selected(rule) {
+            continue;
+        }
+        // Checked right before fixing, so the findings reflect the fixes of earlier rules
+        let mut findings = Vec::new();
+        check(
+            doc,
+            &mut Collector {
+                rule,
+                severity,
+                findings: &mut findings,
+            },
+        );
+        findings.retain(|f| f.fixable);
+        if !findings.is_empty() {
+            fix(doc);
+            fixed.extend(findings);
+        }
+    }
+    fixed
+}
+
+/// Indices of glyphs whose name is already used by an earlier glyph.
+fn duplicate_names(doc: &GtfDocument) -> Vec<usize> {
+    let mut seen: BTreeSet<&str> = BTreeSet::new();
+    doc.glyphs
+        .iter()
+        .enumerate()
+        .filter(|(_, glyph)| !seen.insert(&glyph.name))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+fn check_duplicate_names(doc: &GtfDocument, out: &mut Collector) {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for glyph in &doc.glyphs {
+        *counts.entry(&glyph.name).or_default() += 1;
+    }
+    for index in duplicate_names(doc) {
+        let name = &doc.glyphs[index].name;
+        out.report(
+            Some(name),
+            format!(
+                "Name is used by {} glyphs",
+                counts.get(name.as_str()).copied().unwrap_or(0)
+            ),
+            true,
+        );
+    }
+}
+
+fn fix_duplicate_names(doc: &mut GtfDocument) {
+    let mut taken: BTreeSet<String> = doc.glyphs.iter().map(|g| g.name.clone()).collect();
+    for index in duplicate_names(doc) {
+        let glyph = &mut doc.glyphs[index];
+        let new_name = (2..)
+            .map(|n| format!("{}_{}", glyph.name, n))
+            .find(|candidate| !taken.contains(candidate))
+            .unwrap_or_default();
+        taken.insert(new_name.clone());
+        glyph.name = new_name;
+    }
+}
+
+fn check_duplicate_codepoints(doc: &GtfDocument, out: &mut Collector) {
+    let mut first: HashMap<String, &str> = HashMap::new();
+    for glyph in &doc.glyphs {
+        let Some(unicode) = &glyph.unicode else {
+            continue;
+        };
+        // Compare the decoded value so that U+41 and U+0041 are the same codepoint
+        let key = match glyph.codepoint() {
+            Some(c) => format!("U+{:04X}", c as u32),
+            None => unicode.trim().to_uppercase(),
+        };
+        match first.get(&key) {
+            Some(owner) => out.report(
+                Some(&glyph.name),
+                format!("{} is already mapped to glyph '{}'", key, owner),
+                false,
+            ),
+            None => {
+                first.insert(key, &glyph.name);
+            }
+        }
+    }
+}
+
+/// `CHAR` to write instead of the current one, when it disagrees with a valid `UNICODE`.
+fn expected_char(glyph: &Glyph) -> Option<char> {
+    let expected = glyph.codepoint()?;
+    glyph
+        .char_repr
+        .is_some_and(|c| c != expected)
+        .then_some(expected)
+}
+
+fn check_char_unicode(doc: &GtfDocument, out: &mut Collector) {
+    for glyph in &doc.glyphs {
+        let Some(unicode) = &glyph.unicode else {
+            continue;
+        };
+        if glyph.codepoint().is_none() {
+            out.report(
+                Some(&glyph.name),
+                format!("UNICODE '{}' is not a valid codepoint", unicode),
+                false,
+            );
+        } else if let Some(expected) = expected_char(glyph) {
+            out.report(
+                Some(&glyph.name),
+                format!(
+                    "CHAR {:?} does not match UNICODE {} ({:?})",
+                    glyph.char_repr.unwrap_or_default(),
+                    unicode,
+                    expected
+                ),
+                true,
+            );
+        }
+    }
+}
+
+fn fix_char_unicode(doc: &mut GtfDocument) {
+    for glyph in &mut doc.glyphs {
+        if let Some(expected) = expected_char(glyph) {
+            glyph.char_repr = Some(expected);
+        }
+    }
+}
+
+/// Rows of the glyph cell: `CELL` height, otherwise the bitmap rows below
+/// `OFFSET` (trimmed glyphs keep their cell height).
+fn cell_height(glyph: &Glyph) -> usize {
+    match &glyph.cell {
+        Some(cell) => cell.height as usize,
+        None => glyph.offset.map_or(0, |o| o.y.max(0) as usize) + glyph.bitmap.len(),
+    }
+}
+
+fn check_height(doc: &GtfDocument, out: &mut Collector) {
+    let Some(default_size) = &doc.header.default_size else {
+        return;
+    };
+    for glyph in &doc.glyphs {
+        let height = cell_height(glyph) as u32;
+        if height != default_size.height {
+            out.report(
+                Some(&glyph.name),
+                format!(
+                    "Glyph has {} rows, DEFAULT_SIZE height is {}",
+                    height, default_size.height
+                ),
+                false,
+            );
+        }
+    }
+}
+
+/// Dimensions of the bitmap (widest row) and whether all rows are equally wide.
+fn bitmap_size(glyph: &Glyph) -> (Size, bool) {
+    let widths: BTreeSet<usize> = glyph.bitmap.iter().map(|r| r.chars().count()).collect();
+    let size = Size {
+        width: widths.iter().next_back().copied().unwrap_or(0) as u32,
+        height: glyph.bitmap.len() as u32,
+    };
+    (size, widths.len() <= 1)
+}
+
+fn check_size(doc: &GtfDocument, out: &mut Collector) {
+    for glyph in &doc.glyphs {
+        let Some(size) = &glyph.size else {
+            continue;
+        };
+        let (actual, uniform) = bitmap_size(glyph);
+        if *size == actual {
+            continue;
+        }
+        // Ragged rows have no single correct SIZE, those are left to the user
+        out.report(
+            Some(&glyph.name),
+            format!(
+                "SIZE {}x{} does not match the {}x{} bitmap",
+                size.width, size.height, actual.width, actual.height
+            ),
+            uniform,
+        );
+    }
+}
+
+fn fix_size(doc: &mut GtfDocument) {
+    for glyph in &mut doc.glyphs {
+        let (actual, uniform) = bitmap_size(glyph);
+        if uniform && glyph.size.as_ref().is_some_and(|size| *size != actual) {
+            glyph.size = Some(actual);
+        }
+    }
+}
+
+fn used_chars(glyph: &Glyph) -> BTreeSet<char> {
+    glyph.bitmap.iter().flat_map(|row| row.chars()).collect()
+}
+
+/// Palette entries no pixel uses, and glyph palette entries equal to the
+/// default palette, as (glyph index, character, color). The glyph index is
+/// `None` for entries of the default palette.
+fn unused_palette_entries(doc: &GtfDocument) -> Vec<(Option<usize>, char, Color)> {
+    let default_palette = doc.header.default_palette.as_ref();
+    let mut used_anywhere: BTreeSet<char> = BTreeSet::new();
+    let mut unused_entries = Vec::new();
+
+    for (index, glyph) in doc.glyphs.iter().enumerate() {
+        let used = used_chars(glyph);
+        used_anywhere.extend(&used);
+        let Some(palette) = &glyph.palette else {
+            continue;
+        };
+        // A glyph palette only holds overrides, an entry repeating the default
+        // palette changes nothing even when the bitmap uses it
+        let inherited = |ch: &char, color: &Color| {
+            default_palette
+                .and_then(|p| p.entries.get(ch))
+                .is_some_and(|c| c == color)
+        };
+        let mut unused: Vec<(char, Color)> = palette
+            .entries
+            .iter()
+            .filter(|(ch, color)| !used.contains(ch) || inherited(ch, color))
+            .map(|(ch, color)| (*ch, *color))
+            .collect();
+        unused.sort();
+        unused_entries.extend(
+            unused
+                .into_iter()
+                .map(|(ch, color)| (Some(index), ch, color)),
+        );
+    }
+
+    if let Some(palette) = default_palette {
+        let mut unused: Vec<(char, Color)> = palette
+            .entries
+            .iter()
+            .filter(|(ch, _)| !used_anywhere.contains(ch) && !doc.header.roles.contains(**ch))
+            .map(|(ch, color)| (*ch, *color))
+            .collect();
+        unused.sort();
+        unused_entries.extend(unused.into_iter().map(|(ch, color)| (None, ch, color)));
+    }
+    unused_entries
+}
+
+fn check_unused_palette_entries(doc: &GtfDocument, out: &mut Collector) {
+    for (index, ch, color) in unused_palette_entries(doc) {
+        match index {
+            Some(index) => {
+                let glyph = &doc.glyphs[index];
+                let message = if used_chars(glyph).contains(&ch) {
+                    format!(
+                        "Palette entry '{}' ({}) repeats the default palette",
+                        ch, color
+                    )
+                } else {
+                    format!("Palette entry '{}' ({}) is not used", ch, color)
+                };
+                out.report(Some(&glyph.name), message, true)
+            }
+            None => out.report(
+                None,
+                format!(
+                    "Default palette entry '{}' ({}) is not used by any glyph",
+                    ch, color
+                ),
+                true,
+            ),
+        }
+    }
+}
+
+fn fix_unused_palette_entries(doc: &mut GtfDocument) {
+    for (index, ch, _) in unused_palette_entries(doc) {
+        let palette = match index {
+            Some(index) => doc.glyphs[index].palette.as_mut(),
+            None => doc.header.default_palette.as_mut(),
+        };
+        if let Some(palette) = palette {
+            palette.entries.remove(&ch);
+        }
+    }
+}
+
+fn check_undefined_palette_chars(doc: &GtfDocument, out: &mut Collector) {
+    let default_palette = doc.header.default_palette.as_ref();
+    for glyph in &doc.glyphs {
+        let undefined: String = used_chars(glyph)
+            .into_iter()
+            .filter(|&ch| effective_color(ch, glyph.palette.as_ref(), default_palette).is_none())
+            .collect();
+        if !undefined.is_empty() {
+            out.report(
+                Some(&glyph.name),
+                format!("Characters without a color: '{}'", undefined),
+                false,
+            );
+        }
+    }
+}
+
+fn is_space_glyph(glyph: &Glyph) -> bool {
+    glyph
+        .codepoint()
+        .or(glyph.char_repr)
+        .is_some_and(char::is_whitespace)
+}
+
+/// Whether the bitmap covers the whole glyph cell. A trimmed bitmap of solid
+/// ink is uniform too, but the rest of its cell is background.
+fn fills_cell(glyph: &Glyph) -> bool {
+    glyph.offset.unwrap_or_default() == Offset::default()
+        && glyph
+            .cell
+            .as_ref()
+            .is_none_or(|cell| *cell == bitmap_size(glyph).0)
+}
+
+fn check_empty_glyphs(doc: &GtfDocument, out: &mut Collector) {
+    for glyph in &doc.glyphs {
+        let used = used_chars(glyph);
+        if used.is_empty() {
+            out.report(Some(&glyph.name), "Bitmap has no pixels".to_string(), false);
+        } else if used.len() == 1 && !is_space_glyph(glyph) && fills_cell(glyph) {
+            let ch = used.iter().next().copied().unwrap_or_default();
+            out.report(
+                Some(&glyph.name),
+                format!("Every pixel is '{}', the glyph draws nothing", ch),
+                false,
+            );
+        }
+    }
+}
+
+/// Capitals and digits without descenders, used to estimate the baseline.
+fn is_baseline_reference(ch: char) -> bool {
+    (ch.is_ascii_uppercase() && ch != 'J' && ch != 'Q') || ch.is_ascii_digit()
+}
+
+/// Number of background rows of the cell below the lowest drawn pixel.
+fn bottom_margin(glyph: &Glyph, doc: &GtfDocument, background: Color) -> Option<usize> {
+    let is_ink = |ch: char| pixel_color(ch, glyph, doc) != Some(background);
+    let lowest_ink = glyph
+        .bitmap
+        .iter()
+        .rposition(|row| row.chars().any(is_ink))?;
+    let offset_y = glyph.offset.map_or(0, |o| o.y) as i64;
+    let margin = cell_height(glyph) as i64 - 1 - (offset_y + lowest_ink as i64);
+    Some(margin.max(0) as usize)
+}
+
+fn check_baseline(doc: &GtfDocument, out: &mut Collector) {
+    let Some(background) = background_color(doc) else {
+        return;
+    };
+
+    let margins: Vec<(&Glyph, usize)> = doc
+        .glyphs
+        .iter()
+        .filter(|g| {
+            g.char_repr
+                .or_else(|| g.codepoint())
+                .is_some_and(is_baseline_reference)
+        })
+        .filter_map(|g| bottom_margin(g, doc, background).map(|m| (g, m)))
+        .collect();
+    // Too few references to tell what the baseline is
+    if margins.len() < 3 {
+        return;
+    }
+    let mut counts: BTreeMap<usize, usize> = BTreeMap::new();
+    for (_, margin) in &margins {
+        *counts.entry(*margin).or_default() += 1;
+    }
+    let Some((&baseline, _)) = counts.iter().max_by_key(|(_, count)| **count) else {
+        return;
+    };
+    for (glyph, margin) in margins {
+        if margin != baseline {
+            out.report(
+                Some(&glyph.name),
+                format!(
+                    "Glyph has {} empty row(s) below it, most capitals and digits have {}",
+                    margin, baseline
+                ),
+                false,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gtf::bounds::trim_glyph;
+    use crate::gtf::test_util::{char_glyph, document, glyph, palette};
+
+    fn rules(findings: &[LintFinding]) -> Vec<(&str, Option<&str>)> {
+        findings
+            .iter()
+            .map(|f| (f.rule.as_str(), f.glyph.as_deref()))
+            .collect()
+    }
+
+    #[test]
+    fn reports_without_touching_the_document() {
+        let mut a = char_glyph("A", 'A', &["#.", "##"]);
+        a.char_repr = Some('B');
+        let doc = document(vec![a, glyph("A", &["#.", "##"])]);
+        let before = doc.clone();
+
+        let findings = lint_document(&doc, &LintConfig::default());
+        assert_eq!(doc, before);
+        assert_eq!(
+            rules(&findings),
+            vec![
+                ("duplicate-name", Some("A")),
+                ("char-unicode-mismatch", Some("A"))
+            ]
+        );
+        assert!(findings.iter().all(|f| f.fixable));
+    }
+
+    #[test]
+    fn disabled_rules_are_skipped() {
+        let doc = document(vec![glyph("A", &["#."]), glyph("A", &["#."])]);
+        let mut config = LintConfig::default();
+        config
+            .rules
+            .insert("duplicate-name".to_string(), Severity::Off);
+        assert!(lint_document(&doc, &config).is_empty());
+
+        config
+            .rules
+            .insert("unused-palette-entry".to_string(), Severity::Error);
+        let mut unused = glyph("B", &["#."]);
+        unused.palette = Some(palette(&[('+', "#FF0000")]));
+        let doc = document(vec![unused]);
+        let findings = lint_document(&doc, &config);
+        assert_eq!(rules(&findings), vec![("unused-palette-entry", Some("B"))]);
+        assert_eq!(findings[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn fixes_resolve_what_lint_reports() {
+        let mut a = char_glyph("A", 'A', &["#.", "##"]);
+        a.char_repr = Some('B');
+        let mut wrong_size = glyph("A", &["#.", "##"]);
+        wrong_size.size = Some(Size {
+            width: 3,
+            height: 2,
+        });
+        wrong_size.palette = Some(palette(&[('+', "#FF0000")]));
+        let mut doc = document(vec![a, wrong_size]);
+
+        let fixed = fix_document(&mut doc, &LintConfig::default(), None);
+        assert_eq!(fixed.len(), 4);
+        assert!(lint_document(&doc, &LintConfig::default()).is_empty());
+        assert_eq!(doc.glyphs[0].char_repr, Some('A'));
+        assert_eq!(doc.glyphs[1].name, "A_2");
+        assert_eq!(
+            doc.glyphs[1].size,
+            Some(Size {
+                width: 2,
+                height: 2
+            })
+        );
+        assert!(doc.glyphs[1].palette.as_ref().unwrap().entries.is_empty());
+    }
+
+    #[test]
+    fn palette_entries_repeating_the_default_are_removed() {
+        let mut copied = glyph("A", &["#."]);
+        copied.palette = Some(palette(&[('#', "#FFFFFF"), ('.', "#FF0000")]));
+        let mut doc = document(vec![copied]);
+
+        let findings = lint_document(&doc, &LintConfig::default());
+        assert_eq!(rules(&findings), vec![("unused-palette-entry", Some("A"))]);
+        assert!(findings[0].message.contains("repeats the default palette"));
+
+        fix_document(&mut doc, &LintConfig::default(), None);
+        assert_eq!(doc.glyphs[0].palette, Some(palette(&[('.', "#FF0000")])));
+    }
+
+    #[test]
+    fn fixes_only_selected_rules() {
+        let mut doc = document(vec![glyph("A", &["#."]), glyph("A", &["##"])]);
+        doc.glyphs[1].size = Some(Size {
+            width: 1,
+            height: 1,
+        });
+
+        let fixed = fix_document(
+            &mut doc,
+            &LintConfig::default(),
+            Some(&["size-mismatch".to_string()]),
+        );
+        assert_eq!(rules(&fixed), vec![("size-mismatch", Some("A"))]);
+        assert_eq!(doc.glyphs[1].name, "A");
+        assert_eq!(
+            doc.glyphs[1].size,
+            Some(Size {
+                width: 2,
+                height: 1
+            })
+        );
+    }
+
+    #[test]
+    fn ragged_rows_are_reported_but_not_fixed() {
+        let mut ragged = glyph("A", &["#.#", "#"]);
+        ragged.size = Some(Size {
+            width: 2,
+            height: 2,
+        });
+        let mut doc = document(vec![ragged]);
+        let before = doc.clone();
+
+        let findings = lint_document(&doc, &LintConfig::default());
+        assert_eq!(rules(&findings), vec![("size-mismatch", Some("A"))]);
+        assert!(!findings[0].fixable);
+        assert!(fix_document(&mut doc, &LintConfig::default(), None).is_empty());
+        assert_eq!(doc, before);
+    }
+
+    #[test]
+    fn trimmed_glyphs_are_checked_in_their_cell() {
+        let rows = ["...", ".#.", ".#.", "..."];
+        let glyphs = ['A', 'B', 'C', 'D']
+            .into_iter()
+            .map(|ch| char_glyph(&ch.to_string(), ch, &rows))
+            .collect();
+        let mut doc = document(glyphs);
+        let background = background_color(&doc);
+        for i in 0..3 {
+            doc.glyphs[i] = trim_glyph(&doc.glyphs[i], &doc, background).unwrap();
+        }
+        assert_eq!(doc.glyphs[0].bitmap, vec!["#", "#"]);
+
+        assert!(lint_document(&doc, &LintConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn unknown_rule_ids_are_rejected() {
+        let mut config = LintConfig::default();
+        config
+            .rules
+            .insert("no-such-rule".to_string(), Severity::Error);
+        assert!(config.check_rule_ids().is_err());
+    }
+}
@@ -0,0 +1,574 @@
+//! Three-way merge of GTF documents (base / ours / theirs).
+//!
+//! Glyphs are matched by name. Every value is merged with the usual rule:
+//! if only one side changed it, that side wins; if both sides made the same
+//! change, it is taken once; otherwise it is a conflict. Glyphs changed on
+//! both sides are merged further per field, per palette entry and per pixel,
+//! so two people editing different parts of one glyph do not conflict.
+//!
+//! Conflicts keep the value from `ours` in the merged document and are listed
+//! in `MergeResult::conflicts`.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+use serde::Serialize;
+
+use super::color::Color;
+use super::types::{Glyph, GtfDocument, GtfHeader, Palette, PaletteRoles, Size};
+
+/// Where a conflict happened and what kind of value it concerns.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ConflictKind {
+    /// A header field or a glyph metadata field (`unicode`, `char_repr`, `size`).
+    Field {
+        name: String,
+    },
+    PaletteEntry {
+        char: char,
+    },
+    Pixel {
+        row: usize,
+        col: usize,
+    },
+    /// Both sides changed the bitmap dimensions, pixels cannot be merged.
+    Bitmap,
+    /// Glyph added on both sides with different content, or deleted on one
+    /// side and modified on the other.
+    Glyph,
+}
+
+/// One value both sides changed differently. `None` means absent (not set,
+/// or deleted).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MergeConflict {
+    /// Glyph name, `None` for the header and the default palette.
+    pub glyph: Option<String>,
+    pub kind: ConflictKind,
+    pub base: Option<String>,
+    pub ours: Option<String>,
+    pub theirs: Option<String>,
+}
+
+impl fmt::Display for MergeConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.glyph {
+            Some(name) => write!(f, "glyph '{}': ", name)?,
+            None => write!(f, "header: ")?,
+        }
+        match &self.kind {
+            ConflictKind::Field { name } => write!(f, "field {}", name)?,
+            ConflictKind::PaletteEntry { char } => write!(f, "palette entry '{}'", char)?,
+            ConflictKind::Pixel { row, col } => write!(f, "pixel at row {}, column {}", row, col)?,
+            ConflictKind::Bitmap => write!(f, "bitmap size")?,
+            ConflictKind::Glyph => {
+                let state =
+                    |side: &Option<String>| if side.is_some() { "changed" } else { "deleted" };
+                return match &self.base {
+                    None => write!(f, "added on both sides with different content"),
+                    Some(_) => write!(
+                        f,
+                        "{} in ours, {} in theirs",
+                        state(&self.ours),
+                        state(&self.theirs)
+                    ),
+                };
+            }
+        }
+        let show = |v: &Option<String>| v.clone().unwrap_or_else(|| "(none)".to_string());
+        write!(
+            f,
+            " (base: {}, ours: {}, theirs: {})",
+            show(&self.base),
+            show(&self.ours),
+            show(&self.theirs)
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MergeResult {
+    pub document: GtfDocument,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+impl MergeResult {
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// Merges the changes `ours` and `theirs` made to `base`.
+///
+/// Glyphs keep the order of `ours`; glyphs added only in `theirs` follow the
+/// glyph they follow in `theirs` (or go to the end).
+pub fn merge_documents(
+    base: &GtfDocument,
+    ours: &GtfDocument,
+    theirs: &GtfDocument,
+) -> MergeResult {
+    let mut conflicts = Vec::new();
+    let header = merge_header(&base.header, &ours.header, &theirs.header, &mut conflicts);
+
+    let index = |doc: &GtfDocument| -> HashMap<String, Glyph> {
+        doc.glyphs
+            .iter()
+            .map(|g| (g.name.clone(), g.clone()))
+            .collect()
+    };
+    let (base_glyphs, our_glyphs, their_glyphs) = (index(base), index(ours), index(theirs));
+
+    // Glyph order: ours, with glyphs only known to theirs inserted after their predecessor
+    let mut order: Vec<String> = ours.glyphs.iter().map(|g| g.name.clone()).collect();
+    let mut known: BTreeSet<String> = order.iter().cloned().collect();
+    for (i, glyph) in theirs.glyphs.iter().enumerate() {
+        if known.contains(&glyph.name) {
+            continue;
+        }
+        let position = i
+            .checked_sub(1)
+            .and_then(|prev| order.iter().position(|n| *n == theirs.glyphs[prev].name))
+            .map_or(order.len(), |p| p + 1);
+        order.insert(position, glyph.name.clone());
+        known.insert(glyph.name.clone());
+    }
+    // Glyphs deleted on both sides are not in `order` and stay deleted
+
+    let mut glyphs = Vec::new();
+    for name in order {
+        let merged = merge_glyph(
+            &name,
+            base_glyphs.get(&name),
+            our_glyphs.get(&name),
+            their_glyphs.get(&name),
+            &mut conflicts,
+        );
+        if let Some(glyph) = merged {
+            glyphs.push(glyph);
+        }
+    }
+
+    MergeResult {
+        document: GtfDocument { header, glyphs },
+        conflicts,
+    }
+}
+
+/// Classic three-way pick. `None` means both sides changed the value differently.
+fn pick<T: PartialEq + Clone>(base: &T, ours: &T, theirs: &T) -> Option<T> {
+    if ours == theirs || theirs == base {
+        Some(ours.clone())
+    } else if ours == base {
+        Some(theirs.clone())
+    } else {
+        None
+    }
+}
+
+/// Picks a value; on conflict records it and keeps ours.
+fn merge_value<T: PartialEq + Clone>(
+    base: &T,
+    ours: &T,
+    theirs: &T,
+    glyph: Option<&str>,
+    kind: impl FnOnce() -> ConflictKind,
+    describe: impl Fn(&T) -> Option<String>,
+    conflicts: &mut Vec<MergeConflict>,
+) -> T {
+    pick(base, ours, theirs).unwrap_or_else(|| {
+        conflicts.push(MergeConflict {
+            glyph: glyph.map(str::to_string),
+            kind: kind(),
+            base: describe(base),
+            ours: describe(ours),
+            theirs: describe(theirs),
+        });
+        ours.clone()
+    })
+}
+
+fn field_kind(name: &str) -> impl FnOnce() -> ConflictKind + '_ {
+    move || ConflictKind::Field {
+        name: name.to_string(),
+    }
+}
+
+fn describe_size(size: &Option<Size>) -> Option<String> {
+    size.as_ref().map(|s| format!("{}x{}", s.width, s.height))
+}
+
+fn merge_header(
+    base: &GtfHeader,
+    ours: &GtfHeader,
+    theirs: &GtfHeader,
+    conflicts: &mut Vec<MergeConflict>,
+) -> GtfHeader {
+    let mut text = |name: &str, get: fn(&GtfHeader) -> &Option<String>| {
+        merge_value(
+            get(base),
+            get(ours),
+            get(theirs),
+            None,
+            field_kind(name),
+            Clone::clone,
+            conflicts,
+        )
+    };
+    let font_name = text("font_name", |h| &h.font_name);
+    let version = text("version", |h| &h.version);
+    let author = text("author", |h| &h.author);
+    let description = text("description", |h| &h.description);
+
+    GtfHeader {
+        font_name,
+        version,
+        author,
+        description,
+        default_size: merge_value(
+            &base.default_size,
+            &ours.default_size,
+            &theirs.default_size,
+            None,
+            field_kind("default_size"),
+            describe_size,
+            conflicts,
+        ),
+        default_palette: merge_palette(
+            base.default_palette.as_ref(),
+            ours.default_palette.as_ref(),
+            theirs.default_palette.as_ref(),
+            None,
+            conflicts,
+        ),
+        roles: PaletteRoles {
+            background: merge_value(
+                &base.roles.background,
+                &ours.roles.background,
+                &theirs.roles.background,
+                None,
+                field_kind("background"),
+                |c| c.map(String::from),
+                conflicts,
+            ),
+            foreground: merge_value(
+                &base.roles.foreground,
+                &ours.roles.foreground,
+                &theirs.roles.foreground,
+                None,
+                field_kind("foreground"),
+                |c| c.map(String::from),
+                conflicts,
+            ),
+        },
+    }
+}
+
+/// Merges palettes entry by entry by color value.
+fn merge_palette(
+    base: Option<&Palette>,
+    ours: Option<&Palette>,
+    theirs: Option<&Palette>,
+    glyph: Option<&str>,
+    conflicts: &mut Vec<MergeConflict>,
+) -> Option<Palette> {
+    if ours.is_none() && theirs.is_none() {
+        return None;
+    }
+    let entry =
+        |palette: Option<&Palette>, ch: char| palette.and_then(|p| p.entries.get(&ch)).copied();
+    let chars: BTreeSet<char> = [base, ours, theirs]
+        .into_iter()
+        .flatten()
+        .flat_map(|p| p.entries.keys().copied())
+        .collect();
+
+    let mut merged = Palette::default();
+    for ch in chars {
+        let (b, o, t) = (entry(base, ch), entry(ours, ch), entry(theirs, ch));
+        let color = pick(&b, &o, &t).unwrap_or_else(|| {
+            let describe = |c: Option<Color>| c.map(|c| c.to_string());
+            conflicts.push(MergeConflict {
+                glyph: glyph.map(str::to_string),
+                kind: ConflictKind::PaletteEntry { char: ch },
+                base: describe(b),
+                ours: describe(o),
+                theirs: describe(t),
+            });
+            o
+        });
+        if let Some(color) = color {
+            merged.entries.insert(ch, color);
+        }
+    }
+    Some(merged)
+}
+
+fn merge_glyph(
+    name: &str,
+    base: Option<&Glyph>,
+    ours: Option<&Glyph>,
+    theirs: Option<&Glyph>,
+    conflicts: &mut Vec<MergeConflict>,
+) -> Option<Glyph> {
+    if let Some(winner) = pick(&base, &ours, &theirs) {
+        return winner.cloned();
+    }
+
+    let (base, ours, theirs) = match (base, ours, theirs) {
+        (Some(b), Some(o), Some(t)) => (b, o, t),
+        // Added on both sides differently, or deleted on one side and modified on the other
+        (b, o, t) => {
+            let describe = |g: Option<&Glyph>| g.map(|g| g.bitmap.join("\n"));
+            conflicts.push(MergeConflict {
+                glyph: Some(name.to_string()),
+                kind: ConflictKind::Glyph,
+                base: describe(b),
+                ours: describe(o),
+                theirs: describe(t),
+            });
+            return o.or(t).cloned();
+        }
+    };
+
+    let glyph = Some(name);
+    let unicode = merge_value(
+        &base.unicode,
+        &ours.unicode,
+        &theirs.unicode,
+        glyph,
+        field_kind("unicode"),
+        Clone::clone,
+        conflicts,
+    );
+    let char_repr = merge_value(
+        &base.char_repr,
+        &ours.char_repr,
+        &theirs.char_repr,
+        glyph,
+        field_kind("char_repr"),
+        |c| c.map(String::from),
+        conflicts,
+    );
+    let palette = merge_palette(
+        base.palette.as_ref(),
+        ours.palette.as_ref(),
+        theirs.palette.as_ref(),
+        glyph,
+        conflicts,
+    );
+    let offset = merge_value(
+        &base.offset,
+        &ours.offset,
+        &theirs.offset,
+        glyph,
+        field_kind("offset"),
+        |o| o.map(|o| format!("{},{}", o.x, o.y)),
+        conflicts,
+    );
+    let cell = merge_value(
+        &base.cell,
+        &ours.cell,
+        &theirs.cell,
+        glyph,
+        field_kind("cell"),
+        describe_size,
+        conflicts,
+    );
+    let (size, bitmap) = merge_bitmap(name, base, ours, theirs, conflicts);
+
+    Some(Glyph {
+        name: name.to_string(),
+        unicode,
+        char_repr,
+        size,
+        offset,
+        cell,
+        palette,
+        bitmap,
+        validation_warnings: ours.validation_warnings.clone(),
+    })
+}
+
+/// Merges bitmaps pixel by pixel when all three have the same dimensions,
+/// otherwise takes the side that changed them.
+fn merge_bitmap(
+    name: &str,
+    base: &Glyph,
+    ours: &Glyph,
+    theirs: &Glyph,
+    conflicts: &mut Vec<MergeConflict>,
+) -> (Option<Size>, Vec<String>) {
+    let dims = |g: &Glyph| -> Vec<usize> { g.bitmap.iter().map(|r| r.chars().count()).collect() };
+    let same_dims = dims(base) == dims(ours) && dims(base) == dims(theirs);
+
+    if !same_dims {
+        let sides = (
+            (&base.size, &base.bitmap),
+            (&ours.size, &ours.bitmap),
+            (&theirs.size, &theirs.bitmap),
+        );
+        return match pick(&sides.0, &sides.1, &sides.2) {
+            Some((size, bitmap)) => (size.clone(), bitmap.clone()),
+            None => {
+                conflicts.push(MergeConflict {
+                    glyph: Some(name.to_string()),
+                    kind: ConflictKind::Bitmap,
+                    base: describe_size(&base.size),
+                    ours: describe_size(&ours.size),
+                    theirs: describe_size(&theirs.size),
+                });
+                (ours.size.clone(), ours.bitmap.clone())
+            }
+        };
+    }
+
+    let size = merge_value(
+        &base.size,
+        &ours.size,
+        &theirs.size,
+        Some(name),
+        field_kind("size"),
+        describe_size,
+        conflicts,
+    );
+    let bitmap = base
+        .bitmap
+        .iter()
+        .zip(&ours.bitmap)
+        .zip(&theirs.bitmap)
+        .enumerate()
+        .map(|(row, ((b, o), t))| {
+            b.chars()
+                .zip(o.chars())
+                .zip(t.chars())
+                .enumerate()
+                .map(|(col, ((b, o), t))| {
+                    merge_value(
+                        &b,
+                        &o,
+                        &t,
+                        Some(name),
+                        || ConflictKind::Pixel { row, col },
+                        |c| Some(c.to_string()),
+                        conflicts,
+                    )
+                })
+                .collect()
+        })
+        .collect();
+    (size, bitmap)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gtf::test_util::{color, document, glyph, palette};
+
+    fn base() -> GtfDocument {
+        document(vec![
+            glyph("A", &["....", "....", "...."]),
+            glyph("B", &["##", "##"]),
+            glyph("C", &["#"]),
+        ])
+    }
+
+    #[test]
+    fn unchanged_sides_merge_to_base() {
+        let result = merge_documents(&base(), &base(), &base());
+        assert!(result.is_clean());
+        assert_eq!(result.document, base());
+    }
+
+    #[test]
+    fn different_pixels_of_one_glyph_merge_cleanly() {
+        let mut ours = base();
+        ours.glyphs[0].bitmap[0] = "#...".to_string();
+        let mut theirs = base();
+        theirs.glyphs[0].bitmap[2] = "...#".to_string();
+
+        let result = merge_documents(&base(), &ours, &theirs);
+        assert!(result.is_clean(), "{:?}", result.conflicts);
+        assert_eq!(
+            result.document.glyphs[0].bitmap,
+            vec!["#...", "....", "...#"]
+        );
+    }
+
+    #[test]
+    fn additions_deletions_and_header_changes_from_both_sides() {
+        let mut ours = base();
+        ours.glyphs.remove(2);
+        ours.header.author = Some("Ours".to_string());
+        let mut theirs = base();
+        theirs.glyphs.insert(1, glyph("New", &["+"]));
+        theirs.header.default_palette = Some(palette(&[
+            ('.', "#000000"),
+            ('#', "#FFFFFF"),
+            ('+', "#FF0000"),
+        ]));
+
+        let result = merge_documents(&base(), &ours, &theirs);
+        assert!(result.is_clean(), "{:?}", result.conflicts);
+        let names: Vec<&str> = result
+            .document
+            .glyphs
+            .iter()
+            .map(|g| g.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["A", "New", "B"]);
+        assert_eq!(result.document.header.author.as_deref(), Some("Ours"));
+        let default = result.document.header.default_palette.unwrap();
+        assert_eq!(default.entries[&'+'], color("#FF0000"));
+    }
+
+    #[test]
+    fn same_pixel_changed_differently_conflicts_and_keeps_ours() {
+        let mut ours = base();
+        ours.glyphs[1].bitmap[0] = ".#".to_string();
+        let mut theirs = base();
+        theirs.glyphs[1].bitmap[0] = "+#".to_string();
+
+        let result = merge_documents(&base(), &ours, &theirs);
+        assert_eq!(result.conflicts.len(), 1);
+        let conflict = &result.conflicts[0];
+        assert_eq!(conflict.glyph.as_deref(), Some("B"));
+        assert_eq!(conflict.kind, ConflictKind::Pixel { row: 0, col: 0 });
+        assert_eq!(
+            conflict.to_string(),
+            "glyph 'B': pixel at row 0, column 0 (base: #, ours: ., theirs: +)"
+        );
+        assert_eq!(result.document.glyphs[1].bitmap[0], ".#");
+    }
+
+    #[test]
+    fn deleted_on_one_side_and_modified_on_the_other_conflicts() {
+        let mut ours = base();
+        ours.glyphs.remove(2);
+        let mut theirs = base();
+        theirs.glyphs[2].bitmap[0] = ".".to_string();
+
+        let result = merge_documents(&base(), &ours, &theirs);
+        assert_eq!(result.conflicts.len(), 1);
+        assert_eq!(result.conflicts[0].kind, ConflictKind::Glyph);
+        assert_eq!(
+            result.conflicts[0].to_string(),
+            "glyph 'C': deleted in ours, changed in theirs"
+        );
+    }
+
+    #[test]
+    fn both_sides_resizing_differently_is_a_bitmap_conflict() {
+        let mut ours = base();
+        ours.glyphs[2] = glyph("C", &["#", "#"]);
+        let mut theirs = base();
+        theirs.glyphs[2] = glyph("C", &["##"]);
+
+        let result = merge_documents(&base(), &ours, &theirs);
+        assert!(result
+            .conflicts
+            .iter()
+            .any(|c| c.kind == ConflictKind::Bitmap));
+        assert_eq!(result.document.glyphs[2], ours.glyphs[2]);
+    }
+}
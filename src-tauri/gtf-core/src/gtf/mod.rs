@@ -0,0 +1,47 @@
+//! GTF module layout:
+//! - `types`: Core data structures shared by parsing and serialization.
+//! - `parse`: Stateful parser that converts text (or a `BufRead` stream) into `GtfDocument`.
+//! - `serialize`: Writer that converts `GtfDocument` back to text.
+//! - `color`: Canonical RGBA palette color, parsed from and written as hex.
+//! - `palette`: Color normalization and palette reconciliation helpers.
+//! - `remap`: Font-wide palette character remapping, color merging and palette optimization.
+//! - `resize`: Bitmap resizing.
+//! - `transform`: Flips, rotations, shifting and transposition of glyph bitmaps.
+//! - `style`: Bold, outline, shadow and italic variants derived from a font.
+//! - `scale`: Integer, EPX and smooth upscaling and majority / threshold downscaling.
+//! - `bounds`: Ink bounding boxes and trimming of blank glyph borders.
+//! - `charmap`: `CHAR` / `UNICODE` normalization and glyph naming conventions.
+//! - `charset`: Built-in and user-defined character sets and font coverage.
+//! - `compose`: Accented glyphs built from a base letter and a diacritic glyph.
+//! - `similarity`: Exact and near-duplicate glyph bitmaps (color-normalized hashes, Hamming distance).
+//! - `diff`: Semantic comparison of two documents (glyph pairing, palette and pixel changes).
+//! - `merge`: Three-way merge (base / ours / theirs) with structured conflicts.
+//! - `lint`: Font-wide rules with configurable severities and automatic fixes.
+
+pub mod bounds;
+pub mod charmap;
+pub mod charset;
+pub mod color;
+pub mod compose;
+pub mod diff;
+pub mod lint;
+pub mod merge;
+mod parse;
+pub mod palette;
+pub mod remap;
+pub mod resize;
+pub mod scale;
+mod serialize;
+pub mod similarity;
+pub mod style;
+#[cfg(test)]
+pub(crate) mod test_util;
+pub mod transform;
+pub mod types;
+
+pub use parse::{parse_gtf_content, parse_gtf_reader};
+pub use serialize::{
+    serialize_gtf_document, serialize_gtf_document_with, PaletteOutput, SerializeOptions,
+};
+pub use color::Color;
+pub use types::{GtfDocument, Size};
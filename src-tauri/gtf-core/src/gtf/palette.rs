@@ -0,0 +1,328 @@
+//! Palette helpers shared by commands that move glyphs between palettes.
+//!
+//! Colors are `Color` values parsed on load, so `#fff` and `#FFFFFF` are the
+//! same color. The font background and foreground come from the header
+//! `roles` when set, otherwise they are detected from pixel usage.
+//!
+//! A glyph palette only holds overrides: every other character inherits its
+//! color from the header `DEFAULT_PALETTE` (spec §5.2). `effective_palette`
+//! resolves the layers, `strip_inherited` drops entries equal to the default.
+
+use std::collections::{BTreeMap, HashMap};
+
+use super::color::Color;
+use super::types::{Glyph, GtfDocument, Palette, PaletteRoles};
+
+/// Resolves the color of a bitmap character: glyph palette first, then the default palette.
+pub fn effective_color(
+    ch: char,
+    glyph_palette: Option<&Palette>,
+    default_palette: Option<&Palette>,
+) -> Option<Color> {
+    glyph_palette
+        .and_then(|p| p.entries.get(&ch))
+        .or_else(|| default_palette.and_then(|p| p.entries.get(&ch)))
+        .copied()
+}
+
+/// Color of a pixel of `glyph`, `None` when the character has no color.
+pub fn pixel_color(ch: char, glyph: &Glyph, doc: &GtfDocument) -> Option<Color> {
+    effective_color(
+        ch,
+        glyph.palette.as_ref(),
+        doc.header.default_palette.as_ref(),
+    )
+}
+
+/// Full palette of `glyph`: the default palette with the glyph's overrides on top.
+pub fn effective_palette(glyph: &Glyph, doc: &GtfDocument) -> Palette {
+    let mut palette = doc.header.default_palette.clone().unwrap_or_default();
+    if let Some(own) = &glyph.palette {
+        palette.entries.extend(own.entries.iter().map(|(ch, color)| (*ch, *color)));
+    }
+    palette
+}
+
+/// Entries of `palette` that differ from `default_palette`, i.e. the overrides.
+pub fn strip_inherited(palette: &Palette, default_palette: Option<&Palette>) -> Palette {
+    Palette {
+        entries: palette
+            .entries
+            .iter()
+            .filter(|(ch, color)| default_palette.and_then(|p| p.entries.get(ch)) != Some(*color))
+            .map(|(ch, color)| (*ch, *color))
+            .collect(),
+    }
+}
+
+/// Pixel colors of all glyphs, most common first; ties go to the lower color.
+fn colors_by_usage(doc: &GtfDocument) -> Vec<Color> {
+    let mut color_counts: HashMap<Color, usize> = HashMap::new();
+    for glyph in &doc.glyphs {
+        for ch in glyph.bitmap.iter().flat_map(|row| row.chars()) {
+            if let Some(color) = pixel_color(ch, glyph, doc) {
+                *color_counts.entry(color).or_default() += 1;
+            }
+        }
+    }
+    let mut counted: Vec<(Color, usize)> = color_counts.into_iter().collect();
+    counted.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counted.into_iter().map(|(color, _)| color).collect()
+}
+
+/// Color of a role character in the default palette.
+fn role_color(doc: &GtfDocument, role: Option<char>) -> Option<Color> {
+    let ch = role?;
+    doc.header
+        .default_palette
+        .as_ref()
+        .and_then(|p| p.entries.get(&ch))
+        .copied()
+}
+
+/// Background color of a font: the `BACKGROUND` role, otherwise the most
+/// common pixel color over all glyphs.
+pub fn background_color(doc: &GtfDocument) -> Option<Color> {
+    role_color(doc, doc.header.roles.background)
+        .or_else(|| colors_by_usage(doc).into_iter().next())
+}
+
+/// Foreground (ink) color of a font: the `FOREGROUND` role, otherwise the most
+/// common pixel color other than the background.
+pub fn foreground_color(doc: &GtfDocument) -> Option<Color> {
+    role_color(doc, doc.header.roles.foreground).or_else(|| {
+        let background = background_color(doc);
+        colors_by_usage(doc)
+            .into_iter()
+            .find(|&color| Some(color) != background)
+    })
+}
+
+/// Lowest default palette character with `color`.
+fn default_palette_char(doc: &GtfDocument, color: Color) -> Option<char> {
+    let palette = doc.header.default_palette.as_ref()?;
+    palette
+        .entries
+        .iter()
+        .filter(|(_, c)| **c == color)
+        .map(|(ch, _)| *ch)
+        .min()
+}
+
+/// Roles derived from pixel usage: the lowest default palette characters with
+/// the detected background and foreground colors. Existing roles are ignored.
+pub fn detect_roles(doc: &GtfDocument) -> PaletteRoles {
+    let usage = colors_by_usage(doc);
+    let background = usage.first().copied();
+    let foreground = usage
+        .iter()
+        .copied()
+        .find(|&color| Some(color) != background);
+    let char_of = |color: Option<Color>| default_palette_char(doc, color?);
+    PaletteRoles {
+        background: char_of(background),
+        foreground: char_of(foreground),
+    }
+}
+
+/// Character new glyphs are filled with: the `BACKGROUND` role, otherwise the
+/// lowest default palette character with the background color.
+pub fn default_background_char(doc: &GtfDocument) -> Option<char> {
+    let role = doc.header.roles.background;
+    if role_color(doc, role).is_some() {
+        return role;
+    }
+    default_palette_char(doc, background_color(doc)?)
+}
+
+/// Character drawing the font background in `glyph`: the one most used in its
+/// bitmap, otherwise the lowest palette character with the background color.
+/// `background` is the font's `background_color`, looked up once per operation
+/// because it scans every glyph.
+pub fn background_char(glyph: &Glyph, doc: &GtfDocument, background: Color) -> Option<char> {
+    let mut counts: BTreeMap<char, usize> = BTreeMap::new();
+    for ch in glyph.bitmap.iter().flat_map(|row| row.chars()) {
+        *counts.entry(ch).or_default() += 1;
+    }
+    let in_bitmap = counts
+        .into_iter()
+        .filter(|(ch, _)| pixel_color(*ch, glyph, doc) == Some(background))
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+        .map(|(ch, _)| ch);
+    in_bitmap.or_else(|| {
+        let mut candidates: Vec<char> = glyph
+            .palette
+            .iter()
+            .chain(doc.header.default_palette.iter())
+            .flat_map(|p| p.entries.keys().copied())
+            .filter(|&ch| pixel_color(ch, glyph, doc) == Some(background))
+            .collect();
+        candidates.sort_unstable();
+        candidates.first().copied()
+    })
+}
+
+/// What happened to the palette of a glyph moved into another document.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct PaletteReconciliation {
+    /// Bitmap characters replaced by another character (same color in the target
+    /// palette, or a fresh character when the original one was taken).
+    pub remapped: BTreeMap<char, char>,
+    /// Characters whose color had to be added to the glyph palette.
+    pub added: BTreeMap<char, Color>,
+    /// Characters used in the bitmap that have no color in the source document.
+    pub unresolved: Vec<char>,
+}
+
+/// Rewrites a glyph from the source document so it renders identically in the
+/// target document.
+///
+/// For every bitmap character the source color is looked up. If the target
+/// palette already has a character with that color, the bitmap is remapped to
+/// it. Otherwise the character is kept and its color is stored in the glyph
+/// palette as an override of the target default palette. The resulting glyph
+/// palette only holds these overrides, everything else is inherited.
+pub fn reconcile_glyph_palette(
+    glyph: &Glyph,
+    source_default: Option<&Palette>,
+    target_default: Option<&Palette>,
+) -> (Glyph, PaletteReconciliation) {
+    let mut report = PaletteReconciliation::default();
+
+    // Color -> target character (prefer the smallest char for determinism)
+    let mut target_by_color: HashMap<Color, char> = HashMap::new();
+    if let Some(palette) = target_default {
+        let mut entries: Vec<_> = palette.entries.iter().collect();
+        entries.sort_by_key(|(ch, _)| **ch);
+        for (ch, color) in entries {
+            target_by_color.entry(*color).or_insert(*ch);
+        }
+    }
+
+    let mut palette = Palette::default();
+    let mut mapping: HashMap<char, char> = HashMap::new();
+    let mut needs_color: Vec<(char, Color)> = Vec::new();
+
+    let mut used: Vec<char> = glyph.bitmap.iter().flat_map(|row| row.chars()).collect();
+    used.sort_unstable();
+    used.dedup();
+
+    for &ch in &used {
+        let Some(color) = effective_color(ch, glyph.palette.as_ref(), source_default) else {
+            report.unresolved.push(ch);
+            continue;
+        };
+        let same_char_matches = target_default
+            .and_then(|p| p.entries.get(&ch))
+            .is_some_and(|c| *c == color);
+        if same_char_matches {
+            continue;
+        }
+
+        match target_by_color.get(&color) {
+            Some(&target_ch) => {
+                mapping.insert(ch, target_ch);
+            }
+            None => needs_color.push((ch, color)),
+        }
+    }
+
+    // A new color must not override a character another color was remapped to,
+    // in that case the new color gets a fresh character.
+    let mut taken: Vec<char> = used.clone();
+    taken.extend(target_default.iter().flat_map(|p| p.entries.keys().copied()));
+    for (ch, color) in needs_color {
+        let collides = mapping.values().any(|&dest| dest == ch);
+        let final_ch = if collides {
+            let fresh = ('!'..='~')
+                .chain('\u{00A1}'..='\u{00FF}')
+                .find(|c| !taken.contains(c) && !c.is_whitespace())
+                .unwrap_or(ch);
+            taken.push(fresh);
+            mapping.insert(ch, fresh);
+            fresh
+        } else {
+            ch
+        };
+        palette.entries.insert(final_ch, color);
+        report.added.insert(final_ch, color);
+    }
+    mapping.retain(|from, to| from != to);
+    report.remapped = mapping.iter().map(|(k, v)| (*k, *v)).collect();
+
+    let mut result = glyph.clone();
+    if !mapping.is_empty() {
+        result.bitmap = glyph
+            .bitmap
+            .iter()
+            .map(|row| {
+                row.chars()
+                    .map(|c| *mapping.get(&c).unwrap_or(&c))
+                    .collect()
+            })
+            .collect();
+    }
+    result.palette = Some(palette);
+    (result, report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gtf::test_util::{color, document, glyph, palette};
+
+    #[test]
+    fn background_comes_from_the_role_or_pixel_usage() {
+        let mut doc = document(vec![glyph("A", &["..#", "..#"])]);
+        assert_eq!(background_color(&doc), Some(color("#000000")));
+        assert_eq!(foreground_color(&doc), Some(color("#FFFFFF")));
+
+        doc.header.roles.background = Some('#');
+        assert_eq!(background_color(&doc), Some(color("#FFFFFF")));
+    }
+
+    #[test]
+    fn detected_roles_ignore_the_existing_ones() {
+        let mut doc = document(vec![glyph("A", &["..#", "..#"])]);
+        doc.header.roles = PaletteRoles {
+            background: Some('#'),
+            foreground: Some('.'),
+        };
+        let detected = detect_roles(&doc);
+        assert_eq!(
+            (detected.background, detected.foreground),
+            (Some('.'), Some('#'))
+        );
+    }
+
+    #[test]
+    fn background_char_prefers_the_character_the_bitmap_uses() {
+        let mut doc = document(vec![glyph("A", &["--#", "-.#"])]);
+        doc.header.default_palette = Some(palette(&[
+            ('.', "#000000"),
+            ('-', "#000000"),
+            ('#', "#FFFFFF"),
+        ]));
+        let background = background_color(&doc).unwrap();
+        assert_eq!(background_char(&doc.glyphs[0], &doc, background), Some('-'));
+
+        // Without background pixels, the lowest palette character with the color
+        let solid = glyph("B", &["##"]);
+        assert_eq!(background_char(&solid, &doc, background), Some('-'));
+        let mut own = glyph("C", &["##"]);
+        own.palette = Some(palette(&[('+', "#000000")]));
+        assert_eq!(background_char(&own, &doc, background), Some('+'));
+    }
+
+    #[test]
+    fn strip_inherited_keeps_only_overrides() {
+        let doc = document(vec![]);
+        let full = palette(&[('.', "#000000"), ('#', "#FF0000"), ('+', "#00FF00")]);
+        let stripped = strip_inherited(&full, doc.header.default_palette.as_ref());
+        assert_eq!(stripped, palette(&[('#', "#FF0000"), ('+', "#00FF00")]));
+
+        let mut g = glyph("A", &["#"]);
+        g.palette = Some(stripped);
+        assert_eq!(effective_palette(&g, &doc), full);
+    }
+}
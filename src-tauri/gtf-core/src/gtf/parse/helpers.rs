@@ -2,7 +2,8 @@
 
 use std::str::FromStr;
 
-use crate::gtf::types::{Glyph, GtfHeader, Palette, Size};
+use crate::gtf::color::Color;
+use crate::gtf::types::{Glyph, GtfHeader, Offset, Palette, Size};
 
 /// Append a validation warning to a glyph, initialising the vec if needed.
 pub(super) fn push_warning(glyph: &mut Glyph, warning: String)
@@ -36,6 +37,8 @@ pub(super) fn parse_header_line(line: &str, header: &mut GtfHeader) -> Result<()
         {
             header.default_size = Some(Size::from_str(value)?);
         }
+        "BACKGROUND" => header.roles.background = Some(parse_role_char(value)?),
+        "FOREGROUND" => header.roles.foreground = Some(parse_role_char(value)?),
         "DEFAULT_PALETTE" =>
         {
             return Err(
@@ -47,6 +50,26 @@ pub(super) fn parse_header_line(line: &str, header: &mut GtfHeader) -> Result<()
     Ok(())
 }
 
+/// `BACKGROUND` / `FOREGROUND` name a single default palette character.
+fn parse_role_char(value: &str) -> Result<char, String>
+{
+    let mut chars = value.chars();
+    match (chars.next(), chars.next())
+    {
+        (Some(ch), None) => Ok(ch),
+        _ => Err(format!(
+            "Invalid palette role '{}'. Expected a single palette character.",
+            value
+        )),
+    }
+}
+
+/// Header keys that may still follow the `DEFAULT_PALETTE` block.
+pub(super) fn is_role_line(line: &str) -> bool
+{
+    line.starts_with("BACKGROUND ") || line.starts_with("FOREGROUND ")
+}
+
 pub(super) fn parse_glyph_meta_line(line: &str, glyph: &mut Glyph) -> Result<(), String>
 {
     // CHAR lines need special handling to preserve space characters
@@ -121,6 +144,18 @@ pub(super) fn parse_glyph_meta_line(line: &str, glyph: &mut Glyph) -> Result<(),
             let size = Size::from_str(value)?;
             glyph.size = Some(size);
         }
+        "OFFSET" =>
+        {
+            let value = parts[1].trim();
+            let offset = Offset::from_str(value)?;
+            glyph.offset = Some(offset);
+        }
+        "CELL" =>
+        {
+            let value = parts[1].trim();
+            let cell = Size::from_str(value)?;
+            glyph.cell = Some(cell);
+        }
         _ => return Err(format!("Unknown or invalid glyph metadata key: '{}'", key)),
     }
     Ok(())
@@ -132,7 +167,7 @@ pub(super) fn parse_palette_line(line: &str, palette: &mut Palette) -> Result<()
     if parts.len() != 2
     {
         return Err(format!(
-            "Invalid palette line format: '{}'. Expected 'char #HEXCOLOR'.",
+            "Invalid palette line format: '{}'. Expected 'char #HEXCOLOR' or 'char transparent'.",
             line
         ));
     }
@@ -149,18 +184,14 @@ pub(super) fn parse_palette_line(line: &str, palette: &mut Palette) -> Result<()
     }
     let palette_char = chars[0];
 
-    if !color_part.starts_with('#') || !(color_part.len() == 7 || color_part.len() == 4)
-    {
-        return Err(format!(
-            "Invalid palette color format: '{}'. Expected '#RRGGBB' or '#RGB'.",
+    // #RGB, #RGBA, #RRGGBB, #RRGGBBAA or the keyword 'transparent'
+    let color = Color::from_str(color_part).map_err(|_| {
+        format!(
+            "Invalid palette color format: '{}'. Expected '#RRGGBB', '#RGB', '#RRGGBBAA', '#RGBA' or 'transparent'.",
             color_part
-        ));
-    }
-
-    if palette
-        .entries
-        .insert(palette_char, color_part.to_string())
-        .is_some()
+        )
+    })?;
+    if palette.entries.insert(palette_char, color).is_some()
     {
         return Err(format!(
             "Duplicate palette definition for character '{}'",
@@ -217,31 +248,33 @@ pub(super) fn validate_end_glyph(line: &str, expected_name: Option<&str>) -> Res
     Ok(())
 }
 
-pub(super) fn validate_bitmap_line(line: &str, glyph: &mut Glyph, line_num: usize)
+pub(super) fn validate_bitmap_line(
+    line: &str,
+    glyph: &mut Glyph,
+    default_palette: Option<&Palette>,
+    line_num: usize,
+)
 {
-    let warnings: Vec<String> = if let Some(palette) = &glyph.palette
+    // Characters resolve through the glyph PALETTE first, then DEFAULT_PALETTE (spec §5.2)
+    let palettes: Vec<&Palette> = glyph
+        .palette
+        .iter()
+        .chain(default_palette)
+        .filter(|p| !p.entries.is_empty())
+        .collect();
+    let warnings: Vec<String> = if !palettes.is_empty()
     {
-        if !palette.entries.is_empty()
-        {
-            line.chars()
-                .enumerate()
-                .filter(|(_, ch)| !palette.entries.contains_key(ch))
-                .map(|(i, ch)|
-                {
-                    format!(
-                        "Line {}: Invalid character '{}' at position {} in bitmap for glyph '{}'. Character not found in palette.",
-                        line_num, ch, i + 1, glyph.name
-                    )
-                })
-                .collect()
-        }
-        else
-        {
-            vec![format!(
-                "Line {}: Cannot validate bitmap characters for glyph '{}' because palette data is missing unexpectedly.",
-                line_num, glyph.name
-            )]
-        }
+        line.chars()
+            .enumerate()
+            .filter(|(_, ch)| !palettes.iter().any(|p| p.entries.contains_key(ch)))
+            .map(|(i, ch)|
+            {
+                format!(
+                    "Line {}: Invalid character '{}' at position {} in bitmap for glyph '{}'. Character not found in palette.",
+                    line_num, ch, i + 1, glyph.name
+                )
+            })
+            .collect()
     }
     else
     {
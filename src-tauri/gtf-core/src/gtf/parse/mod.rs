@@ -0,0 +1,158 @@
+//! Parsing logic for the GTF text format.
+//!
+//! Uses a small state machine to walk through HEADER/Glyph blocks
+//! and collects warnings for lenient bitmap/palette issues.
+//!
+//! Submodules:
+//! - `state_handlers` – logic for each parser state transition
+//! - `helpers` – low-level line parsers and validators
+
+mod helpers;
+mod state_handlers;
+
+use std::io::BufRead;
+
+use crate::gtf::types::{Glyph, GtfDocument};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(crate) enum ParseState
+{
+    Searching,
+    InHeader,
+    InDefaultPalette,
+    InGlyphDefinition,
+    InPalette,
+    ExpectingDataKeyword,
+    InBitmap,
+    ExpectingEndGlyph,
+}
+
+pub(crate) struct ParseContext
+{
+    pub document: GtfDocument,
+    pub state: ParseState,
+    pub current_glyph: Option<Glyph>,
+    pub current_glyph_name: Option<String>,
+    pub bitmap_lines_collected: u32,
+    pub found_palette_block: bool,
+}
+
+impl ParseContext
+{
+    fn new() -> Self
+    {
+        Self {
+            document: GtfDocument::default(),
+            state: ParseState::Searching,
+            current_glyph: None,
+            current_glyph_name: None,
+            bitmap_lines_collected: 0,
+            found_palette_block: false,
+        }
+    }
+}
+
+pub fn parse_gtf_content(content: &str) -> Result<GtfDocument, String>
+{
+    let mut parser = GtfStreamParser::new();
+
+    for line in content.lines()
+    {
+        parser.feed_line(line)?;
+    }
+
+    parser.finish()
+}
+
+/// Parses a GTF document from any buffered reader without loading the whole
+/// file into memory first. Lines are fed one by one into `GtfStreamParser`.
+pub fn parse_gtf_reader<R: BufRead>(mut reader: R) -> Result<GtfDocument, String>
+{
+    let mut parser = GtfStreamParser::new();
+    let mut buffer = String::new();
+
+    loop
+    {
+        buffer.clear();
+        let read = reader
+            .read_line(&mut buffer)
+            .map_err(|e| format!("Line {}: Failed to read input: {}", parser.line_num + 1, e))?;
+        if read == 0
+        {
+            break;
+        }
+        parser.feed_line(buffer.trim_end_matches(['\n', '\r']))?;
+    }
+
+    parser.finish()
+}
+
+/// Incremental GTF parser.
+///
+/// Drives the `ParseState` machine one line at a time, so callers can feed
+/// lines from any source (file, network, chunked IPC) and only pay for the
+/// glyphs parsed so far.
+pub struct GtfStreamParser
+{
+    ctx: ParseContext,
+    line_num: usize,
+}
+
+impl GtfStreamParser
+{
+    pub fn new() -> Self
+    {
+        Self {
+            ctx: ParseContext::new(),
+            line_num: 0,
+        }
+    }
+
+    /// Processes a single line (without the trailing newline).
+    pub fn feed_line(&mut self, line: &str) -> Result<(), String>
+    {
+        self.line_num += 1;
+        let ctx = &mut self.ctx;
+        let trimmed = line.trim();
+        let current_line_num = self.line_num;
+
+        if trimmed.is_empty()
+        {
+            return Ok(());
+        }
+
+        match ctx.state
+        {
+            ParseState::Searching =>
+                state_handlers::handle_searching(ctx, trimmed, current_line_num),
+            ParseState::InHeader =>
+                state_handlers::handle_in_header(ctx, trimmed, current_line_num),
+            ParseState::InDefaultPalette =>
+                state_handlers::handle_in_default_palette(ctx, trimmed, current_line_num),
+            ParseState::InGlyphDefinition =>
+                state_handlers::handle_in_glyph_definition(ctx, trimmed, line, current_line_num),
+            ParseState::InPalette =>
+                state_handlers::handle_in_palette(ctx, trimmed, current_line_num),
+            ParseState::InBitmap =>
+                state_handlers::handle_in_bitmap(ctx, trimmed, current_line_num),
+            ParseState::ExpectingDataKeyword =>
+                state_handlers::handle_expecting_data(ctx, trimmed, current_line_num),
+            ParseState::ExpectingEndGlyph =>
+                state_handlers::handle_expecting_end_glyph(ctx, trimmed, current_line_num),
+        }
+    }
+
+    /// Finishes parsing (EOF handling) and returns the document.
+    pub fn finish(self) -> Result<GtfDocument, String>
+    {
+        state_handlers::finalize(self.ctx)
+    }
+}
+
+impl Default for GtfStreamParser
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
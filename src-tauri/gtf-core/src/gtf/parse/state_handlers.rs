@@ -3,9 +3,11 @@
 //! Each function processes a single non-empty line while in the given state
 //! and mutates the shared `ParseContext` accordingly.
 
+use crate::gtf::palette::strip_inherited;
 use crate::gtf::types::{Glyph, GtfDocument, Palette};
 
 use super::helpers::{
+    is_role_line,
     parse_glyph_meta_line, 
     parse_header_line, 
     parse_palette_line, 
@@ -99,6 +101,8 @@ pub(super) fn handle_searching(
             unicode: None,
             char_repr: None,
             size: None,
+            offset: None,
+            cell: None,
             bitmap: Vec::new(),
             validation_warnings: None,
         });
@@ -160,6 +164,11 @@ pub(super) fn handle_in_default_palette(
     {
         ctx.state = ParseState::Searching;
     }
+    else if is_role_line(trimmed)
+    {
+        parse_header_line(trimmed, &mut ctx.document.header)
+            .map_err(|e| format!("Line {}: {}", line_num, e))?;
+    }
     else
     {
         parse_palette_line(trimmed, def_palette).map_err(|e| {
@@ -220,6 +229,12 @@ pub(super) fn handle_in_glyph_definition(
         ctx.current_glyph_name = None;
         ctx.state = ParseState::Searching;
     }
+    else if trimmed == "DATA" && glyph.size.is_some()
+    {
+        // PALETTE omitted: every color is inherited from DEFAULT_PALETTE (spec §5.3)
+        ctx.state = ParseState::InBitmap;
+        ctx.bitmap_lines_collected = 0;
+    }
     else if glyph.size.is_some() && !trimmed.contains(' ')
     {
         let expected_width = glyph.size.as_ref().unwrap().width as usize;
@@ -233,7 +248,12 @@ pub(super) fn handle_in_glyph_definition(
             push_warning(glyph, warning_msg);
         }
 
-        validate_bitmap_line(trimmed, glyph, line_num);
+        validate_bitmap_line(
+            trimmed,
+            glyph,
+            ctx.document.header.default_palette.as_ref(),
+            line_num,
+        );
         glyph.bitmap.push(trimmed.to_string());
         ctx.bitmap_lines_collected = 1;
         ctx.state = ParseState::InBitmap;
@@ -277,6 +297,8 @@ pub(super) fn handle_in_palette(
 
     if trimmed == "END PALETTE"
     {
+        // Keep only overrides; entries equal to DEFAULT_PALETTE are inherited (spec §5.2)
+        *palette = strip_inherited(palette, ctx.document.header.default_palette.as_ref());
         if glyph.size.is_some()
         {
             ctx.state = ParseState::ExpectingDataKeyword;
@@ -335,7 +357,12 @@ pub(super) fn handle_in_bitmap(
             println!("Parser Warning: {}", warning_msg);
             push_warning(glyph, warning_msg);
         }
-        validate_bitmap_line(trimmed, glyph, line_num);
+        validate_bitmap_line(
+            trimmed,
+            glyph,
+            ctx.document.header.default_palette.as_ref(),
+            line_num,
+        );
         glyph.bitmap.push(trimmed.to_string());
         ctx.bitmap_lines_collected += 1;
     }
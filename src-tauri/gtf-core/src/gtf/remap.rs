@@ -0,0 +1,416 @@
+//! Font-wide palette remapping, used when consolidating fonts.
+//!
+//! - `remap_char` renames a palette character in the default palette, every
+//!   glyph palette, every bitmap and the palette roles. When the target
+//!   character already exists the two are merged and the target keeps its color.
+//! - `replace_color` swaps one color for another in all palettes.
+//! - `merge_similar_colors` snaps colors within a tolerance to the most used
+//!   color of their group and folds default palette characters that end up
+//!   with the same color into one.
+//! - `optimize_palettes` drops glyph palette entries identical to the
+//!   `DEFAULT_PALETTE` (the optimized form of spec §5.2).
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use super::color::Color;
+use super::palette::{effective_color, pixel_color};
+use super::types::{GtfDocument, Palette};
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RemapReport {
+    /// Glyphs whose bitmap or palette changed.
+    pub glyphs: Vec<String>,
+    /// Glyphs whose pixels of the old character now show the target's (different) color.
+    pub recolored: Vec<String>,
+    /// `true` when the target character already existed and the two were merged.
+    pub merged: bool,
+}
+
+fn palettes(doc: &GtfDocument) -> impl Iterator<Item = &Palette> {
+    doc.header
+        .default_palette
+        .iter()
+        .chain(doc.glyphs.iter().filter_map(|g| g.palette.as_ref()))
+}
+
+fn palettes_mut(doc: &mut GtfDocument) -> impl Iterator<Item = &mut Palette> {
+    doc.header
+        .default_palette
+        .iter_mut()
+        .chain(doc.glyphs.iter_mut().filter_map(|g| g.palette.as_mut()))
+}
+
+/// Character defined in a palette or drawn in a bitmap.
+fn is_used(doc: &GtfDocument, ch: char) -> bool {
+    palettes(doc).any(|p| p.entries.contains_key(&ch))
+        || doc
+            .glyphs
+            .iter()
+            .any(|g| g.bitmap.iter().any(|row| row.contains(ch)))
+}
+
+/// Replaces palette character `from` with `to` everywhere in `doc`.
+///
+/// Each glyph keeps the color `to` had before; where `to` had no color it
+/// takes over the color of `from`, as a glyph palette entry when the default
+/// palette disagrees.
+pub fn remap_char(doc: &mut GtfDocument, from: char, to: char) -> Result<RemapReport, String> {
+    if from == to {
+        return Err("Source and target character are the same".to_string());
+    }
+    if to.is_whitespace() {
+        return Err("Palette character must not be whitespace".to_string());
+    }
+    if !is_used(doc, from) {
+        return Err(format!("Character '{}' is not used in the font", from));
+    }
+
+    let mut report = RemapReport {
+        merged: is_used(doc, to),
+        ..Default::default()
+    };
+
+    let old_default = doc.header.default_palette.clone();
+    if let Some(palette) = &mut doc.header.default_palette {
+        if let Some(color) = palette.entries.remove(&from) {
+            palette.entries.entry(to).or_insert(color);
+        }
+    }
+    for role in [
+        &mut doc.header.roles.background,
+        &mut doc.header.roles.foreground,
+    ] {
+        if *role == Some(from) {
+            *role = Some(to);
+        }
+    }
+
+    let new_default = doc.header.default_palette.clone();
+    for glyph in &mut doc.glyphs {
+        let from_color = effective_color(from, glyph.palette.as_ref(), old_default.as_ref());
+        let to_color = effective_color(to, glyph.palette.as_ref(), old_default.as_ref());
+        let uses_from = glyph.bitmap.iter().any(|row| row.contains(from));
+        let mut changed = uses_from;
+
+        if uses_from {
+            glyph.bitmap = glyph
+                .bitmap
+                .iter()
+                .map(|row| row.replace(from, &to.to_string()))
+                .collect();
+        }
+        if let Some(palette) = &mut glyph.palette {
+            changed |= palette.entries.remove(&from).is_some();
+        }
+        if let Some(color) = to_color.or(from_color) {
+            let resolved = effective_color(to, glyph.palette.as_ref(), new_default.as_ref());
+            if resolved != Some(color) {
+                glyph
+                    .palette
+                    .get_or_insert_with(Default::default)
+                    .entries
+                    .insert(to, color);
+                changed = true;
+            }
+        }
+
+        if uses_from && to_color.is_some() && from_color != to_color {
+            report.recolored.push(glyph.name.clone());
+        }
+        if changed {
+            report.glyphs.push(glyph.name.clone());
+        }
+    }
+    Ok(report)
+}
+
+/// Replaces `from` with `to` in every palette; returns the number of entries changed.
+pub fn replace_color(doc: &mut GtfDocument, from: Color, to: Color) -> usize {
+    let mut replaced = 0;
+    for palette in palettes_mut(doc) {
+        for color in palette.entries.values_mut() {
+            if *color == from {
+                *color = to;
+                replaced += 1;
+            }
+        }
+    }
+    replaced
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ColorMergeOptions {
+    /// Largest difference of any channel (R, G, B or alpha) still merged; 0 only folds characters.
+    #[serde(default)]
+    pub tolerance: u8,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ColorReplacement {
+    pub from: Color,
+    pub to: Color,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ColorMergeReport {
+    /// Colors replaced by a similar, more used color.
+    pub replaced: Vec<ColorReplacement>,
+    /// Default palette characters folded into another character of the same color.
+    pub merged_chars: BTreeMap<char, char>,
+}
+
+fn channel_distance(a: Color, b: Color) -> u8 {
+    [
+        a.r.abs_diff(b.r),
+        a.g.abs_diff(b.g),
+        a.b.abs_diff(b.b),
+        a.a.abs_diff(b.a),
+    ]
+    .into_iter()
+    .max()
+    .unwrap_or(0)
+}
+
+/// Merges palette colors closer than `options.tolerance` and then folds default
+/// palette characters sharing a color into the lowest one.
+///
+/// Colors are visited from the most used (by pixels); each joins the first
+/// kept color within tolerance, so groups do not chain. Characters are only
+/// folded when no glyph palette gives them different colors.
+pub fn merge_similar_colors(
+    doc: &mut GtfDocument,
+    options: &ColorMergeOptions,
+) -> ColorMergeReport {
+    let mut report = ColorMergeReport::default();
+
+    let mut usage: HashMap<Color, usize> = HashMap::new();
+    for palette in palettes(doc) {
+        for color in palette.entries.values() {
+            usage.entry(*color).or_default();
+        }
+    }
+    for glyph in &doc.glyphs {
+        for ch in glyph.bitmap.iter().flat_map(|row| row.chars()) {
+            if let Some(color) = pixel_color(ch, glyph, doc) {
+                *usage.entry(color).or_default() += 1;
+            }
+        }
+    }
+    let mut colors: Vec<(Color, usize)> = usage.into_iter().collect();
+    colors.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    let mut kept: Vec<Color> = Vec::new();
+    let mut replacements: HashMap<Color, Color> = HashMap::new();
+    for (color, _) in colors {
+        match kept
+            .iter()
+            .find(|&&k| channel_distance(k, color) <= options.tolerance)
+        {
+            Some(&target) => {
+                replacements.insert(color, target);
+                report.replaced.push(ColorReplacement {
+                    from: color,
+                    to: target,
+                });
+            }
+            None => kept.push(color),
+        }
+    }
+    for palette in palettes_mut(doc) {
+        for color in palette.entries.values_mut() {
+            if let Some(target) = replacements.get(color) {
+                *color = *target;
+            }
+        }
+    }
+    report
+        .replaced
+        .sort_by(|a, b| a.to.cmp(&b.to).then_with(|| a.from.cmp(&b.from)));
+
+    let mut by_color: BTreeMap<Color, Vec<char>> = BTreeMap::new();
+    if let Some(palette) = &doc.header.default_palette {
+        for (ch, color) in &palette.entries {
+            by_color.entry(*color).or_default().push(*ch);
+        }
+    }
+    for mut chars in by_color.into_values() {
+        chars.sort_unstable();
+        let Some((&keep, rest)) = chars.split_first() else {
+            continue;
+        };
+        for &ch in rest {
+            let same_everywhere = doc
+                .glyphs
+                .iter()
+                .all(|g| pixel_color(ch, g, doc) == pixel_color(keep, g, doc));
+            if same_everywhere && remap_char(doc, ch, keep).is_ok() {
+                report.merged_chars.insert(ch, keep);
+            }
+        }
+    }
+    report
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PaletteOptimization {
+    /// Glyph palette entries removed because the default palette has the same color.
+    pub removed_entries: usize,
+    /// Glyphs whose palette changed.
+    pub glyphs: Vec<String>,
+}
+
+/// Removes glyph palette entries identical to the default palette. Glyph
+/// palettes left empty are written without a `PALETTE` block.
+pub fn optimize_palettes(doc: &mut GtfDocument) -> PaletteOptimization {
+    let mut report = PaletteOptimization::default();
+    let default_palette = doc.header.default_palette.clone().unwrap_or_default();
+    for glyph in &mut doc.glyphs {
+        let Some(palette) = &mut glyph.palette else {
+            continue;
+        };
+        let before = palette.entries.len();
+        palette
+            .entries
+            .retain(|ch, color| default_palette.entries.get(ch) != Some(color));
+        let removed = before - palette.entries.len();
+        if removed > 0 {
+            report.removed_entries += removed;
+            report.glyphs.push(glyph.name.clone());
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gtf::test_util::{color, document, glyph, palette};
+    use crate::gtf::types::Glyph;
+
+    fn rows(bitmap: &[&str]) -> Vec<String> {
+        bitmap.iter().map(|r| r.to_string()).collect()
+    }
+
+    #[test]
+    fn remap_renames_the_char_everywhere() {
+        let mut doc = document(vec![glyph("A", &[".#", "#."]), glyph("B", &["..", ".."])]);
+        doc.header.roles.foreground = Some('#');
+        let report = remap_char(&mut doc, '#', '@').unwrap();
+
+        assert!(!report.merged);
+        assert_eq!(report.glyphs, vec!["A"]);
+        assert!(report.recolored.is_empty());
+        assert_eq!(doc.glyphs[0].bitmap, rows(&[".@", "@."]));
+        assert_eq!(
+            doc.header.default_palette,
+            Some(palette(&[('.', "#000000"), ('@', "#FFFFFF")]))
+        );
+        assert_eq!(doc.header.roles.foreground, Some('@'));
+        assert_eq!(doc.glyphs[0].palette, Some(Palette::default()));
+    }
+
+    #[test]
+    fn remap_onto_an_existing_char_merges_and_reports_recolored_glyphs() {
+        let red = Glyph {
+            palette: Some(palette(&[('x', "#FF0000")])),
+            ..glyph("B", &["x#"])
+        };
+        let mut doc = document(vec![glyph("A", &[".#"]), red]);
+        let report = remap_char(&mut doc, 'x', '#').unwrap();
+
+        assert!(report.merged);
+        assert_eq!(report.glyphs, vec!["B"]);
+        assert_eq!(report.recolored, vec!["B"]);
+        assert_eq!(doc.glyphs[1].bitmap, rows(&["##"]));
+        assert_eq!(doc.glyphs[1].palette, Some(Palette::default()));
+    }
+
+    #[test]
+    fn remap_keeps_glyph_colors_that_differ_from_the_default() {
+        let red = Glyph {
+            palette: Some(palette(&[('#', "#FF0000")])),
+            ..glyph("C", &["#."])
+        };
+        let mut doc = document(vec![glyph("A", &["#."]), red]);
+        remap_char(&mut doc, '#', '+').unwrap();
+
+        assert_eq!(doc.glyphs[1].bitmap, rows(&["+."]));
+        assert_eq!(doc.glyphs[1].palette, Some(palette(&[('+', "#FF0000")])));
+        assert_eq!(doc.glyphs[0].palette, Some(Palette::default()));
+        assert_eq!(
+            pixel_color('+', &doc.glyphs[0], &doc),
+            Some(color("#FFFFFF"))
+        );
+    }
+
+    #[test]
+    fn remap_rejects_invalid_chars() {
+        let mut doc = document(vec![glyph("A", &[".#"])]);
+        assert!(remap_char(&mut doc, '#', '#').is_err());
+        assert!(remap_char(&mut doc, '#', ' ').is_err());
+        assert!(remap_char(&mut doc, 'z', '#').is_err());
+    }
+
+    #[test]
+    fn replace_color_counts_changed_entries() {
+        let white = Glyph {
+            palette: Some(palette(&[('x', "#FFFFFF")])),
+            ..glyph("B", &["x."])
+        };
+        let mut doc = document(vec![glyph("A", &[".#"]), white]);
+        let replaced = replace_color(&mut doc, color("#FFFFFF"), color("#00FF00"));
+
+        assert_eq!(replaced, 2);
+        assert_eq!(
+            pixel_color('#', &doc.glyphs[0], &doc),
+            Some(color("#00FF00"))
+        );
+        assert_eq!(
+            pixel_color('x', &doc.glyphs[1], &doc),
+            Some(color("#00FF00"))
+        );
+    }
+
+    #[test]
+    fn similar_colors_snap_to_the_most_used_and_chars_fold() {
+        let mut doc = document(vec![glyph("A", &["..#", "o.#"])]);
+        doc.header.default_palette = Some(palette(&[
+            ('.', "#000000"),
+            ('o', "#010101"),
+            ('#', "#FFFFFF"),
+        ]));
+
+        let exact = merge_similar_colors(&mut doc, &ColorMergeOptions::default());
+        assert!(exact.replaced.is_empty() && exact.merged_chars.is_empty());
+
+        let report = merge_similar_colors(&mut doc, &ColorMergeOptions { tolerance: 1 });
+        assert_eq!(report.replaced.len(), 1);
+        assert_eq!(
+            (report.replaced[0].from, report.replaced[0].to),
+            (color("#010101"), color("#000000"))
+        );
+        assert_eq!(report.merged_chars, BTreeMap::from([('o', '.')]));
+        assert_eq!(doc.glyphs[0].bitmap, rows(&["..#", "..#"]));
+    }
+
+    #[test]
+    fn optimize_drops_entries_equal_to_the_default() {
+        let mixed = Glyph {
+            palette: Some(palette(&[('#', "#FFFFFF"), ('x', "#FF0000")])),
+            ..glyph("B", &["#x"])
+        };
+        let plain = Glyph {
+            palette: None,
+            ..glyph("A", &[".#"])
+        };
+        let mut doc = document(vec![plain, mixed]);
+        let report = optimize_palettes(&mut doc);
+
+        assert_eq!(report.removed_entries, 1);
+        assert_eq!(report.glyphs, vec!["B"]);
+        assert_eq!(doc.glyphs[1].palette, Some(palette(&[('x', "#FF0000")])));
+        assert_eq!(doc.glyphs[0].palette, None);
+    }
+}
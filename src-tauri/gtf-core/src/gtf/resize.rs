@@ -0,0 +1,358 @@
+//! Bitmap resizing shared by the `resize_bitmap` command and batch operations.
+//!
+//! The anchor decides where the old content sits in the new bitmap: growing a
+//! glyph anchored at `Bottom` adds rows at the top and keeps the baseline in
+//! place. Pixels added by growing get a fill character, normally the glyph's
+//! background (see `palette::background_char`).
+
+use serde::{Deserialize, Serialize};
+
+use super::bounds::cell_bitmap;
+use super::palette::{background_char, background_color, pixel_color};
+use super::types::{Glyph, GtfDocument, Offset, Size};
+
+/// Fill character when the background cannot be determined.
+pub const DEFAULT_FILL: char = '.';
+
+/// One of nine points of the bitmap that stays in place when resizing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Anchor {
+    #[default]
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    /// Position along (horizontal, vertical): 0 = start, 1 = middle, 2 = end.
+    fn position(self) -> (i64, i64) {
+        match self {
+            Anchor::TopLeft => (0, 0),
+            Anchor::Top => (1, 0),
+            Anchor::TopRight => (2, 0),
+            Anchor::Left => (0, 1),
+            Anchor::Center => (1, 1),
+            Anchor::Right => (2, 1),
+            Anchor::BottomLeft => (0, 2),
+            Anchor::Bottom => (1, 2),
+            Anchor::BottomRight => (2, 2),
+        }
+    }
+}
+
+/// Where the old content starts in the new bitmap along one axis; negative
+/// when content is cropped at the start.
+fn offset(old: u32, new: u32, position: i64) -> i64 {
+    (new as i64 - old as i64) * position / 2
+}
+
+fn check_size(size: &Size, which: &str) -> Result<(), String> {
+    if size.width == 0 || size.height == 0 {
+        return Err(format!(
+            "{} size dimensions must be greater than zero",
+            which
+        ));
+    }
+    Ok(())
+}
+
+/// Resizes a bitmap keeping `anchor` in place. Rows are first padded with
+/// `fill` (or truncated) to `old_size`, so ragged bitmaps are handled too.
+pub fn resize_bitmap_anchored(
+    bitmap: &[String],
+    old_size: &Size,
+    new_size: &Size,
+    anchor: Anchor,
+    fill: char,
+) -> Result<Vec<String>, String> {
+    check_size(new_size, "New")?;
+    check_size(old_size, "Old")?;
+
+    let (horizontal, vertical) = anchor.position();
+    let dx = offset(old_size.width, new_size.width, horizontal);
+    let dy = offset(old_size.height, new_size.height, vertical);
+    let old: Vec<Vec<char>> = bitmap.iter().map(|row| row.chars().collect()).collect();
+    let pixel = |row: i64, col: i64| -> char {
+        if row < 0 || col < 0 || row >= old_size.height as i64 || col >= old_size.width as i64 {
+            return fill;
+        }
+        old.get(row as usize)
+            .and_then(|r| r.get(col as usize))
+            .copied()
+            .unwrap_or(fill)
+    };
+
+    Ok((0..new_size.height as i64)
+        .map(|row| {
+            (0..new_size.width as i64)
+                .map(|col| pixel(row - dy, col - dx))
+                .collect()
+        })
+        .collect())
+}
+
+/// Resizes a bitmap to new dimensions, anchored at the top-left corner.
+/// - Height adjustment: Adds rows filled with `DEFAULT_FILL` ('.') or removes rows
+/// - Width adjustment: Pads rows with `DEFAULT_FILL` or truncates them
+pub fn resize_bitmap(
+    bitmap: Vec<String>,
+    old_size: Size,
+    new_size: Size,
+) -> Result<Vec<String>, String> {
+    resize_bitmap_anchored(&bitmap, &old_size, &new_size, Anchor::TopLeft, DEFAULT_FILL)
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FontHeightChange {
+    pub old_height: u32,
+    pub new_height: u32,
+    pub resized: usize,
+    /// Glyphs that lost drawn (non-background) pixels.
+    pub clipped: Vec<String>,
+}
+
+/// Font height the change is measured from: `DEFAULT_SIZE`, otherwise the
+/// most common glyph height.
+fn font_height(doc: &GtfDocument) -> Option<u32> {
+    if let Some(size) = &doc.header.default_size {
+        return Some(size.height);
+    }
+    let mut counts = std::collections::BTreeMap::<u32, usize>::new();
+    for glyph in &doc.glyphs {
+        *counts.entry(glyph.cell_size().height).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .max_by_key(|(_, count)| *count)
+        .map(|(height, _)| height)
+}
+
+/// Trimmed glyph with `delta` rows added to (or removed from) its cell: the
+/// bitmap stays as it is and `OFFSET` moves with the rows added above it.
+fn resize_trimmed_cell(glyph: &Glyph, delta: i64, anchor: Anchor) -> Result<Glyph, String> {
+    let cell = glyph.cell_size();
+    let new_height = cell.height as i64 + delta;
+    if new_height <= 0 {
+        return Err(format!(
+            "Glyph '{}' is only {} rows high and cannot lose {}",
+            glyph.name, cell.height, -delta
+        ));
+    }
+    let (_, vertical) = anchor.position();
+    let dy = offset(cell.height, new_height as u32, vertical);
+    let position = glyph.offset.unwrap_or_default();
+    let mut moved = glyph.clone();
+    moved.offset = Some(Offset {
+        x: position.x,
+        y: position.y + dy as i32,
+    });
+    moved.cell = Some(Size {
+        width: cell.width,
+        height: new_height as u32,
+    });
+    Ok(moved)
+}
+
+/// Changes the height of the whole font to `height`. Every glyph gains (or
+/// loses) the same rows above and below, split by the vertical part of
+/// `anchor`, so baselines stay aligned; glyphs taller or shorter than the font
+/// keep their difference. Trimmed glyphs keep their bitmap, only their `CELL`
+/// and `OFFSET` change. `DEFAULT_SIZE` is updated when present.
+pub fn change_font_height(
+    doc: &mut GtfDocument,
+    height: u32,
+    anchor: Anchor,
+) -> Result<FontHeightChange, String> {
+    if height == 0 {
+        return Err("Font height must be greater than zero".to_string());
+    }
+    let old_height = font_height(doc).ok_or("The font has no glyphs and no DEFAULT_SIZE")?;
+    let delta = height as i64 - old_height as i64;
+    let mut report = FontHeightChange {
+        old_height,
+        new_height: height,
+        ..Default::default()
+    };
+    if delta == 0 {
+        return Ok(report);
+    }
+
+    let background = background_color(doc);
+    let count_ink = |rows: &[String], glyph: &Glyph| {
+        rows.iter()
+            .flat_map(|row| row.chars())
+            .filter(|&ch| pixel_color(ch, glyph, doc) != background)
+            .count()
+    };
+    let mut resized = Vec::with_capacity(doc.glyphs.len());
+    for glyph in &doc.glyphs {
+        let fill = background
+            .and_then(|background| background_char(glyph, doc, background))
+            .unwrap_or(DEFAULT_FILL);
+        if glyph.offset.is_some() || glyph.cell.is_some() {
+            let moved = resize_trimmed_cell(glyph, delta, anchor)?;
+            // Pixels moved out of the cell are kept but no longer drawn
+            if delta < 0
+                && count_ink(&cell_bitmap(&moved, fill), glyph)
+                    < count_ink(&cell_bitmap(glyph, fill), glyph)
+            {
+                report.clipped.push(glyph.name.clone());
+            }
+            resized.push(moved);
+            report.resized += 1;
+            continue;
+        }
+        let old_size = Size {
+            width: glyph
+                .bitmap
+                .iter()
+                .map(|row| row.chars().count())
+                .max()
+                .unwrap_or(0) as u32,
+            height: glyph.bitmap.len() as u32,
+        };
+        let new_glyph_height = old_size.height as i64 + delta;
+        if old_size.width == 0 || old_size.height == 0 {
+            resized.push(glyph.clone());
+            continue;
+        }
+        if new_glyph_height <= 0 {
+            return Err(format!(
+                "Glyph '{}' is only {} rows high and cannot lose {}",
+                glyph.name, old_size.height, -delta
+            ));
+        }
+        let new_size = Size {
+            width: old_size.width,
+            height: new_glyph_height as u32,
+        };
+        let bitmap = resize_bitmap_anchored(&glyph.bitmap, &old_size, &new_size, anchor, fill)?;
+
+        if delta < 0 && count_ink(&bitmap, glyph) < count_ink(&glyph.bitmap, glyph) {
+            report.clipped.push(glyph.name.clone());
+        }
+
+        let mut glyph = glyph.clone();
+        if glyph.size.is_some() {
+            glyph.size = Some(new_size);
+        }
+        glyph.bitmap = bitmap;
+        resized.push(glyph);
+        report.resized += 1;
+    }
+
+    doc.glyphs = resized;
+    if let Some(size) = &mut doc.header.default_size {
+        size.height = height;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gtf::bounds::trim_glyph;
+    use crate::gtf::test_util::{document, glyph};
+
+    fn rows(bitmap: &[&str]) -> Vec<String> {
+        bitmap.iter().map(|r| r.to_string()).collect()
+    }
+
+    fn size(width: u32, height: u32) -> Size {
+        Size { width, height }
+    }
+
+    #[test]
+    fn anchor_keeps_its_point_in_place() {
+        let bitmap = rows(&["#.", ".#"]);
+        let grown = |anchor| {
+            resize_bitmap_anchored(&bitmap, &size(2, 2), &size(4, 3), anchor, '-').unwrap()
+        };
+        assert_eq!(grown(Anchor::TopLeft), rows(&["#.--", ".#--", "----"]));
+        assert_eq!(grown(Anchor::Center), rows(&["-#.-", "-.#-", "----"]));
+        assert_eq!(grown(Anchor::BottomRight), rows(&["----", "--#.", "--.#"]));
+
+        let cropped =
+            resize_bitmap_anchored(&bitmap, &size(2, 2), &size(1, 1), Anchor::BottomRight, '-');
+        assert_eq!(cropped.unwrap(), rows(&["#"]));
+        assert!(
+            resize_bitmap_anchored(&bitmap, &size(2, 2), &size(0, 1), Anchor::Top, '-').is_err()
+        );
+    }
+
+    #[test]
+    fn ragged_rows_are_padded_to_the_old_size() {
+        let bitmap = rows(&["#", "###"]);
+        let resized =
+            resize_bitmap_anchored(&bitmap, &size(3, 2), &size(3, 2), Anchor::TopLeft, '.');
+        assert_eq!(resized.unwrap(), rows(&["#..", "###"]));
+    }
+
+    #[test]
+    fn font_height_keeps_baselines_aligned() {
+        let mut doc = document(vec![
+            glyph("A", &[".#.", "#.#", "###"]),
+            glyph("g", &["...", "##.", ".#.", "#.."]),
+        ]);
+        let change = change_font_height(&mut doc, 4, Anchor::Bottom).unwrap();
+
+        assert_eq!(
+            (change.old_height, change.new_height, change.resized),
+            (3, 4, 2)
+        );
+        assert_eq!(doc.header.default_size, Some(size(3, 4)));
+        assert_eq!(doc.glyphs[0].bitmap, rows(&["...", ".#.", "#.#", "###"]));
+        assert_eq!(doc.glyphs[1].bitmap.len(), 5);
+        assert_eq!(doc.glyphs[1].size, Some(size(3, 5)));
+    }
+
+    #[test]
+    fn shrinking_reports_clipped_glyphs() {
+        let mut doc = document(vec![
+            glyph("A", &[".#.", "#.#", "###"]),
+            glyph("B", &["...", "...", "###"]),
+        ]);
+        let change = change_font_height(&mut doc, 2, Anchor::Bottom).unwrap();
+
+        assert_eq!(change.clipped, vec!["A"]);
+        assert_eq!(doc.glyphs[1].bitmap, rows(&["...", "###"]));
+        assert!(change_font_height(&mut doc, 0, Anchor::Bottom).is_err());
+    }
+
+    #[test]
+    fn trimmed_glyphs_stay_trimmed() {
+        let full = glyph("A", &["...", ".#.", "#.#", "..."]);
+        let mut doc = document(vec![
+            full.clone(),
+            glyph("B", &["...", "...", "...", "###"]),
+        ]);
+        let background = background_color(&doc);
+        doc.glyphs[0] = trim_glyph(&full, &doc, background).unwrap();
+
+        change_font_height(&mut doc, 6, Anchor::Bottom).unwrap();
+        let a = &doc.glyphs[0];
+        assert_eq!(a.bitmap, rows(&[".#.", "#.#"]));
+        assert_eq!(a.offset, Some(Offset { x: 0, y: 3 }));
+        assert_eq!(a.cell, Some(size(3, 6)));
+        assert_eq!(
+            cell_bitmap(a, '.'),
+            rows(&["...", "...", "...", ".#.", "#.#", "..."])
+        );
+
+        // Shrinking moves the bitmap up and reports pixels cut off by the cell
+        let change = change_font_height(&mut doc, 2, Anchor::Bottom).unwrap();
+        assert_eq!(change.clipped, vec!["A"]);
+        let a = &doc.glyphs[0];
+        assert_eq!(a.offset, Some(Offset { x: 0, y: -1 }));
+        assert_eq!(a.cell, Some(size(3, 2)));
+        assert_eq!(cell_bitmap(a, '.'), rows(&["#.#", "..."]));
+        assert_eq!(doc.glyphs[1].bitmap, rows(&["...", "###"]));
+    }
+}
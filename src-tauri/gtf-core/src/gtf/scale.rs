@@ -0,0 +1,455 @@
+//! Whole-font scaling for larger or smaller panels.
+//!
+//! Upscaling never mixes colors: every output pixel is a copy of an input
+//! pixel, so results stay within the palette. Upscaling factors are limited
+//! to `MAX_FACTOR`, the result grows with the square of the factor.
+//! - `Nearest`: every pixel becomes a `factor` x `factor` block.
+//! - `Epx`: Scale2x / Scale3x (EPX) for factors 2 and 3, Scale2x twice for 4.
+//!   Rounds off stair steps of diagonal lines.
+//! - `Smooth`: the EPX corner rule for any factor, filling a triangle of each
+//!   block corner instead of one pixel, which gives smoother diagonals at
+//!   large factors (in the spirit of hqNx, but without blending).
+//!
+//! `Downscale` maps every `factor` x `factor` block to one pixel by majority or
+//! by an ink threshold; ink is any pixel whose color differs from the font
+//! background. Pixels are compared by character, and everything outside a
+//! bitmap counts as background. Trimmed glyphs are scaled as whole cells.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::bounds::untrim_glyph;
+use super::color::Color;
+use super::palette::{background_char, background_color, pixel_color};
+use super::resize::DEFAULT_FILL;
+use super::types::{Glyph, GtfDocument, Size};
+
+/// Largest `Nearest` / `Smooth` factor.
+pub const MAX_FACTOR: u32 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum DownscaleRule {
+    /// Most common pixel of the block; ties prefer ink so thin strokes survive.
+    Majority,
+    /// Ink when at least `percent` of the block is ink, background otherwise.
+    Threshold { percent: u32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "algorithm", rename_all = "snake_case")]
+pub enum ScaleMode {
+    Nearest { factor: u32 },
+    Epx { factor: u32 },
+    Smooth { factor: u32 },
+    Downscale { factor: u32, rule: DownscaleRule },
+}
+
+impl ScaleMode {
+    fn factor(&self) -> u32 {
+        match *self {
+            ScaleMode::Nearest { factor }
+            | ScaleMode::Epx { factor }
+            | ScaleMode::Smooth { factor }
+            | ScaleMode::Downscale { factor, .. } => factor,
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        match *self {
+            ScaleMode::Epx { factor } if !(2..=4).contains(&factor) => {
+                Err(format!("EPX scales by 2, 3 or 4, got {}", factor))
+            }
+            ScaleMode::Nearest { factor } | ScaleMode::Smooth { factor }
+                if !(2..=MAX_FACTOR).contains(&factor) =>
+            {
+                Err(format!(
+                    "Scale factor must be between 2 and {}, got {}",
+                    MAX_FACTOR, factor
+                ))
+            }
+            ScaleMode::Downscale {
+                rule: DownscaleRule::Threshold { percent },
+                ..
+            } if percent == 0 || percent > 100 => Err(format!(
+                "Threshold must be between 1 and 100 percent, got {}",
+                percent
+            )),
+            _ if self.factor() < 2 => Err("Scale factor must be at least 2".to_string()),
+            _ => Ok(()),
+        }
+    }
+
+    /// New length of a side of `len` pixels.
+    fn scaled(&self, len: u32) -> u32 {
+        match self {
+            ScaleMode::Downscale { factor, .. } => len.div_ceil(*factor),
+            _ => len * self.factor(),
+        }
+    }
+}
+
+/// Pixels of a glyph; everything outside the bitmap is background.
+struct Grid {
+    pixels: Vec<Vec<char>>,
+    fill: char,
+}
+
+impl Grid {
+    fn at(&self, row: i64, col: i64) -> char {
+        if row < 0 || col < 0 {
+            return self.fill;
+        }
+        self.pixels
+            .get(row as usize)
+            .and_then(|r| r.get(col as usize))
+            .copied()
+            .unwrap_or(self.fill)
+    }
+}
+
+fn nearest(grid: &Grid, n: usize) -> Vec<Vec<char>> {
+    grid.pixels
+        .iter()
+        .flat_map(|row| {
+            let wide: Vec<char> = row
+                .iter()
+                .flat_map(|&ch| std::iter::repeat_n(ch, n))
+                .collect();
+            std::iter::repeat_n(wide, n)
+        })
+        .collect()
+}
+
+fn scale2x(grid: &Grid) -> Vec<Vec<char>> {
+    let mut out = nearest(grid, 2);
+    for (r, row) in grid.pixels.iter().enumerate() {
+        for c in 0..row.len() {
+            let (ri, ci) = (r as i64, c as i64);
+            let p = grid.pixels[r][c];
+            let a = grid.at(ri - 1, ci);
+            let b = grid.at(ri, ci + 1);
+            let cc = grid.at(ri, ci - 1);
+            let d = grid.at(ri + 1, ci);
+            let (or, oc) = (2 * r, 2 * c);
+            out[or][oc] = if cc == a && cc != d && a != b { a } else { p };
+            out[or][oc + 1] = if a == b && a != cc && b != d { b } else { p };
+            out[or + 1][oc] = if d == cc && d != b && cc != a { cc } else { p };
+            out[or + 1][oc + 1] = if b == d && b != a && d != cc { d } else { p };
+        }
+    }
+    out
+}
+
+fn scale3x(grid: &Grid) -> Vec<Vec<char>> {
+    let mut out = nearest(grid, 3);
+    for (r, row) in grid.pixels.iter().enumerate() {
+        for c in 0..row.len() {
+            let (ri, ci) = (r as i64, c as i64);
+            let px = |dr: i64, dc: i64| grid.at(ri + dr, ci + dc);
+            let (a, b, c3) = (px(-1, -1), px(-1, 0), px(-1, 1));
+            let (d, e, f) = (px(0, -1), px(0, 0), px(0, 1));
+            let (g, h, i) = (px(1, -1), px(1, 0), px(1, 1));
+            let pick = |cond: bool, v: char| if cond { v } else { e };
+            let block = [
+                pick(d == b && b != f && d != h, d),
+                pick(
+                    (d == b && b != f && d != h && e != c3)
+                        || (b == f && b != d && f != h && e != a),
+                    b,
+                ),
+                pick(b == f && b != d && f != h, f),
+                pick(
+                    (d == b && b != f && d != h && e != g)
+                        || (d == h && d != b && h != f && e != a),
+                    d,
+                ),
+                e,
+                pick(
+                    (b == f && b != d && f != h && e != i)
+                        || (h == f && d != h && b != f && e != c3),
+                    f,
+                ),
+                pick(d == h && d != b && h != f, d),
+                pick(
+                    (d == h && d != b && h != f && e != i)
+                        || (h == f && d != h && b != f && e != g),
+                    h,
+                ),
+                pick(h == f && d != h && b != f, f),
+            ];
+            for (k, &ch) in block.iter().enumerate() {
+                out[3 * r + k / 3][3 * c + k % 3] = ch;
+            }
+        }
+    }
+    out
+}
+
+fn smooth(grid: &Grid, n: usize) -> Vec<Vec<char>> {
+    let mut out = nearest(grid, n);
+    for (r, row) in grid.pixels.iter().enumerate() {
+        for c in 0..row.len() {
+            let (ri, ci) = (r as i64, c as i64);
+            let up = grid.at(ri - 1, ci);
+            let right = grid.at(ri, ci + 1);
+            let left = grid.at(ri, ci - 1);
+            let down = grid.at(ri + 1, ci);
+            // (corner color, condition, flip rows, flip columns) per block corner
+            let corners = [
+                (up, left == up && left != down && up != right, false, false),
+                (
+                    right,
+                    up == right && up != left && right != down,
+                    false,
+                    true,
+                ),
+                (
+                    left,
+                    down == left && down != right && left != up,
+                    true,
+                    false,
+                ),
+                (
+                    down,
+                    right == down && right != up && down != left,
+                    true,
+                    true,
+                ),
+            ];
+            for (color, applies, flip_rows, flip_cols) in corners {
+                if !applies {
+                    continue;
+                }
+                for i in 0..n {
+                    for j in 0..n {
+                        if 2 * (i + j) >= n {
+                            continue;
+                        }
+                        let orow = if flip_rows { n - 1 - i } else { i };
+                        let ocol = if flip_cols { n - 1 - j } else { j };
+                        out[r * n + orow][c * n + ocol] = color;
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+fn downscale(
+    grid: &Grid,
+    n: usize,
+    rule: DownscaleRule,
+    is_ink: &dyn Fn(char) -> bool,
+) -> Vec<Vec<char>> {
+    let (pixels, fill) = (&grid.pixels, grid.fill);
+    let height = pixels.len();
+    let width = pixels.first().map_or(0, |row| row.len());
+    (0..height.div_ceil(n))
+        .map(|br| {
+            (0..width.div_ceil(n))
+                .map(|bc| {
+                    let block: Vec<char> = (br * n..((br + 1) * n).min(height))
+                        .flat_map(|r| (bc * n..((bc + 1) * n).min(width)).map(move |c| (r, c)))
+                        .map(|(r, c)| pixels[r][c])
+                        .collect();
+                    let mut counts: BTreeMap<char, usize> = BTreeMap::new();
+                    for &ch in &block {
+                        *counts.entry(ch).or_default() += 1;
+                    }
+                    // Most common first; ties prefer ink, then the lower character
+                    let best = |ink_only: bool| {
+                        counts
+                            .iter()
+                            .filter(|(ch, _)| !ink_only || is_ink(**ch))
+                            .max_by(|a, b| {
+                                a.1.cmp(b.1)
+                                    .then_with(|| is_ink(*a.0).cmp(&is_ink(*b.0)))
+                                    .then_with(|| b.0.cmp(a.0))
+                            })
+                            .map(|(ch, _)| *ch)
+                    };
+                    match rule {
+                        DownscaleRule::Majority => best(false).unwrap_or(fill),
+                        DownscaleRule::Threshold { percent } => {
+                            let ink = block.iter().filter(|&&ch| is_ink(ch)).count();
+                            if ink * 100 >= percent as usize * block.len() {
+                                best(true).unwrap_or(fill)
+                            } else {
+                                fill
+                            }
+                        }
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Scaled copy of a glyph of `doc` with `SIZE` matching the new bitmap.
+pub fn scale_glyph(glyph: &Glyph, doc: &GtfDocument, mode: &ScaleMode) -> Result<Glyph, String> {
+    mode.validate()?;
+    scale_with_background(glyph, doc, mode, background_color(doc))
+}
+
+/// `scale_glyph` with the font background looked up by the caller, once per font.
+fn scale_with_background(
+    glyph: &Glyph,
+    doc: &GtfDocument,
+    mode: &ScaleMode,
+    background: Option<Color>,
+) -> Result<Glyph, String> {
+    let fill = background
+        .and_then(|background| background_char(glyph, doc, background))
+        .unwrap_or(DEFAULT_FILL);
+    let untrimmed = untrim_glyph(glyph, fill);
+    let glyph = untrimmed.as_ref();
+    let width = glyph
+        .bitmap
+        .iter()
+        .map(|row| row.chars().count())
+        .max()
+        .unwrap_or(0);
+    let grid = Grid {
+        pixels: glyph
+            .bitmap
+            .iter()
+            .map(|row| {
+                let mut pixels: Vec<char> = row.chars().collect();
+                pixels.resize(width, fill);
+                pixels
+            })
+            .collect(),
+        fill,
+    };
+
+    let scaled = if grid.pixels.is_empty() || width == 0 {
+        grid.pixels
+    } else {
+        let n = mode.factor() as usize;
+        match *mode {
+            ScaleMode::Nearest { .. } => nearest(&grid, n),
+            ScaleMode::Epx { factor: 3 } => scale3x(&grid),
+            ScaleMode::Epx { factor: 4 } => scale2x(&Grid {
+                pixels: scale2x(&grid),
+                fill,
+            }),
+            ScaleMode::Epx { .. } => scale2x(&grid),
+            ScaleMode::Smooth { .. } => smooth(&grid, n),
+            ScaleMode::Downscale { rule, .. } => {
+                let is_ink = |ch: char| pixel_color(ch, glyph, doc) != background;
+                downscale(&grid, n, rule, &is_ink)
+            }
+        }
+    };
+
+    let mut result = glyph.clone();
+    if let Some(size) = &glyph.size {
+        result.size = Some(Size {
+            width: mode.scaled(size.width),
+            height: mode.scaled(size.height),
+        });
+    }
+    result.bitmap = scaled
+        .into_iter()
+        .map(|row| row.into_iter().collect())
+        .collect();
+    Ok(result)
+}
+
+/// New document with every glyph scaled and `DEFAULT_SIZE` updated.
+pub fn scale_document(doc: &GtfDocument, mode: &ScaleMode) -> Result<GtfDocument, String> {
+    mode.validate()?;
+    let background = background_color(doc);
+    let mut result = doc.clone();
+    result.glyphs = doc
+        .glyphs
+        .iter()
+        .map(|glyph| scale_with_background(glyph, doc, mode, background))
+        .collect::<Result<_, _>>()?;
+    if let Some(size) = &mut result.header.default_size {
+        size.width = mode.scaled(size.width);
+        size.height = mode.scaled(size.height);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gtf::test_util::{document, glyph};
+
+    fn scaled(rows: &[&str], mode: ScaleMode) -> Vec<String> {
+        // A blank glyph keeps '.' the background even for ink-heavy test bitmaps
+        let doc = document(vec![glyph("A", rows), glyph("blank", &["....", "...."])]);
+        scale_glyph(&doc.glyphs[0], &doc, &mode).unwrap().bitmap
+    }
+
+    #[test]
+    fn nearest_pads_ragged_rows_with_the_background() {
+        assert_eq!(
+            scaled(&["#", ".#"], ScaleMode::Nearest { factor: 2 }),
+            vec!["##..", "##..", "..##", "..##"]
+        );
+    }
+
+    #[test]
+    fn epx_rounds_off_diagonals() {
+        assert_eq!(
+            scaled(&["#.", ".#"], ScaleMode::Epx { factor: 2 }),
+            vec!["##..", "###.", ".###", "..##"]
+        );
+        assert_eq!(scaled(&["#."], ScaleMode::Epx { factor: 4 }).len(), 4);
+        assert_eq!(
+            scaled(&["#."], ScaleMode::Smooth { factor: 5 })[0].len(),
+            10
+        );
+    }
+
+    #[test]
+    fn downscale_by_majority_or_threshold() {
+        let rows = ["##..", "##..", "....", "...#"];
+        let majority = ScaleMode::Downscale {
+            factor: 2,
+            rule: DownscaleRule::Majority,
+        };
+        let threshold = ScaleMode::Downscale {
+            factor: 2,
+            rule: DownscaleRule::Threshold { percent: 25 },
+        };
+        assert_eq!(scaled(&rows, majority), vec!["#.", ".."]);
+        assert_eq!(scaled(&rows, threshold), vec!["#.", ".#"]);
+        // Ragged input counts the missing pixels as background
+        assert_eq!(scaled(&["###", "#"], majority), vec!["##"]);
+    }
+
+    #[test]
+    fn factors_are_limited() {
+        let invalid = [
+            ScaleMode::Nearest { factor: 1 },
+            ScaleMode::Nearest {
+                factor: MAX_FACTOR + 1,
+            },
+            ScaleMode::Smooth { factor: 1000 },
+            ScaleMode::Epx { factor: 5 },
+            ScaleMode::Downscale {
+                factor: 2,
+                rule: DownscaleRule::Threshold { percent: 0 },
+            },
+        ];
+        let doc = document(vec![glyph("A", &["#."])]);
+        for mode in invalid {
+            assert!(scale_document(&doc, &mode).is_err(), "{:?}", mode);
+        }
+        let doubled = scale_document(&doc, &ScaleMode::Smooth { factor: 2 }).unwrap();
+        assert_eq!(
+            doubled.header.default_size,
+            Some(Size {
+                width: 4,
+                height: 2
+            })
+        );
+    }
+}
@@ -0,0 +1,252 @@
+use std::fmt::Write; // Required for writeln!
+
+use serde::{Deserialize, Serialize};
+
+use super::types::{Glyph, GtfDocument, Palette};
+
+/// How glyph `PALETTE` blocks are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PaletteOutput {
+    /// Only entries that differ from `DEFAULT_PALETTE` (spec §5.2).
+    #[default]
+    Optimized,
+    /// The full effective palette of every glyph, for tools without inheritance.
+    Expanded,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SerializeOptions {
+    #[serde(default)]
+    pub palette_output: PaletteOutput,
+}
+
+// --- Serialization Logic ---
+
+/// Writes `document` with optimized glyph palettes.
+pub fn serialize_gtf_document(document: &GtfDocument) -> Result<String, String> {
+    serialize_gtf_document_with(document, &SerializeOptions::default())
+}
+
+pub fn serialize_gtf_document_with(
+    document: &GtfDocument,
+    options: &SerializeOptions,
+) -> Result<String, String> {
+    let mut output = String::new();
+
+    // --- Serialize Header ---
+    writeln!(output, "HEADER").map_err(|e| format!("Failed to write HEADER: {}", e))?;
+
+    if let Some(name) = &document.header.font_name {
+        writeln!(output, "FONT {}", name).map_err(|e| format!("Failed to write FONT: {}", e))?;
+    }
+
+    if let Some(version) = &document.header.version {
+        writeln!(output, "VERSION {}", version)
+            .map_err(|e| format!("Failed to write VERSION: {}", e))?;
+    }
+
+    if let Some(author) = &document.header.author {
+        writeln!(output, "AUTHOR {}", author)
+            .map_err(|e| format!("Failed to write AUTHOR: {}", e))?;
+    }
+
+    if let Some(description) = &document.header.description {
+        let single_line_description = description.replace('\n', " ");
+        writeln!(output, "DESCRIPTION {}", single_line_description)
+            .map_err(|e| format!("Failed to write DESCRIPTION: {}", e))?;
+    }
+
+    // Serialize Default Size if present
+    if let Some(size) = &document.header.default_size {
+        writeln!(output, "DEFAULT_SIZE {}x{}", size.width, size.height)
+            .map_err(|e| format!("Failed to write DEFAULT_SIZE: {}", e))?;
+    }
+
+    // Palette roles go before DEFAULT_PALETTE, whose block runs to END HEADER
+    if let Some(ch) = document.header.roles.background {
+        writeln!(output, "BACKGROUND {}", ch)
+            .map_err(|e| format!("Failed to write BACKGROUND: {}", e))?;
+    }
+    if let Some(ch) = document.header.roles.foreground {
+        writeln!(output, "FOREGROUND {}", ch)
+            .map_err(|e| format!("Failed to write FOREGROUND: {}", e))?;
+    }
+
+    // Serialize Default Palette if present and not empty
+    if let Some(def_palette) = &document.header.default_palette {
+        if !def_palette.entries.is_empty() {
+            writeln!(output, "DEFAULT_PALETTE")
+                .map_err(|e| format!("Failed to write DEFAULT_PALETTE line: {}", e))?;
+
+            // sort palette entries by character
+            let mut sorted_entries: Vec<_> = def_palette.entries.iter().collect();
+            sorted_entries.sort_by_key(|(k, _)| *k);
+
+            for (char, color) in sorted_entries {
+                writeln!(output, "{} {}", char, color)
+                    .map_err(|e| format!("Failed to write palette entry for '{}': {}", char, e))?;
+            }
+        }
+    }
+
+    writeln!(output, "END HEADER").map_err(|e| format!("Failed to write END HEADER: {}", e))?;
+    writeln!(output).map_err(|e| format!("Failed to write blank line after header: {}", e))?;
+
+    // --- Serialize Glyphs ---
+    let default_palette = document.header.default_palette.as_ref();
+    for glyph in &document.glyphs {
+        let palette = glyph_palette_entries(glyph, default_palette, options.palette_output);
+        serialize_glyph(&mut output, glyph, &palette)?;
+    }
+
+    Ok(output.trim_end().to_string()) // Return the final string, removing trailing newline
+}
+
+/// Entries written to the glyph `PALETTE` block: the overrides, or the
+/// default palette with the overrides on top.
+fn glyph_palette_entries(
+    glyph: &Glyph,
+    default_palette: Option<&Palette>,
+    palette_output: PaletteOutput,
+) -> Palette {
+    let default_entries = default_palette.map(|p| &p.entries);
+    let own = glyph.palette.as_ref().map(|p| &p.entries);
+    let entries = match palette_output {
+        PaletteOutput::Optimized => own
+            .into_iter()
+            .flatten()
+            .filter(|(ch, color)| default_entries.and_then(|d| d.get(ch)) != Some(*color))
+            .map(|(ch, color)| (*ch, *color))
+            .collect(),
+        PaletteOutput::Expanded => default_entries
+            .into_iter()
+            .flatten()
+            .chain(own.into_iter().flatten())
+            .map(|(ch, color)| (*ch, *color))
+            .collect(),
+    };
+    Palette { entries }
+}
+
+fn serialize_glyph(output: &mut String, glyph: &Glyph, palette: &Palette) -> Result<(), String> {
+    writeln!(output, "GLYPH {}", glyph.name)
+        .map_err(|e| format!("Failed to write GLYPH for '{}': {}", glyph.name, e))?;
+
+    // Write Metadata
+    if let Some(unicode) = &glyph.unicode {
+        writeln!(output, "UNICODE {}", unicode)
+            .map_err(|e| format!("Failed to write UNICODE for '{}': {}", glyph.name, e))?;
+    }
+    if let Some(char_repr) = glyph.char_repr {
+        writeln!(output, "CHAR {}", char_repr)
+            .map_err(|e| format!("Failed to write CHAR for '{}': {}", glyph.name, e))?;
+    }
+    if let Some(size) = &glyph.size {
+        writeln!(output, "SIZE {}x{}", size.width, size.height)
+            .map_err(|e| format!("Failed to write SIZE for '{}': {}", glyph.name, e))?;
+    }
+    if let Some(offset) = &glyph.offset {
+        writeln!(output, "OFFSET {},{}", offset.x, offset.y)
+            .map_err(|e| format!("Failed to write OFFSET for '{}': {}", glyph.name, e))?;
+    }
+    if let Some(cell) = &glyph.cell {
+        writeln!(output, "CELL {}x{}", cell.width, cell.height)
+            .map_err(|e| format!("Failed to write CELL for '{}': {}", glyph.name, e))?;
+    }
+
+    // Write Palette Block (if entries exist)
+    if !palette.entries.is_empty() {
+        writeln!(output, "PALETTE")
+            .map_err(|e| format!("Failed to write PALETTE for '{}': {}", glyph.name, e))?;
+
+        // sort palette entries by character
+        let mut sorted_entries: Vec<_> = palette.entries.iter().collect();
+        sorted_entries.sort_by_key(|(k, _)| *k);
+
+        for (char, color) in sorted_entries {
+            writeln!(output, "{} {}", char, color).map_err(|e| {
+                format!("Failed to write palette entry for '{}': {}", glyph.name, e)
+            })?;
+        }
+        // Write END PALETTE
+        writeln!(output, "END PALETTE")
+            .map_err(|e| format!("Failed to write END PALETTE for '{}': {}", glyph.name, e))?;
+    }
+
+    // Write DATA section (if size is defined OR bitmap exists)
+    let has_bitmap_data = !glyph.bitmap.is_empty();
+    let has_size = glyph.size.is_some();
+
+    if has_size || has_bitmap_data {
+        // Write DATA keyword
+        writeln!(output, "DATA")
+            .map_err(|e| format!("Failed to write DATA for '{}': {}", glyph.name, e))?;
+
+        // Write Bitmap lines (write what exists)
+        for row in &glyph.bitmap {
+            writeln!(output, "{}", row)
+                .map_err(|e| format!("Failed to write bitmap line for '{}': {}", glyph.name, e))?;
+        }
+
+        // Write END DATA
+        writeln!(output, "END DATA")
+            .map_err(|e| format!("Failed to write END DATA for '{}': {}", glyph.name, e))?;
+    }
+
+    // Write END GLYPH
+    writeln!(output, "END GLYPH {}", glyph.name)
+        .map_err(|e| format!("Failed to write END GLYPH for '{}': {}", glyph.name, e))?;
+    writeln!(output).map_err(|e| {
+        format!(
+            "Failed to write blank line after glyph '{}': {}",
+            glyph.name, e
+        )
+    })?; // Blank line after each glyph block
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gtf::parse::parse_gtf_content;
+    use crate::gtf::test_util::{document, glyph, palette};
+
+    fn font() -> GtfDocument {
+        let own = Glyph {
+            palette: Some(palette(&[('x', "#FF0000"), ('#', "#00FF00")])),
+            ..glyph("B", &["x#.", "#x."])
+        };
+        document(vec![glyph("A", &[".#.", "#.#"]), own])
+    }
+
+    fn round_trip(doc: &GtfDocument, palette_output: PaletteOutput) -> (String, GtfDocument) {
+        let options = SerializeOptions { palette_output };
+        let text = serialize_gtf_document_with(doc, &options).unwrap();
+        let parsed = parse_gtf_content(&text).unwrap();
+        (text, parsed)
+    }
+
+    #[test]
+    fn optimized_output_writes_only_overrides_and_parses_back() {
+        let doc = font();
+        let (text, parsed) = round_trip(&doc, PaletteOutput::Optimized);
+
+        // A inherits everything, so its DATA follows the metadata directly
+        assert!(text.contains("SIZE 3x2\nDATA\n.#."));
+        assert_eq!(text.matches("END PALETTE").count(), 1);
+        assert_eq!(parsed, doc);
+    }
+
+    #[test]
+    fn expanded_output_writes_full_palettes_and_parses_back() {
+        let doc = font();
+        let (text, parsed) = round_trip(&doc, PaletteOutput::Expanded);
+
+        assert_eq!(text.matches("END PALETTE").count(), 2);
+        assert!(text.contains("PALETTE\n# #FFFFFF\n. #000000\nEND PALETTE"));
+        assert!(text.contains("PALETTE\n# #00FF00\n. #000000\nx #FF0000\nEND PALETTE"));
+        assert_eq!(parsed, doc);
+    }
+}
@@ -0,0 +1,286 @@
+//! Exact and near-duplicate glyph detection.
+//!
+//! Bitmaps are normalized to colors first (`palette::pixel_color`), so two
+//! glyphs drawing the same picture with different palette characters still
+//! match. Exact duplicates share a hash of the normalized bitmap; near
+//! duplicates are bitmaps of the same size differing in at most
+//! `max_distance` pixels (Hamming distance). Clusters are single-linkage: a
+//! glyph joins a cluster when it is close enough to any of its members.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+
+use serde::{Deserialize, Serialize};
+
+use super::color::Color;
+use super::palette::{background_color, pixel_color};
+use super::types::{Glyph, GtfDocument};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SimilarityOptions {
+    /// Maximum number of differing pixels for near duplicates; 0 finds exact ones only.
+    #[serde(default)]
+    pub max_distance: u32,
+    /// Also cluster glyphs without ink (spaces), which are usually intentional.
+    #[serde(default)]
+    pub include_blank: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateMember {
+    pub name: String,
+    pub unicode: Option<String>,
+    pub char_repr: Option<char>,
+    /// Hash of the normalized bitmap; equal hashes mean identical pictures.
+    pub hash: String,
+    /// Differing pixels compared to the first member of the cluster.
+    pub distance: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateCluster {
+    /// `true` when all members have identical bitmaps.
+    pub exact: bool,
+    /// In document order; the first member is the one to keep when deduplicating.
+    pub members: Vec<DuplicateMember>,
+}
+
+/// Bitmap resolved to colors, one entry per pixel in row-major order.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct NormalizedBitmap {
+    width: usize,
+    height: usize,
+    pixels: Vec<Option<Color>>,
+}
+
+impl NormalizedBitmap {
+    fn new(glyph: &Glyph, doc: &GtfDocument) -> Self {
+        let width = glyph
+            .bitmap
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0);
+        let background = background_color(doc);
+        let pixels = glyph
+            .bitmap
+            .iter()
+            .flat_map(|row| {
+                let mut colors: Vec<Option<Color>> =
+                    row.chars().map(|ch| pixel_color(ch, glyph, doc)).collect();
+                colors.resize(width, background);
+                colors
+            })
+            .collect();
+        NormalizedBitmap {
+            width,
+            height: glyph.bitmap.len(),
+            pixels,
+        }
+    }
+
+    fn hash_hex(&self) -> String {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        format!("{:016x}", hasher.finish())
+    }
+
+    /// Differing pixels, `None` for different sizes or more than `limit`.
+    fn distance(&self, other: &NormalizedBitmap, limit: u32) -> Option<u32> {
+        if self.width != other.width || self.height != other.height {
+            return None;
+        }
+        let mut differing = 0;
+        for (a, b) in self.pixels.iter().zip(&other.pixels) {
+            if a != b {
+                differing += 1;
+                if differing > limit {
+                    return None;
+                }
+            }
+        }
+        Some(differing)
+    }
+}
+
+fn find(parent: &mut [usize], i: usize) -> usize {
+    let mut root = i;
+    while parent[root] != root {
+        root = parent[root];
+    }
+    parent[i] = root;
+    root
+}
+
+/// Clusters of duplicate glyphs, ordered by their first member.
+pub fn find_duplicates(doc: &GtfDocument, options: &SimilarityOptions) -> Vec<DuplicateCluster> {
+    let background = background_color(doc);
+    let bitmaps: Vec<(usize, NormalizedBitmap)> = doc
+        .glyphs
+        .iter()
+        .enumerate()
+        .map(|(i, glyph)| (i, NormalizedBitmap::new(glyph, doc)))
+        .filter(|(_, bitmap)| {
+            options.include_blank || bitmap.pixels.iter().any(|color| *color != background)
+        })
+        .collect();
+
+    // Exact duplicates first, then compare one representative per distinct bitmap
+    let mut distinct: Vec<(&NormalizedBitmap, Vec<usize>)> = Vec::new();
+    let mut by_hash: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (slot, (_, bitmap)) in bitmaps.iter().enumerate() {
+        let candidates = by_hash.entry(bitmap.hash_hex()).or_default();
+        match candidates.iter().find(|&&d| distinct[d].0 == bitmap) {
+            Some(&d) => distinct[d].1.push(slot),
+            None => {
+                candidates.push(distinct.len());
+                distinct.push((bitmap, vec![slot]));
+            }
+        }
+    }
+
+    let mut parent: Vec<usize> = (0..distinct.len()).collect();
+    if options.max_distance > 0 {
+        for a in 0..distinct.len() {
+            for b in a + 1..distinct.len() {
+                if distinct[a]
+                    .0
+                    .distance(distinct[b].0, options.max_distance)
+                    .is_some()
+                {
+                    let (ra, rb) = (find(&mut parent, a), find(&mut parent, b));
+                    parent[rb] = ra;
+                }
+            }
+        }
+    }
+
+    let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (d, (_, slots)) in distinct.iter().enumerate() {
+        let root = find(&mut parent, d);
+        groups
+            .entry(root)
+            .or_default()
+            .extend(slots.iter().copied());
+    }
+
+    let mut clusters: Vec<(usize, DuplicateCluster)> = groups
+        .into_values()
+        .filter(|slots| slots.len() > 1)
+        .map(|mut slots| {
+            // Slots follow document order, so the first member comes first in the font
+            slots.sort_unstable();
+            let first = &bitmaps[slots[0]].1;
+            let members: Vec<DuplicateMember> = slots
+                .iter()
+                .map(|&slot| {
+                    let (index, bitmap) = &bitmaps[slot];
+                    let glyph = &doc.glyphs[*index];
+                    DuplicateMember {
+                        name: glyph.name.clone(),
+                        unicode: glyph.unicode.clone(),
+                        char_repr: glyph.char_repr,
+                        hash: bitmap.hash_hex(),
+                        distance: first.distance(bitmap, u32::MAX).unwrap_or(u32::MAX),
+                    }
+                })
+                .collect();
+            let cluster = DuplicateCluster {
+                exact: members.iter().all(|m| m.distance == 0),
+                members,
+            };
+            (slots[0], cluster)
+        })
+        .collect();
+    clusters.sort_by_key(|(first, _)| *first);
+    clusters.into_iter().map(|(_, cluster)| cluster).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gtf::test_util::{document, glyph, palette};
+
+    fn names(cluster: &DuplicateCluster) -> Vec<&str> {
+        cluster.members.iter().map(|m| m.name.as_str()).collect()
+    }
+
+    #[test]
+    fn exact_duplicates_match_by_color_not_palette_char() {
+        let recolored = Glyph {
+            palette: Some(palette(&[('o', "#000000"), ('x', "#FFFFFF")])),
+            ..glyph("B", &["oxo", "xox"])
+        };
+        let doc = document(vec![
+            glyph("A", &[".#.", "#.#"]),
+            recolored,
+            glyph("C", &["###", "..."]),
+        ]);
+        let clusters = find_duplicates(&doc, &SimilarityOptions::default());
+
+        assert_eq!(clusters.len(), 1);
+        assert!(clusters[0].exact);
+        assert_eq!(names(&clusters[0]), vec!["A", "B"]);
+        assert_eq!(clusters[0].members[0].hash, clusters[0].members[1].hash);
+    }
+
+    #[test]
+    fn near_duplicates_are_linked_within_the_distance() {
+        let doc = document(vec![
+            glyph("A", &["##.", "#.."]),
+            glyph("B", &["###", "#.."]),
+            glyph("C", &["###", "##."]),
+            glyph("D", &["...", ".##"]),
+        ]);
+        assert!(find_duplicates(&doc, &SimilarityOptions::default()).is_empty());
+
+        let options = SimilarityOptions {
+            max_distance: 1,
+            ..Default::default()
+        };
+        let clusters = find_duplicates(&doc, &options);
+        assert_eq!(clusters.len(), 1);
+        assert!(!clusters[0].exact);
+        // C is two pixels from A but joins through B
+        assert_eq!(names(&clusters[0]), vec!["A", "B", "C"]);
+        let distances: Vec<u32> = clusters[0].members.iter().map(|m| m.distance).collect();
+        assert_eq!(distances, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn sizes_must_match_and_ragged_rows_are_padded() {
+        let doc = document(vec![
+            glyph("A", &["#..", "#"]),
+            glyph("B", &["#..", "#.."]),
+            glyph("C", &["#...", "#..."]),
+        ]);
+        let options = SimilarityOptions {
+            max_distance: 8,
+            ..Default::default()
+        };
+        let clusters = find_duplicates(&doc, &options);
+
+        assert_eq!(clusters.len(), 1);
+        assert!(clusters[0].exact);
+        assert_eq!(names(&clusters[0]), vec!["A", "B"]);
+    }
+
+    #[test]
+    fn blank_glyphs_are_skipped_unless_requested() {
+        let doc = document(vec![
+            glyph("A", &["#.", ".#"]),
+            glyph("space", &["..", ".."]),
+            glyph("nbsp", &["..", ".."]),
+        ]);
+        assert!(find_duplicates(&doc, &SimilarityOptions::default()).is_empty());
+
+        let options = SimilarityOptions {
+            include_blank: true,
+            ..Default::default()
+        };
+        let clusters = find_duplicates(&doc, &options);
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(names(&clusters[0]), vec!["space", "nbsp"]);
+    }
+}
@@ -0,0 +1,441 @@
+//! Synthetic style variants of a font: bold, outline, drop shadow and italic.
+//!
+//! Effects work on ink, the pixels whose color differs from the font
+//! background (`palette::background_color`), and are applied in order, so
+//! `[Bold, Outline]` outlines the emboldened letters. Every effect grows all
+//! glyphs by the same amount, which keeps baselines aligned; `DEFAULT_SIZE`
+//! grows with them. Trimmed glyphs are expanded to their cell first. New
+//! colors (outline, shadow) get a palette character not used anywhere in the
+//! font, added to the default palette and inherited by every glyph.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use super::bounds::untrim_glyph;
+use super::color::Color;
+use super::palette::{background_char, background_color, pixel_color};
+use super::resize::DEFAULT_FILL;
+use super::types::{GtfDocument, Palette, Size};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StyleEffect {
+    /// Horizontal dilation: every ink pixel is repeated `weight` pixels to the right.
+    Bold {
+        #[serde(default = "one")]
+        weight: u32,
+    },
+    /// Border of `thickness` pixels (including diagonals) around the ink.
+    Outline {
+        color: Color,
+        #[serde(default = "one")]
+        thickness: u32,
+    },
+    /// Copy of the ink offset by (`dx`, `dy`) behind the letter.
+    Shadow { color: Color, dx: i32, dy: i32 },
+    /// Row shearing: every `step` rows above the bottom shift one pixel right.
+    Italic {
+        #[serde(default = "two")]
+        step: u32,
+    },
+}
+
+fn one() -> u32 {
+    1
+}
+
+fn two() -> u32 {
+    2
+}
+
+impl StyleEffect {
+    fn name(&self) -> &'static str {
+        match self {
+            StyleEffect::Bold { .. } => "Bold",
+            StyleEffect::Outline { .. } => "Outline",
+            StyleEffect::Shadow { .. } => "Shadow",
+            StyleEffect::Italic { .. } => "Italic",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StyleOptions {
+    pub effects: Vec<StyleEffect>,
+    /// Name of the new font; defaults to the source name plus the effect names.
+    pub font_name: Option<String>,
+}
+
+/// Pixel grid of one glyph with its ink mask.
+struct Canvas {
+    pixels: Vec<Vec<char>>,
+    ink: Vec<Vec<bool>>,
+    fill: char,
+}
+
+impl Canvas {
+    fn width(&self) -> usize {
+        self.pixels.first().map_or(0, |row| row.len())
+    }
+
+    fn height(&self) -> usize {
+        self.pixels.len()
+    }
+
+    /// Empty canvas of the given size with this canvas copied at (`top`, `left`).
+    fn expanded(&self, width: usize, height: usize, top: usize, left: usize) -> Canvas {
+        let mut pixels = vec![vec![self.fill; width]; height];
+        let mut ink = vec![vec![false; width]; height];
+        for (row, line) in self.pixels.iter().enumerate() {
+            for (col, &ch) in line.iter().enumerate() {
+                pixels[row + top][col + left] = ch;
+                ink[row + top][col + left] = self.ink[row][col];
+            }
+        }
+        Canvas {
+            pixels,
+            ink,
+            fill: self.fill,
+        }
+    }
+
+    fn is_ink(&self, row: i64, col: i64) -> bool {
+        row >= 0
+            && col >= 0
+            && (row as usize) < self.height()
+            && (col as usize) < self.width()
+            && self.ink[row as usize][col as usize]
+    }
+}
+
+fn bold(canvas: &Canvas, weight: usize) -> Canvas {
+    let mut out = canvas.expanded(canvas.width() + weight, canvas.height(), 0, 0);
+    for row in 0..canvas.height() {
+        for col in 0..canvas.width() {
+            if !canvas.ink[row][col] {
+                continue;
+            }
+            for spread in 1..=weight {
+                if !out.ink[row][col + spread] {
+                    out.pixels[row][col + spread] = canvas.pixels[row][col];
+                    out.ink[row][col + spread] = true;
+                }
+            }
+        }
+    }
+    out
+}
+
+fn outline(canvas: &Canvas, thickness: usize, outline_char: char) -> Canvas {
+    let t = thickness as i64;
+    let mut out = canvas.expanded(
+        canvas.width() + 2 * thickness,
+        canvas.height() + 2 * thickness,
+        thickness,
+        thickness,
+    );
+    let source = canvas.expanded(out.width(), out.height(), thickness, thickness);
+    for row in 0..out.height() as i64 {
+        for col in 0..out.width() as i64 {
+            if source.is_ink(row, col) {
+                continue;
+            }
+            let near_ink = (-t..=t)
+                .flat_map(|dr| (-t..=t).map(move |dc| (dr, dc)))
+                .any(|(dr, dc)| source.is_ink(row + dr, col + dc));
+            if near_ink {
+                out.pixels[row as usize][col as usize] = outline_char;
+                out.ink[row as usize][col as usize] = true;
+            }
+        }
+    }
+    out
+}
+
+fn shadow(canvas: &Canvas, dx: i32, dy: i32, shadow_char: char) -> Canvas {
+    let (grow_w, grow_h) = (dx.unsigned_abs() as usize, dy.unsigned_abs() as usize);
+    // Content moves right / down when the shadow goes left / up
+    let left = if dx < 0 { grow_w } else { 0 };
+    let top = if dy < 0 { grow_h } else { 0 };
+    let mut out = canvas.expanded(canvas.width() + grow_w, canvas.height() + grow_h, top, left);
+    let source = canvas.expanded(out.width(), out.height(), top, left);
+    for row in 0..out.height() as i64 {
+        for col in 0..out.width() as i64 {
+            if !source.is_ink(row, col) && source.is_ink(row - dy as i64, col - dx as i64) {
+                out.pixels[row as usize][col as usize] = shadow_char;
+                out.ink[row as usize][col as usize] = true;
+            }
+        }
+    }
+    out
+}
+
+fn italic(canvas: &Canvas, step: usize, max_shift: usize) -> Canvas {
+    let mut out = canvas.expanded(canvas.width() + max_shift, canvas.height(), 0, 0);
+    let height = canvas.height();
+    for row in 0..height {
+        let shift = ((height - 1 - row) / step).min(max_shift);
+        out.pixels[row] = vec![canvas.fill; out.width()];
+        out.ink[row] = vec![false; out.width()];
+        for col in 0..canvas.width() {
+            out.pixels[row][col + shift] = canvas.pixels[row][col];
+            out.ink[row][col + shift] = canvas.ink[row][col];
+        }
+    }
+    out
+}
+
+/// Palette character for `color`: an existing entry with that color that no
+/// glyph overrides, otherwise a character not used in any palette or bitmap.
+/// The color is added to the default palette, glyphs inherit it from there.
+fn register_color(doc: &mut GtfDocument, color: Color, background: Color) -> Result<char, String> {
+    if color == background {
+        return Err(format!("Color {} is the font background", color));
+    }
+
+    let palettes: Vec<&Palette> = doc
+        .header
+        .default_palette
+        .iter()
+        .chain(doc.glyphs.iter().filter_map(|g| g.palette.as_ref()))
+        .collect();
+    let mut candidates: Vec<char> = palettes
+        .iter()
+        .flat_map(|p| p.entries.iter())
+        .filter(|(_, c)| **c == color)
+        .map(|(ch, _)| *ch)
+        .collect();
+    candidates.sort_unstable();
+    let reusable = candidates.into_iter().find(|ch| {
+        palettes
+            .iter()
+            .all(|p| p.entries.get(ch).is_none_or(|c| *c == color))
+    });
+
+    let ch = match reusable {
+        Some(ch) => ch,
+        None => {
+            let mut taken: HashSet<char> = palettes
+                .iter()
+                .flat_map(|p| p.entries.keys().copied())
+                .collect();
+            for glyph in &doc.glyphs {
+                taken.extend(glyph.bitmap.iter().flat_map(|row| row.chars()));
+            }
+            ('!'..='~')
+                .chain('\u{00A1}'..='\u{00FF}')
+                .find(|c| !taken.contains(c) && !c.is_whitespace())
+                .ok_or("No free palette character left")?
+        }
+    };
+
+    doc.header
+        .default_palette
+        .get_or_insert_with(Default::default)
+        .entries
+        .entry(ch)
+        .or_insert(color);
+    Ok(ch)
+}
+
+/// Derives a new font from `doc` with `options.effects` applied in order.
+pub fn generate_style(doc: &GtfDocument, options: &StyleOptions) -> Result<GtfDocument, String> {
+    if options.effects.is_empty() {
+        return Err("No style effect selected".to_string());
+    }
+    let background = background_color(doc).ok_or("The font has no colored pixels")?;
+    let mut result = doc.clone();
+
+    for effect in &options.effects {
+        // New colors first, so ink detection below sees them
+        let effect_char = match effect {
+            StyleEffect::Outline { color, .. } | StyleEffect::Shadow { color, .. } => {
+                Some(register_color(&mut result, *color, background)?)
+            }
+            _ => None,
+        };
+        let font_height = result
+            .header
+            .default_size
+            .as_ref()
+            .map(|s| s.height as usize)
+            .or_else(|| {
+                result
+                    .glyphs
+                    .iter()
+                    .map(|g| g.cell_size().height as usize)
+                    .max()
+            })
+            .unwrap_or(0);
+        let italic_shift = match effect {
+            StyleEffect::Italic { step: 0 } => {
+                return Err("Italic step must be at least 1".to_string())
+            }
+            StyleEffect::Italic { step } => font_height.saturating_sub(1) / *step as usize,
+            _ => 0,
+        };
+
+        let mut glyphs = Vec::with_capacity(result.glyphs.len());
+        for glyph in &result.glyphs {
+            let fill = background_char(glyph, &result, background).unwrap_or(DEFAULT_FILL);
+            let untrimmed = untrim_glyph(glyph, fill);
+            let glyph = untrimmed.as_ref();
+            let pixels: Vec<Vec<char>> = glyph.bitmap.iter().map(|r| r.chars().collect()).collect();
+            let width = pixels.iter().map(|r| r.len()).max().unwrap_or(0);
+            let pixels: Vec<Vec<char>> = pixels
+                .into_iter()
+                .map(|mut r| {
+                    r.resize(width, fill);
+                    r
+                })
+                .collect();
+            let ink = pixels
+                .iter()
+                .map(|r| {
+                    r.iter()
+                        .map(|&ch| pixel_color(ch, glyph, &result).is_some_and(|c| c != background))
+                        .collect()
+                })
+                .collect();
+            let canvas = Canvas { pixels, ink, fill };
+
+            let styled = match effect {
+                StyleEffect::Bold { weight } => bold(&canvas, *weight as usize),
+                StyleEffect::Outline { thickness, .. } => {
+                    outline(&canvas, *thickness as usize, effect_char.unwrap_or(fill))
+                }
+                StyleEffect::Shadow { dx, dy, .. } => {
+                    shadow(&canvas, *dx, *dy, effect_char.unwrap_or(fill))
+                }
+                StyleEffect::Italic { step } => italic(&canvas, *step as usize, italic_shift),
+            };
+
+            let mut glyph = glyph.clone();
+            if glyph.size.is_some() {
+                glyph.size = Some(Size {
+                    width: styled.width() as u32,
+                    height: styled.height() as u32,
+                });
+            }
+            glyph.bitmap = styled
+                .pixels
+                .into_iter()
+                .map(|row| row.into_iter().collect())
+                .collect();
+            glyphs.push(glyph);
+        }
+        result.glyphs = glyphs;
+
+        if let Some(size) = &mut result.header.default_size {
+            let (grow_w, grow_h) = match effect {
+                StyleEffect::Bold { weight } => (*weight, 0),
+                StyleEffect::Outline { thickness, .. } => (2 * thickness, 2 * thickness),
+                StyleEffect::Shadow { dx, dy, .. } => (dx.unsigned_abs(), dy.unsigned_abs()),
+                StyleEffect::Italic { .. } => (italic_shift as u32, 0),
+            };
+            size.width += grow_w;
+            size.height += grow_h;
+        }
+    }
+
+    let suffix: Vec<&str> = options.effects.iter().map(|e| e.name()).collect();
+    result.header.font_name = Some(match &options.font_name {
+        Some(name) => name.clone(),
+        None => format!(
+            "{} {}",
+            doc.header.font_name.as_deref().unwrap_or("Font"),
+            suffix.join(" ")
+        ),
+    });
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gtf::test_util::{color, document, glyph};
+
+    fn styled(doc: &GtfDocument, effect: StyleEffect) -> GtfDocument {
+        let options = StyleOptions {
+            effects: vec![effect],
+            font_name: None,
+        };
+        generate_style(doc, &options).unwrap()
+    }
+
+    #[test]
+    fn bold_pads_ragged_rows_first() {
+        let doc = document(vec![glyph("A", &["#", ".#."])]);
+        let bold = styled(&doc, StyleEffect::Bold { weight: 1 });
+
+        assert_eq!(bold.glyphs[0].bitmap, vec!["##..", ".##."]);
+        assert_eq!(
+            bold.glyphs[0].size,
+            Some(Size {
+                width: 4,
+                height: 2
+            })
+        );
+        assert_eq!(
+            bold.header.default_size,
+            Some(Size {
+                width: 4,
+                height: 2
+            })
+        );
+        assert_eq!(bold.header.font_name.as_deref(), Some("Test Bold"));
+    }
+
+    #[test]
+    fn outline_gets_a_new_palette_character() {
+        let doc = document(vec![glyph("A", &["...", ".#.", "..."])]);
+        let red = color("#FF0000");
+        let outlined = styled(
+            &doc,
+            StyleEffect::Outline {
+                color: red,
+                thickness: 1,
+            },
+        );
+
+        let palette = outlined.header.default_palette.as_ref().unwrap();
+        let (&o, _) = palette.entries.iter().find(|(_, c)| **c == red).unwrap();
+        assert!(!['.', '#'].contains(&o));
+        let expected: Vec<String> = [".....", ".ooo.", ".o#o.", ".ooo.", "....."]
+            .iter()
+            .map(|row| row.replace('o', &o.to_string()))
+            .collect();
+        assert_eq!(outlined.glyphs[0].bitmap, expected);
+    }
+
+    #[test]
+    fn italic_shears_towards_the_top() {
+        let doc = document(vec![glyph("I", &["#..", "#..", "#.."])]);
+        let italic = styled(&doc, StyleEffect::Italic { step: 1 });
+        assert_eq!(italic.glyphs[0].bitmap, vec!["..#..", ".#...", "#...."]);
+    }
+
+    #[test]
+    fn invalid_options_are_rejected() {
+        let doc = document(vec![glyph("A", &["..", ".#"])]);
+        let run = |effects| {
+            generate_style(
+                &doc,
+                &StyleOptions {
+                    effects,
+                    font_name: None,
+                },
+            )
+        };
+        assert!(run(vec![]).is_err());
+        assert!(run(vec![StyleEffect::Italic { step: 0 }]).is_err());
+        assert!(run(vec![StyleEffect::Shadow {
+            color: color("#000000"),
+            dx: 1,
+            dy: 1,
+        }])
+        .is_err());
+    }
+}
@@ -0,0 +1,53 @@
+//! Builders shared by the unit tests of the `gtf` modules.
+
+use std::str::FromStr;
+
+use super::color::Color;
+use super::types::{Glyph, GtfDocument, GtfHeader, Palette, Size};
+
+pub fn color(hex: &str) -> Color {
+    Color::from_str(hex).unwrap()
+}
+
+pub fn palette(entries: &[(char, &str)]) -> Palette {
+    Palette {
+        entries: entries.iter().map(|(ch, hex)| (*ch, color(hex))).collect(),
+    }
+}
+
+/// Glyph with `SIZE` taken from the widest row and an empty palette (everything inherited).
+pub fn glyph(name: &str, rows: &[&str]) -> Glyph {
+    Glyph {
+        name: name.to_string(),
+        size: Some(Size {
+            width: rows.iter().map(|r| r.chars().count()).max().unwrap_or(0) as u32,
+            height: rows.len() as u32,
+        }),
+        palette: Some(Palette::default()),
+        bitmap: rows.iter().map(|r| r.to_string()).collect(),
+        ..Default::default()
+    }
+}
+
+/// Glyph encoding `ch` (`UNICODE` and `CHAR`).
+pub fn char_glyph(name: &str, ch: char, rows: &[&str]) -> Glyph {
+    Glyph {
+        unicode: Some(format!("U+{:04X}", ch as u32)),
+        char_repr: Some(ch),
+        ..glyph(name, rows)
+    }
+}
+
+/// Document with a black `.` / white `#` default palette.
+pub fn document(glyphs: Vec<Glyph>) -> GtfDocument {
+    let default_size = glyphs.first().and_then(|g| g.size.clone());
+    GtfDocument {
+        header: GtfHeader {
+            font_name: Some("Test".to_string()),
+            default_size,
+            default_palette: Some(palette(&[('.', "#000000"), ('#', "#FFFFFF")])),
+            ..Default::default()
+        },
+        glyphs,
+    }
+}
@@ -0,0 +1,363 @@
+//! Geometric bitmap transforms: flips, rotations, shifting and transposition.
+//!
+//! Transforms move pixels without looking at their colors, so the glyph
+//! palette stays as it is. Rotations by 90/270 degrees and transposition swap
+//! width and height; `transform_glyph` updates `SIZE` to match.
+//!
+//! Trimmed glyphs (`OFFSET` / `CELL`) are transformed as whole cells: flips,
+//! rotations and transposition also move the bitmap within its cell, shifts
+//! first expand the bitmap back to the cell so no ink is cut off.
+
+use std::borrow::Cow;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::bounds::untrim_glyph;
+use super::types::{Glyph, Offset, Size};
+
+/// What happens to pixels shifted past an edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShiftEdge {
+    /// Pixels leaving one edge re-enter at the opposite edge.
+    Wrap,
+    /// Pixels leaving the bitmap are dropped; vacated pixels get this character.
+    Fill(char),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Transform {
+    FlipHorizontal,
+    FlipVertical,
+    /// Clockwise rotation by 90, 180 or 270 degrees.
+    Rotate {
+        degrees: u32,
+    },
+    /// Moves pixels right by `dx` and down by `dy` (negative values left / up).
+    Shift {
+        dx: i32,
+        dy: i32,
+        edge: ShiftEdge,
+    },
+    /// Mirrors along the main diagonal (rows become columns).
+    Transpose,
+}
+
+impl fmt::Display for Transform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Transform::FlipHorizontal => write!(f, "Flip horizontally"),
+            Transform::FlipVertical => write!(f, "Flip vertically"),
+            Transform::Rotate { degrees } => write!(f, "Rotate {}°", degrees),
+            Transform::Shift { dx, dy, .. } => write!(f, "Shift by ({}, {})", dx, dy),
+            Transform::Transpose => write!(f, "Transpose"),
+        }
+    }
+}
+
+/// Bitmap as a grid of pixels; rows must all have the same width.
+fn to_grid(bitmap: &[String]) -> Result<Vec<Vec<char>>, String> {
+    let grid: Vec<Vec<char>> = bitmap.iter().map(|row| row.chars().collect()).collect();
+    if let Some(first) = grid.first() {
+        if let Some(row) = grid.iter().position(|r| r.len() != first.len()) {
+            return Err(format!(
+                "Bitmap row {} has {} pixels, expected {}",
+                row + 1,
+                grid[row].len(),
+                first.len()
+            ));
+        }
+    }
+    Ok(grid)
+}
+
+fn from_grid(grid: Vec<Vec<char>>) -> Vec<String> {
+    grid.into_iter()
+        .map(|row| row.into_iter().collect())
+        .collect()
+}
+
+fn transpose(grid: &[Vec<char>]) -> Vec<Vec<char>> {
+    let width = grid.first().map_or(0, |row| row.len());
+    (0..width)
+        .map(|col| grid.iter().map(|row| row[col]).collect())
+        .collect()
+}
+
+/// Position a pixel moves from when shifted by `delta` in a line of `len` pixels.
+fn shifted_source(index: usize, delta: i32, len: usize, edge: ShiftEdge) -> Option<usize> {
+    let source = index as i64 - delta as i64;
+    match edge {
+        ShiftEdge::Wrap => Some(source.rem_euclid(len as i64) as usize),
+        ShiftEdge::Fill(_) => (0..len as i64).contains(&source).then_some(source as usize),
+    }
+}
+
+/// Applies `transform` to a rectangular bitmap.
+pub fn transform_bitmap(bitmap: &[String], transform: &Transform) -> Result<Vec<String>, String> {
+    let grid = to_grid(bitmap)?;
+    let result = match *transform {
+        Transform::FlipHorizontal => grid
+            .into_iter()
+            .map(|row| row.into_iter().rev().collect())
+            .collect(),
+        Transform::FlipVertical => grid.into_iter().rev().collect(),
+        Transform::Rotate { degrees: 90 } => transpose(&grid)
+            .into_iter()
+            .map(|row| row.into_iter().rev().collect())
+            .collect(),
+        Transform::Rotate { degrees: 180 } => grid
+            .into_iter()
+            .rev()
+            .map(|row| row.into_iter().rev().collect())
+            .collect(),
+        Transform::Rotate { degrees: 270 } => transpose(&grid).into_iter().rev().collect(),
+        Transform::Rotate { degrees } => {
+            return Err(format!(
+                "Rotation must be 90, 180 or 270 degrees, got {}",
+                degrees
+            ))
+        }
+        Transform::Shift { dx, dy, edge } => {
+            let height = grid.len();
+            let width = grid.first().map_or(0, |row| row.len());
+            (0..height)
+                .map(|row| {
+                    (0..width)
+                        .map(|col| {
+                            let source_row = shifted_source(row, dy, height, edge);
+                            let source_col = shifted_source(col, dx, width, edge);
+                            match (source_row, source_col, edge) {
+                                (Some(r), Some(c), _) => grid[r][c],
+                                (_, _, ShiftEdge::Fill(fill)) => fill,
+                                (_, _, ShiftEdge::Wrap) => {
+                                    unreachable!("wrapping always has a source")
+                                }
+                            }
+                        })
+                        .collect()
+                })
+                .collect()
+        }
+        Transform::Transpose => transpose(&grid),
+    };
+    Ok(from_grid(result))
+}
+
+/// Where a `width` x `height` bitmap at `offset` in a `cell` ends up after
+/// `transform`, as the new offset and cell.
+fn transform_placement(
+    offset: Offset,
+    cell: &Size,
+    width: u32,
+    height: u32,
+    transform: &Transform,
+) -> (Offset, Size) {
+    let (cell_w, cell_h) = (cell.width as i32, cell.height as i32);
+    let right = cell_w - offset.x - width as i32;
+    let bottom = cell_h - offset.y - height as i32;
+    let swapped = Size {
+        width: cell.height,
+        height: cell.width,
+    };
+    match transform {
+        Transform::FlipHorizontal => (Offset { x: right, ..offset }, cell.clone()),
+        Transform::FlipVertical => (
+            Offset {
+                y: bottom,
+                ..offset
+            },
+            cell.clone(),
+        ),
+        Transform::Rotate { degrees: 90 } => (
+            Offset {
+                x: bottom,
+                y: offset.x,
+            },
+            swapped,
+        ),
+        Transform::Rotate { degrees: 180 } => (
+            Offset {
+                x: right,
+                y: bottom,
+            },
+            cell.clone(),
+        ),
+        Transform::Rotate { degrees: 270 } => (
+            Offset {
+                x: offset.y,
+                y: right,
+            },
+            swapped,
+        ),
+        Transform::Transpose => (
+            Offset {
+                x: offset.y,
+                y: offset.x,
+            },
+            swapped,
+        ),
+        Transform::Rotate { .. } | Transform::Shift { .. } => (offset, cell.clone()),
+    }
+}
+
+/// Transformed copy of a glyph with `SIZE` matching the new bitmap. `fill`
+/// is the glyph's background character, used when a shift expands a trimmed
+/// glyph to its cell.
+pub fn transform_glyph(glyph: &Glyph, transform: &Transform, fill: char) -> Result<Glyph, String> {
+    let glyph = match transform {
+        Transform::Shift { .. } => untrim_glyph(glyph, fill),
+        _ => Cow::Borrowed(glyph),
+    };
+    let bitmap = transform_bitmap(&glyph.bitmap, transform)
+        .map_err(|e| format!("Glyph '{}': {}", glyph.name, e))?;
+    let placement = (glyph.offset.is_some() || glyph.cell.is_some()).then(|| {
+        transform_placement(
+            glyph.offset.unwrap_or_default(),
+            &glyph.cell_size(),
+            glyph.bitmap.first().map_or(0, |row| row.chars().count()) as u32,
+            glyph.bitmap.len() as u32,
+            transform,
+        )
+    });
+    let mut result = glyph.into_owned();
+    if let Some((offset, cell)) = placement {
+        result.offset = Some(offset);
+        result.cell = Some(cell);
+    }
+    if result.size.is_some() {
+        result.size = Some(Size {
+            width: bitmap.first().map_or(0, |row| row.chars().count()) as u32,
+            height: bitmap.len() as u32,
+        });
+    }
+    result.bitmap = bitmap;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gtf::bounds::{cell_bitmap, trim_glyph};
+    use crate::gtf::test_util::{color, document, glyph};
+
+    const L: &[&str] = &["#..", "#..", "##."];
+
+    fn bitmap(rows: &[&str]) -> Vec<String> {
+        rows.iter().map(|r| r.to_string()).collect()
+    }
+
+    fn apply(bitmap: &[String], transforms: &[Transform]) -> Vec<String> {
+        transforms.iter().fold(bitmap.to_vec(), |current, t| {
+            transform_bitmap(&current, t).unwrap()
+        })
+    }
+
+    #[test]
+    fn rotations_and_flips() {
+        let l = bitmap(L);
+        assert_eq!(
+            apply(&l, &[Transform::Rotate { degrees: 90 }]),
+            bitmap(&["###", "#..", "..."])
+        );
+        assert_eq!(
+            apply(&l, &[Transform::FlipHorizontal]),
+            bitmap(&["..#", "..#", ".##"])
+        );
+        assert_eq!(
+            apply(&l, &[Transform::FlipVertical]),
+            bitmap(&["##.", "#..", "#.."])
+        );
+        assert_eq!(
+            apply(&l, &[Transform::Transpose]),
+            bitmap(&["###", "..#", "..."])
+        );
+    }
+
+    #[test]
+    fn identities() {
+        let l = bitmap(L);
+        let rotate = |degrees| Transform::Rotate { degrees };
+        assert_eq!(apply(&l, &[rotate(90); 4]), l);
+        assert_eq!(apply(&l, &[rotate(90), rotate(270)]), l);
+        assert_eq!(apply(&l, &[rotate(180), rotate(180)]), l);
+        assert_eq!(apply(&l, &[Transform::FlipHorizontal; 2]), l);
+        assert_eq!(apply(&l, &[Transform::FlipVertical; 2]), l);
+        assert_eq!(apply(&l, &[Transform::Transpose; 2]), l);
+        assert_eq!(
+            apply(&l, &[Transform::FlipHorizontal, Transform::FlipVertical]),
+            apply(&l, &[rotate(180)])
+        );
+        assert_eq!(
+            apply(&l, &[Transform::Transpose, Transform::FlipHorizontal]),
+            apply(&l, &[rotate(90)])
+        );
+    }
+
+    #[test]
+    fn shifts_wrap_or_fill() {
+        let l = bitmap(L);
+        let shift = |dx, dy, edge| Transform::Shift { dx, dy, edge };
+        assert_eq!(
+            apply(&l, &[shift(1, -1, ShiftEdge::Fill('.'))]),
+            bitmap(&[".#.", ".##", "..."])
+        );
+        assert_eq!(
+            apply(&l, &[shift(2, 1, ShiftEdge::Wrap)]),
+            bitmap(&["#.#", "..#", "..#"])
+        );
+        assert_eq!(
+            apply(
+                &l,
+                &[shift(2, 1, ShiftEdge::Wrap), shift(-2, -1, ShiftEdge::Wrap)]
+            ),
+            l
+        );
+    }
+
+    #[test]
+    fn rotated_glyph_swaps_its_size() {
+        let tall = glyph("I", &["#", "#", "#"]);
+        let rotated = transform_glyph(&tall, &Transform::Rotate { degrees: 270 }, '.').unwrap();
+        assert_eq!(rotated.bitmap, bitmap(&["###"]));
+        assert_eq!(
+            rotated.size,
+            Some(Size {
+                width: 3,
+                height: 1
+            })
+        );
+    }
+
+    #[test]
+    fn trimmed_glyphs_transform_like_their_cell() {
+        let full = glyph("F", &["....", ".#..", ".##.", "....", "...."]);
+        let trimmed = trim_glyph(&full, &document(vec![]), Some(color("#000000"))).unwrap();
+        let shift = |dx, dy, edge| Transform::Shift { dx, dy, edge };
+        for transform in [
+            Transform::FlipHorizontal,
+            Transform::FlipVertical,
+            Transform::Rotate { degrees: 90 },
+            Transform::Rotate { degrees: 180 },
+            Transform::Rotate { degrees: 270 },
+            Transform::Transpose,
+            shift(2, 1, ShiftEdge::Fill('.')),
+            shift(-1, 3, ShiftEdge::Wrap),
+        ] {
+            let expected = transform_glyph(&full, &transform, '.').unwrap();
+            let actual = transform_glyph(&trimmed, &transform, '.').unwrap();
+            assert_eq!(cell_bitmap(&actual, '.'), expected.bitmap, "{}", transform);
+            assert_eq!(actual.cell_size(), expected.cell_size(), "{}", transform);
+        }
+    }
+
+    #[test]
+    fn rejects_ragged_bitmaps_and_odd_angles() {
+        let ragged = glyph("R", &["##", "#"]);
+        let error = transform_glyph(&ragged, &Transform::FlipHorizontal, '.').unwrap_err();
+        assert_eq!(error, "Glyph 'R': Bitmap row 2 has 1 pixels, expected 2");
+        assert!(transform_bitmap(&bitmap(L), &Transform::Rotate { degrees: 45 }).is_err());
+    }
+}
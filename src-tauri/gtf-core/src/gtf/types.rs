@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use std::str::FromStr; // Pro parsování čísel
+
+use super::color::Color;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Size {
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Position of a glyph bitmap's top-left pixel within its cell (`OFFSET x,y`).
+/// Set when blank rows/columns are trimmed so the glyph can still be placed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, Default)]
+pub struct Offset {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, Default)]
+pub struct Palette {
+    // Map character -> color (e.g., '#' -> #FFFFFF), normalized on load
+    pub entries: HashMap<char, Color>,
+}
+
+/// Default palette characters with a fixed meaning (`BACKGROUND` / `FOREGROUND`
+/// header lines). Renderers leave background pixels transparent and recolor
+/// foreground pixels; without a role the colors are detected from pixel usage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, Default)]
+pub struct PaletteRoles {
+    pub background: Option<char>,
+    pub foreground: Option<char>,
+}
+
+impl PaletteRoles {
+    /// Whether `ch` is assigned a role.
+    pub fn contains(&self, ch: char) -> bool {
+        self.background == Some(ch) || self.foreground == Some(ch)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, Default)]
+pub struct Glyph {
+    pub name: String,            // Internal name (e.g., "Dot", "GlyphA")
+    pub unicode: Option<String>, // e.g., "U+2022"
+    pub char_repr: Option<char>, // e.g., Some('•') or None
+    pub size: Option<Size>,
+    #[serde(default)]
+    pub offset: Option<Offset>, // Cell position of the bitmap, e.g. after trimming
+    #[serde(default)]
+    pub cell: Option<Size>, // Cell size (advance and line height) when the bitmap was trimmed
+    // Palette is always present, even if empty, for color-always mode
+    pub palette: Option<Palette>, // Keep Option<> for parsing flexibility, but treat as always color
+    pub bitmap: Vec<String>,      // Vec of strings, each string is a row
+    #[serde(default)] // Ensure warnings field defaults if missing in JSON
+    pub validation_warnings: Option<Vec<String>>, // Warnings found during parsing
+}
+
+/// Lightweight view of a glyph without its bitmap and palette.
+/// Used for paging through large fonts without sending every bitmap to the UI.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct GlyphSummary {
+    pub name: String,
+    pub unicode: Option<String>,
+    pub char_repr: Option<char>,
+    pub size: Option<Size>,
+    pub warning_count: usize,
+}
+
+impl Glyph {
+    pub fn summary(&self) -> GlyphSummary {
+        GlyphSummary {
+            name: self.name.clone(),
+            unicode: self.unicode.clone(),
+            char_repr: self.char_repr,
+            size: self.size.clone(),
+            warning_count: self.validation_warnings.as_ref().map_or(0, |w| w.len()),
+        }
+    }
+
+    /// Size of the glyph cell: `CELL` if set, otherwise the cell ends at the
+    /// bottom-right corner of the bitmap placed at `OFFSET`.
+    pub fn cell_size(&self) -> Size {
+        if let Some(cell) = &self.cell {
+            return cell.clone();
+        }
+        let size = self.size.clone().unwrap_or_else(|| Size {
+            width: self
+                .bitmap
+                .iter()
+                .map(|row| row.chars().count())
+                .max()
+                .unwrap_or(0) as u32,
+            height: self.bitmap.len() as u32,
+        });
+        let offset = self.offset.unwrap_or_default();
+        Size {
+            width: offset.x.max(0) as u32 + size.width,
+            height: offset.y.max(0) as u32 + size.height,
+        }
+    }
+
+    /// Character encoded by `UNICODE` (`U+XXXX`), if present and valid.
+    pub fn codepoint(&self) -> Option<char> {
+        let hex = self.unicode.as_deref()?.trim().strip_prefix("U+")?;
+        u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+    }
+
+    /// Replaces a single pixel character and returns the previous one.
+    pub fn set_pixel(&mut self, row: usize, col: usize, new_char: char) -> Result<char, String> {
+        let row_str = self
+            .bitmap
+            .get_mut(row)
+            .ok_or("Pixel coordinates out of bounds")?;
+        // Strings are UTF-8, so the row has to be rebuilt char by char
+        let mut chars: Vec<char> = row_str.chars().collect();
+        let slot = chars
+            .get_mut(col)
+            .ok_or("Pixel coordinates out of bounds")?;
+        let previous = std::mem::replace(slot, new_char);
+        *row_str = chars.into_iter().collect();
+        Ok(previous)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, Default)]
+pub struct GtfHeader {
+    pub font_name: Option<String>,
+    pub version: Option<String>,
+    pub author: Option<String>,
+    pub description: Option<String>,
+    #[serde(default)] // Default if missing in JSON
+    pub default_size: Option<Size>, // Optional default size for new glyphs
+    #[serde(default)] // Default if missing in JSON
+    pub default_palette: Option<Palette>, // Optional default palette for the font
+    #[serde(default)]
+    pub roles: PaletteRoles, // Background / foreground characters of the default palette
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, Default)]
+pub struct GtfDocument {
+    pub header: GtfHeader,
+    pub glyphs: Vec<Glyph>,
+}
+
+// Pomocná struktura pro parsování SIZE
+impl FromStr for Size {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split('x').collect();
+        if parts.len() != 2 {
+            return Err(format!("Invalid SIZE format: '{}'. Expected 'WxH'.", s));
+        }
+        let width = parts[0]
+            .parse::<u32>()
+            .map_err(|e| format!("Invalid width: {}. {}", parts[0], e))?;
+        let height = parts[1]
+            .parse::<u32>()
+            .map_err(|e| format!("Invalid height: {}. {}", parts[1], e))?;
+        Ok(Size { width, height })
+    }
+}
+
+impl FromStr for Offset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (x, y) = s
+            .split_once(',')
+            .ok_or_else(|| format!("Invalid OFFSET format: '{}'. Expected 'X,Y'.", s))?;
+        let x = x
+            .trim()
+            .parse::<i32>()
+            .map_err(|e| format!("Invalid offset x: {}. {}", x, e))?;
+        let y = y
+            .trim()
+            .parse::<i32>()
+            .map_err(|e| format!("Invalid offset y: {}. {}", y, e))?;
+        Ok(Offset { x, y })
+    }
+}
@@ -8,13 +8,21 @@
 //! - **DAT Text** (.dat) - VISE legacy text format (placeholder)
 //! - **FNT Text** (.fnt) - Bitmap font text format (placeholder)
 //! - **BFNT Binary** (.bfnt) - Binary bitmap font format (placeholder)
+//!
+//! Formats other than GTF are behind the `dat`, `fnt` and `bfnt` crate features.
 
+#[cfg(feature = "bfnt")]
 mod bfnt_binary;
+#[cfg(feature = "dat")]
 mod dat_text;
+#[cfg(feature = "fnt")]
 mod fnt_text;
 
+#[cfg(feature = "bfnt")]
 pub use bfnt_binary::BfntBinaryImporter;
+#[cfg(feature = "dat")]
 pub use dat_text::DatTextImporter;
+#[cfg(feature = "fnt")]
 pub use fnt_text::FntTextImporter;
 
 use crate::gtf::types::GtfDocument;
@@ -82,10 +90,13 @@ pub fn get_all_importer_info() -> Vec<ImporterInfo> {
             name: "GTF Text".to_string(),
             extensions: vec!["gtf".to_string()],
             mode: ImportMode::Text,
-            description: "Native GTF v2 text format (fully implemented)".to_string(),
+            description: "Native GTF v3 text format (fully implemented)".to_string(),
         },
+        #[cfg(feature = "dat")]
         DatTextImporter.info(),
+        #[cfg(feature = "fnt")]
         FntTextImporter.info(),
+        #[cfg(feature = "bfnt")]
         BfntBinaryImporter.info(),
     ]
 }
@@ -99,8 +110,11 @@ pub fn import_file(path: &str, format: &str) -> Result<GtfDocument, String> {
                 .map_err(|e| format!("Failed to read file '{}': {}", path, e))?;
             crate::gtf::parse_gtf_content(&content)
         }
+        #[cfg(feature = "dat")]
         "dat" => DatTextImporter.import_from_file(path),
+        #[cfg(feature = "fnt")]
         "fnt" => FntTextImporter.import_from_file(path),
+        #[cfg(feature = "bfnt")]
         "bfnt" => BfntBinaryImporter.import_from_file(path),
         _ => Err(format!("Unknown import format: '{}'", format)),
     }
@@ -0,0 +1,24 @@
+//! GUI-independent core of the GTF editor.
+//!
+//! - `gtf`: Document model, parser, serializer, palette helpers, diff and merge.
+//! - `importers`: Conversion from external font formats into `gtf::GtfDocument`.
+//! - `exporters`: Conversion from `gtf::GtfDocument` into external font formats.
+//! - `rendering`: PNG rendering of departure boards and diffs (`image` feature).
+//!
+//! ## Features
+//! - `image` (default): Enables the `rendering` module.
+//! - `dat`, `fnt`, `bfnt`, `bmp` (default): Enable the corresponding import/export format.
+//!   Native GTF is always available.
+//! - `cli`: Builds the `gtf-cli` command-line tool.
+//!
+//! ```no_run
+//! let content = std::fs::read_to_string("font.gtf").unwrap();
+//! let document = gtf_core::gtf::parse_gtf_content(&content).unwrap();
+//! println!("{} glyphs", document.glyphs.len());
+//! ```
+
+pub mod exporters;
+pub mod gtf;
+pub mod importers;
+#[cfg(feature = "image")]
+pub mod rendering;
@@ -0,0 +1,399 @@
+use super::{blend_pixel, RenderRequest};
+use crate::gtf::color::Color;
+use crate::gtf::palette::{background_color, foreground_color, pixel_color};
+use crate::gtf::types::{Glyph, GtfDocument};
+use image::{Rgba, RgbaImage};
+use std::collections::HashMap;
+
+pub fn render_board(req: &RenderRequest, doc: &GtfDocument) -> Result<Vec<u8>, String> {
+    let scale = req.pixel_scale;
+    let mut img = RgbaImage::new(req.display_width * scale, req.display_height * scale);
+
+    // Fill background black
+    for pixel in img.pixels_mut() {
+        *pixel = Rgba([0, 0, 0, 255]);
+    }
+
+    // Grid (optional)
+    if req.show_grid && scale > 1 {
+        let grid_color = Rgba([80, 80, 80, 255]);
+        for x in (0..img.width()).step_by(scale as usize) {
+            for y in 0..img.height() {
+                img.put_pixel(x, y, grid_color);
+            }
+        }
+        for y in (0..img.height()).step_by(scale as usize) {
+            for x in 0..img.width() {
+                img.put_pixel(x, y, grid_color);
+            }
+        }
+    }
+
+    let font = BoardFont::new(doc);
+
+    let glyph_height = line_height(doc) as i32;
+
+    let row_spacing = glyph_height + 1;
+    let mut current_y = 1;
+
+    // --- Render Header ---
+    if req.show_header {
+        for h_line in &req.header_lines {
+            if h_line.text.is_empty() {
+                continue;
+            }
+            if current_y + glyph_height > req.display_height as i32 {
+                break;
+            }
+
+            render_text(
+                &mut img,
+                &h_line.text,
+                1,
+                current_y,
+                Some(&h_line.color),
+                &font,
+                scale,
+            );
+            current_y += row_spacing;
+        }
+
+        // Draw separator
+        let sep_y = (current_y * scale as i32) as u32;
+        if sep_y < img.height() {
+            let sep_color = Rgba([128, 128, 128, 150]);
+            let h = (scale as f32 * 0.5).max(1.0) as u32;
+            for y in sep_y..std::cmp::min(sep_y + h, img.height()) {
+                for x in 0..img.width() {
+                    blend_pixel(&mut img, x, y, sep_color);
+                }
+            }
+        }
+        current_y += 1;
+    }
+
+    // --- Render Rows ---
+    let footer_reserved = if req.show_footer { row_spacing + 2 } else { 0 };
+
+    for row in &req.rows {
+        if current_y + glyph_height > (req.display_height as i32 - footer_reserved) {
+            break;
+        }
+
+        for (ci, col) in req.columns.iter().enumerate() {
+            if ci >= row.cells.len() {
+                break;
+            }
+            let cell_text = &row.cells[ci];
+            if cell_text.is_empty() {
+                continue;
+            }
+
+            render_text_in_column(
+                &mut img,
+                cell_text,
+                col.x,
+                col.width,
+                current_y,
+                &col.align,
+                Some(&col.color),
+                &font,
+                scale,
+            );
+        }
+        current_y += row_spacing;
+    }
+
+    // --- Render Footer ---
+    if req.show_footer && !req.footer_text.is_empty() {
+        let footer_y = req.display_height as i32 - glyph_height - 1;
+
+        // Separator
+        let sep_y = ((footer_y - 1) * scale as i32) as u32;
+        if sep_y < img.height() {
+            let sep_color = Rgba([128, 128, 128, 150]);
+            for x in 0..img.width() {
+                blend_pixel(&mut img, x, sep_y, sep_color);
+            }
+        }
+
+        render_text(
+            &mut img,
+            &req.footer_text,
+            1,
+            footer_y,
+            None,
+            &font,
+            scale,
+        );
+    }
+
+    // Encode to PNG
+    let mut buffer = std::io::Cursor::new(Vec::new());
+    img.write_to(&mut buffer, image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode image: {}", e))?;
+
+    Ok(buffer.into_inner())
+}
+
+/// Text colors from the request; unparsable colors render white.
+fn hex_to_rgba(hex: &str) -> Rgba<u8> {
+    Rgba(hex.parse::<Color>().unwrap_or(Color::WHITE).to_rgba())
+}
+
+/// Glyph lookup and the font colors with a special meaning on the board:
+/// background pixels are not drawn, foreground pixels take the text color.
+struct BoardFont<'a> {
+    doc: &'a GtfDocument,
+    glyphs: HashMap<String, &'a Glyph>,
+    background: Option<Color>,
+    foreground: Option<Color>,
+}
+
+impl<'a> BoardFont<'a> {
+    fn new(doc: &'a GtfDocument) -> Self {
+        // Map character representations to glyph names for lookups
+        let mut glyphs: HashMap<String, &Glyph> = HashMap::new();
+        for glyph in &doc.glyphs {
+            if let Some(ref char_repr) = glyph.char_repr {
+                glyphs.insert(char_repr.to_string(), glyph);
+            }
+            // Also map name as fallback
+            glyphs.insert(glyph.name.clone(), glyph);
+        }
+        BoardFont {
+            doc,
+            glyphs,
+            background: background_color(doc),
+            foreground: foreground_color(doc),
+        }
+    }
+}
+
+/// Line height of the font: the cell height of the first glyph.
+fn line_height(doc: &GtfDocument) -> u32 {
+    doc.glyphs
+        .first()
+        .filter(|g| g.size.is_some())
+        .map(|g| g.cell_size().height)
+        .unwrap_or(8)
+}
+
+/// Advance width of `glyph`: its cell width, 4 for glyphs without a size.
+fn advance(glyph: &Glyph) -> i32 {
+    glyph.size.as_ref().map_or(4, |_| glyph.cell_size().width) as i32
+}
+
+fn render_glyph(
+    img: &mut RgbaImage,
+    glyph: &Glyph,
+    font: &BoardFont,
+    x: i32,
+    y: i32,
+    color_override: Option<&str>,
+    scale: u32,
+) {
+    let override_rgba = color_override.map(hex_to_rgba);
+    // Trimmed bitmaps are drawn at their position within the cell
+    let offset = glyph.offset.unwrap_or_default();
+    let (x, y) = (x + offset.x, y + offset.y);
+
+    for (row_idx, row_str) in glyph.bitmap.iter().enumerate() {
+        for (col_idx, ch) in row_str.chars().enumerate() {
+            if let Some(color) = pixel_color(ch, glyph, font.doc) {
+                if Some(color) == font.background {
+                    continue;
+                }
+
+                let mut pixel_color = Rgba(color.to_rgba());
+
+                // Only the foreground (ink) takes the text color, other colors stay
+                if let Some(ovr) = override_rgba {
+                    if Some(color) == font.foreground {
+                        pixel_color = ovr;
+                    }
+                }
+
+                // Draw scaled pixel
+                let start_x = (x + col_idx as i32) * scale as i32;
+                let start_y = (y + row_idx as i32) * scale as i32;
+
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        let px = start_x + dx as i32;
+                        let py = start_y + dy as i32;
+                        if px >= 0 && px < img.width() as i32 && py >= 0 && py < img.height() as i32
+                        {
+                            blend_pixel(img, px as u32, py as u32, pixel_color);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Size in display pixels needed to render `lines` one below another the way
+/// `render_board` renders rows (1 px margin, 1 px spacing between glyphs and rows).
+pub fn measure_lines(doc: &GtfDocument, lines: &[&str]) -> (u32, u32) {
+    let font = BoardFont::new(doc);
+    let glyph_height = line_height(doc);
+
+    let width = lines
+        .iter()
+        .map(|line| measure_text(line, &font).max(0) as u32)
+        .max()
+        .unwrap_or(0);
+    (width + 2, lines.len() as u32 * (glyph_height + 1) + 1)
+}
+
+fn measure_text(text: &str, font: &BoardFont) -> i32 {
+    let mut width = 0;
+    let spacing = 1;
+    for (i, ch) in text.chars().enumerate() {
+        let ch_str = ch.to_string();
+        let w = if let Some(g) = font.glyphs.get(&ch_str) {
+            advance(g)
+        } else {
+            4
+        };
+        width += w;
+        if i < text.chars().count() - 1 {
+            width += spacing;
+        }
+    }
+    width
+}
+
+fn render_text(
+    img: &mut RgbaImage,
+    text: &str,
+    start_x: i32,
+    start_y: i32,
+    color_override: Option<&str>,
+    font: &BoardFont,
+    scale: u32,
+) -> i32 {
+    let mut cursor_x = start_x;
+    let spacing = 1;
+    for ch in text.chars() {
+        let ch_str = ch.to_string();
+        if let Some(glyph) = font.glyphs.get(&ch_str) {
+            render_glyph(img, glyph, font, cursor_x, start_y, color_override, scale);
+            cursor_x += advance(glyph) + spacing;
+        } else {
+            cursor_x += 4 + spacing;
+        }
+    }
+    cursor_x - start_x
+}
+
+fn render_text_in_column(
+    img: &mut RgbaImage,
+    text: &str,
+    region_x: i32,
+    region_width: i32,
+    start_y: i32,
+    align: &str,
+    color_override: Option<&str>,
+    font: &BoardFont,
+    scale: u32,
+) {
+    let text_w = measure_text(text, font);
+    let mut cursor_x = match align {
+        "right" => region_x + region_width - text_w,
+        "center" => region_x + (region_width - text_w) / 2,
+        _ => region_x,
+    };
+
+    let spacing = 1;
+    for ch in text.chars() {
+        let ch_str = ch.to_string();
+        if let Some(glyph) = font.glyphs.get(&ch_str) {
+            render_glyph(img, glyph, font, cursor_x, start_y, color_override, scale);
+            cursor_x += advance(glyph) + spacing;
+        } else {
+            cursor_x += 4 + spacing;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gtf::bounds::{background_role_for_trim, trim_glyph};
+    use crate::gtf::test_util::{char_glyph, document, palette};
+    use crate::rendering::{ColumnDef, HeaderLine, RenderRow};
+
+    fn request(text: &str) -> RenderRequest {
+        RenderRequest {
+            display_width: 24,
+            display_height: 16,
+            pixel_scale: 1,
+            show_grid: false,
+            show_header: true,
+            header_lines: vec![HeaderLine {
+                text: text.to_string(),
+                color: "#FFAA00".to_string(),
+            }],
+            show_footer: true,
+            footer_text: text.to_string(),
+            columns: vec![ColumnDef {
+                label: "Line".to_string(),
+                x: 0,
+                width: 24,
+                align: "right".to_string(),
+                color: "#FFFFFF".to_string(),
+            }],
+            rows: vec![RenderRow {
+                cells: vec![text.to_string()],
+            }],
+            gtf_data: None,
+        }
+    }
+
+    #[test]
+    fn trimmed_glyphs_render_in_their_cell() {
+        let doc = document(vec![
+            char_glyph("I", 'I', &["....", ".#..", ".#..", ".#..", "...."]),
+            char_glyph("L", 'L', &["....", "#...", "#...", "###.", "...."]),
+        ]);
+        let mut trimmed = doc.clone();
+        trimmed.header.roles.background = background_role_for_trim(&doc);
+        let background = background_color(&doc);
+        for glyph in &mut trimmed.glyphs {
+            *glyph = trim_glyph(glyph, &doc, background).unwrap();
+        }
+        assert_eq!(trimmed.glyphs[0].bitmap, vec!["#", "#", "#"]);
+
+        assert_eq!(measure_lines(&trimmed, &["ILI"]), (16, 7));
+        assert_eq!(
+            measure_lines(&trimmed, &["ILI", "LI"]),
+            measure_lines(&doc, &["ILI", "LI"])
+        );
+        assert_eq!(
+            render_board(&request("ILI"), &trimmed).unwrap(),
+            render_board(&request("ILI"), &doc).unwrap()
+        );
+    }
+
+    #[test]
+    fn semi_transparent_colors_are_blended() {
+        let mut doc = document(vec![char_glyph(
+            "I",
+            'I',
+            &["....", ".#+.", ".##.", "...."],
+        )]);
+        doc.header.default_palette = Some(palette(&[
+            ('.', "#000000"),
+            ('#', "#FFFFFF"),
+            ('+', "#FF000080"),
+        ]));
+        let png = render_board(&request("I"), &doc).unwrap();
+        let img = image::load_from_memory(&png).unwrap().to_rgba8();
+
+        // Half-transparent red over the black board
+        assert!(img.pixels().any(|p| *p == Rgba([128, 0, 0, 255])));
+        assert!(!img.pixels().any(|p| *p == Rgba([255, 0, 0, 255])));
+    }
+}
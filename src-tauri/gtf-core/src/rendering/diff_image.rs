@@ -0,0 +1,215 @@
+use super::blend_pixel;
+use crate::gtf::diff::{DocumentDiff, GlyphChangeKind};
+use crate::gtf::palette::effective_color;
+use crate::gtf::types::{Glyph, GtfDocument};
+use image::{Rgba, RgbaImage};
+use std::collections::HashSet;
+
+const BACKGROUND: Rgba<u8> = Rgba([32, 32, 32, 255]);
+const PANEL_EMPTY: Rgba<u8> = Rgba([48, 48, 48, 255]);
+const UNKNOWN_COLOR: Rgba<u8> = Rgba([255, 0, 255, 255]);
+const CHANGED_COLOR: Rgba<u8> = Rgba([255, 48, 48, 255]);
+const ADDED_MARK: Rgba<u8> = Rgba([64, 192, 64, 255]);
+const REMOVED_MARK: Rgba<u8> = Rgba([192, 64, 64, 255]);
+const MODIFIED_MARK: Rgba<u8> = Rgba([208, 160, 48, 255]);
+
+/// Gap between panels and rows, in glyph pixels.
+const GAP: u32 = 2;
+/// Width of the colored status bar at the start of each row, in glyph pixels.
+const MARK_WIDTH: u32 = 1;
+
+/// Renders changed glyphs side by side: one row per glyph with the old bitmap,
+/// the new bitmap and a mask of the new bitmap with changed pixels in red.
+/// The status bar on the left is green for added, red for removed and amber
+/// for modified or renamed glyphs.
+pub fn render_diff(
+    old: &GtfDocument,
+    new: &GtfDocument,
+    diff: &DocumentDiff,
+    scale: u32,
+) -> Result<Vec<u8>, String> {
+    let scale = scale.max(1);
+    let rows: Vec<DiffRow> = diff
+        .glyphs
+        .iter()
+        .map(|g| DiffRow {
+            old: g.old_name.as_ref().and_then(|n| find(old, n)),
+            new: g.new_name.as_ref().and_then(|n| find(new, n)),
+            kind: g.kind,
+            changed: g.pixels.iter().map(|p| (p.row, p.col)).collect(),
+        })
+        .collect();
+    if rows.is_empty() {
+        return Err("Documents have no glyph differences to render".to_string());
+    }
+
+    let dims = |g: Option<&Glyph>| g.map(bitmap_size).unwrap_or((0, 0));
+    let panel_width = rows
+        .iter()
+        .map(|r| dims(r.old).0.max(dims(r.new).0))
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let row_heights: Vec<u32> = rows
+        .iter()
+        .map(|r| dims(r.old).1.max(dims(r.new).1).max(1))
+        .collect();
+
+    let width = MARK_WIDTH + GAP + 3 * panel_width + 3 * GAP;
+    let height = row_heights.iter().sum::<u32>() + GAP * (rows.len() as u32 + 1);
+    let mut img = RgbaImage::from_pixel(width * scale, height * scale, BACKGROUND);
+
+    let mut y = GAP;
+    for (row, row_height) in rows.iter().zip(&row_heights) {
+        let mark = match row.kind {
+            GlyphChangeKind::Added => ADDED_MARK,
+            GlyphChangeKind::Removed => REMOVED_MARK,
+            GlyphChangeKind::Modified | GlyphChangeKind::Renamed => MODIFIED_MARK,
+        };
+        fill(&mut img, 0, y, MARK_WIDTH, *row_height, mark, scale);
+
+        let old_x = MARK_WIDTH + GAP;
+        let new_x = old_x + panel_width + GAP;
+        let mask_x = new_x + panel_width + GAP;
+        for x in [old_x, new_x, mask_x] {
+            fill(&mut img, x, y, panel_width, *row_height, PANEL_EMPTY, scale);
+        }
+
+        if let Some(glyph) = row.old {
+            draw_glyph(&mut img, glyph, old, old_x, y, scale, |_, _, color| color);
+        }
+        if let Some(glyph) = row.new {
+            draw_glyph(&mut img, glyph, new, new_x, y, scale, |_, _, color| color);
+            draw_glyph(&mut img, glyph, new, mask_x, y, scale, |r, c, color| {
+                if row.changed.contains(&(r, c)) {
+                    CHANGED_COLOR
+                } else {
+                    dim(color)
+                }
+            });
+        }
+        // Pixels that exist only in the old bitmap (glyph got smaller or was removed)
+        for &(r, c) in &row.changed {
+            let outside_new = row.new.is_none_or(|g| {
+                g.bitmap
+                    .get(r)
+                    .is_none_or(|bitmap_row| bitmap_row.chars().nth(c).is_none())
+            });
+            if outside_new {
+                fill(
+                    &mut img,
+                    mask_x + c as u32,
+                    y + r as u32,
+                    1,
+                    1,
+                    CHANGED_COLOR,
+                    scale,
+                );
+            }
+        }
+
+        y += row_height + GAP;
+    }
+
+    let mut buffer = std::io::Cursor::new(Vec::new());
+    img.write_to(&mut buffer, image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode image: {}", e))?;
+    Ok(buffer.into_inner())
+}
+
+/// One rendered row: the paired glyphs and the positions of changed pixels.
+struct DiffRow<'a> {
+    old: Option<&'a Glyph>,
+    new: Option<&'a Glyph>,
+    kind: GlyphChangeKind,
+    changed: HashSet<(usize, usize)>,
+}
+
+fn find<'a>(doc: &'a GtfDocument, name: &str) -> Option<&'a Glyph> {
+    doc.glyphs.iter().find(|g| g.name == name)
+}
+
+fn bitmap_size(glyph: &Glyph) -> (u32, u32) {
+    let width = glyph
+        .bitmap
+        .iter()
+        .map(|row| row.chars().count())
+        .max()
+        .unwrap_or(0);
+    (width as u32, glyph.bitmap.len() as u32)
+}
+
+fn dim(color: Rgba<u8>) -> Rgba<u8> {
+    let [r, g, b, a] = color.0;
+    Rgba([r / 3, g / 3, b / 3, a])
+}
+
+/// Fills a rectangle given in glyph pixels, blending translucent colors.
+fn fill(img: &mut RgbaImage, x: u32, y: u32, w: u32, h: u32, color: Rgba<u8>, scale: u32) {
+    for py in y * scale..((y + h) * scale).min(img.height()) {
+        for px in x * scale..((x + w) * scale).min(img.width()) {
+            blend_pixel(img, px, py, color);
+        }
+    }
+}
+
+/// Draws a glyph with colors resolved from its palette and the document default palette.
+/// `paint` may replace the color of each pixel (row, column, resolved color).
+fn draw_glyph(
+    img: &mut RgbaImage,
+    glyph: &Glyph,
+    doc: &GtfDocument,
+    x: u32,
+    y: u32,
+    scale: u32,
+    paint: impl Fn(usize, usize, Rgba<u8>) -> Rgba<u8>,
+) {
+    for (row, row_str) in glyph.bitmap.iter().enumerate() {
+        for (col, ch) in row_str.chars().enumerate() {
+            let color = effective_color(
+                ch,
+                glyph.palette.as_ref(),
+                doc.header.default_palette.as_ref(),
+            )
+            .map(|color| Rgba(color.to_rgba()))
+            .unwrap_or(UNKNOWN_COLOR);
+            fill(
+                img,
+                x + col as u32,
+                y + row as u32,
+                1,
+                1,
+                paint(row, col, color),
+                scale,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gtf::diff::diff_documents;
+    use crate::gtf::test_util::{document, glyph};
+
+    #[test]
+    fn mask_marks_changed_pixels() {
+        let old = document(vec![glyph("A", &["#.", ".."]), glyph("B", &["##"])]);
+        let new = document(vec![glyph("A", &["##", ".."]), glyph("B", &["##"])]);
+        let diff = diff_documents(&old, &new);
+        let png = render_diff(&old, &new, &diff, 1).unwrap();
+        let img = image::load_from_memory(&png).unwrap().to_rgba8();
+
+        // One row (B is unchanged): status bar, old, new and mask panels
+        assert_eq!(img.dimensions(), (15, 6));
+        assert_eq!(*img.get_pixel(0, 2), MODIFIED_MARK);
+        let (mask_x, y) = (11, 2);
+        assert_eq!(*img.get_pixel(mask_x + 1, y), CHANGED_COLOR);
+        assert_eq!(*img.get_pixel(mask_x, y), dim(Rgba([255, 255, 255, 255])));
+        assert_eq!(*img.get_pixel(mask_x, y + 1), dim(Rgba([0, 0, 0, 255])));
+        // The new panel shows the new bitmap as is
+        assert_eq!(*img.get_pixel(mask_x - 3, y), Rgba([255, 255, 255, 255]));
+
+        assert!(render_diff(&old, &old, &diff_documents(&old, &old), 1).is_err());
+    }
+}
@@ -1,10 +1,26 @@
+//! Rendering of GTF documents to PNG images (requires the `image` feature).
+//!
+//! - `departure_board`: Text layout of a departure board (header, columns, rows, footer).
+//! - `diff_image`: Side-by-side visualization of a `gtf::diff::DocumentDiff`.
+//!
+//! Pixels are alpha-blended over what is already drawn, so partially
+//! transparent palette colors render as antialiasing.
+
+use crate::gtf::color::Color;
 use crate::gtf::types::GtfDocument;
-use crate::state::AppState;
-use base64::Engine;
+use image::{Rgba, RgbaImage};
 use serde::Deserialize;
 
 pub mod departure_board;
+pub mod diff_image;
+
+/// Draws `color` over the pixel at `x`, `y` (source-over).
+fn blend_pixel(img: &mut RgbaImage, x: u32, y: u32, color: Rgba<u8>) {
+    let below = Color::from_rgba(img.get_pixel(x, y).0);
+    img.put_pixel(x, y, Rgba(Color::from_rgba(color.0).over(below).to_rgba()));
+}
 
+/// Board layout and content, all dimensions in display pixels.
 #[derive(Debug, Deserialize)]
 pub struct RenderRequest {
     pub display_width: u32,
@@ -20,12 +36,14 @@ pub struct RenderRequest {
     pub gtf_data: Option<GtfDocument>, // Nyní volitelné, pokud chybí, vezmeme z globálního stavu
 }
 
+/// One line of the board header.
 #[derive(Debug, Deserialize)]
 pub struct HeaderLine {
     pub text: String,
     pub color: String,
 }
 
+/// Column position, alignment and text color.
 #[derive(Debug, Deserialize)]
 pub struct ColumnDef {
     pub label: String,
@@ -35,26 +53,9 @@ pub struct ColumnDef {
     pub color: String,
 }
 
+/// One board row, one cell per column.
 #[derive(Debug, Deserialize)]
 pub struct RenderRow {
     pub cells: Vec<String>,
 }
 
-#[tauri::command]
-pub fn render_departure_board(
-    request: RenderRequest,
-    state: tauri::State<'_, AppState>,
-) -> Result<String, String> {
-    // 1. Získáme data - buď z požadavku, nebo ze stavu
-    let doc_lock = state.document.lock().unwrap();
-    let doc = match &request.gtf_data {
-        Some(d) => d,
-        None => doc_lock
-            .as_ref()
-            .ok_or("No document loaded in backend for rendering")?,
-    };
-
-    // 2. Vyrenderujeme s použitím správných dat
-    let png_bytes = departure_board::render_board(&request, doc)?;
-    Ok(base64::engine::general_purpose::STANDARD.encode(png_bytes))
-}
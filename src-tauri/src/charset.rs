@@ -0,0 +1,167 @@
+//! Tauri commands checking which characters of a character set an open
+//! document covers (`gtf_core::gtf::charset`).
+//!
+//! User-defined sets live in `AppState` next to the built-in ones; the
+//! frontend persists them and pushes them back on startup.
+
+use std::collections::HashSet;
+
+use crate::gtf::charset::{self, CharacterSet, SetCoverage};
+use crate::history::DocumentEdit;
+use crate::state::{AppState, DocumentId, OpenDocument};
+
+/// Built-in sets followed by the user-defined ones.
+#[tauri::command]
+pub fn get_character_sets(state: tauri::State<'_, AppState>) -> Vec<CharacterSet> {
+    let custom = state.custom_character_sets.lock().unwrap();
+    let mut sets = charset::builtin_sets();
+    sets.extend(custom.iter().cloned());
+    sets
+}
+
+#[tauri::command]
+pub fn set_custom_character_sets(
+    sets: Vec<CharacterSet>,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    let builtin: HashSet<String> = charset::builtin_sets().into_iter().map(|s| s.id).collect();
+    let mut seen = HashSet::new();
+    for set in &sets {
+        if set.id.is_empty() {
+            return Err(format!("Character set '{}' has no id", set.name));
+        }
+        if builtin.contains(&set.id) {
+            return Err(format!("Character set id '{}' is reserved", set.id));
+        }
+        if !seen.insert(set.id.as_str()) {
+            return Err(format!("Duplicate character set id '{}'", set.id));
+        }
+    }
+    *state.custom_character_sets.lock().unwrap() = sets;
+    Ok(())
+}
+
+/// Coverage of the given sets (all sets without `set_ids`) by the document.
+#[tauri::command]
+pub fn analyze_character_coverage(
+    document_id: DocumentId,
+    set_ids: Option<Vec<String>>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<SetCoverage>, String> {
+    let sets = {
+        let custom = state.custom_character_sets.lock().unwrap();
+        match set_ids {
+            Some(ids) => ids
+                .iter()
+                .map(|id| {
+                    charset::find_set(id, &custom)
+                        .ok_or_else(|| format!("Unknown character set '{}'", id))
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            None => {
+                let mut sets = charset::builtin_sets();
+                sets.extend(custom.iter().cloned());
+                sets
+            }
+        }
+    };
+    let workspace = state.workspace.lock().unwrap();
+    let open = workspace.get(document_id)?;
+    Ok(sets
+        .iter()
+        .map(|set| charset::coverage(&open.document, set))
+        .collect())
+}
+
+/// Creates blank glyphs for all missing characters of a set as one undo step
+/// and returns the names of the new glyphs.
+#[tauri::command]
+pub fn add_missing_glyphs(
+    document_id: DocumentId,
+    set_id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    let set = {
+        let custom = state.custom_character_sets.lock().unwrap();
+        charset::find_set(&set_id, &custom)
+            .ok_or_else(|| format!("Unknown character set '{}'", set_id))?
+    };
+    let mut workspace = state.workspace.lock().unwrap();
+    add_glyphs_for_set(workspace.get_mut(document_id)?, &set)
+}
+
+/// The new glyphs are appended, so the edit is just their inserts.
+fn add_glyphs_for_set(open: &mut OpenDocument, set: &CharacterSet) -> Result<Vec<String>, String> {
+    let mut extended = (*open.document).clone();
+    let first = extended.glyphs.len();
+    let added = charset::add_missing_glyphs(&mut extended, set);
+    if added.is_empty() {
+        return Ok(added);
+    }
+    let edits = extended
+        .glyphs
+        .drain(first..)
+        .enumerate()
+        .map(|(i, glyph)| DocumentEdit::InsertGlyph {
+            index: first + i,
+            glyph,
+        })
+        .collect();
+    open.apply_edit(
+        format!("Add {} glyph(s) for {}", added.len(), set.name),
+        DocumentEdit::Compound(edits),
+    )?;
+    Ok(added)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gtf::types::{Glyph, GtfDocument, GtfHeader, Size};
+
+    #[test]
+    fn added_glyphs_are_one_undo_step() {
+        let glyph = |name: &str, ch: char| Glyph {
+            name: name.to_string(),
+            unicode: Some(format!("U+{:04X}", ch as u32)),
+            char_repr: Some(ch),
+            bitmap: vec![".#".to_string()],
+            ..Default::default()
+        };
+        let original = GtfDocument {
+            header: GtfHeader {
+                default_size: Some(Size {
+                    width: 2,
+                    height: 1,
+                }),
+                ..Default::default()
+            },
+            glyphs: vec![glyph("A", 'A'), glyph("C", 'C')],
+        };
+        let mut open = OpenDocument::new(original.clone(), None, false);
+        let set = CharacterSet {
+            id: "abcd".to_string(),
+            name: "ABCD".to_string(),
+            category: String::new(),
+            chars: "ABCD".to_string(),
+        };
+
+        let added = add_glyphs_for_set(&mut open, &set).unwrap();
+        assert_eq!(added, vec!["B", "D"]);
+        let names: Vec<&str> = open
+            .document
+            .glyphs
+            .iter()
+            .map(|g| g.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["A", "C", "B", "D"]);
+        assert!(open.is_dirty);
+
+        // Nothing left to add, nothing recorded
+        assert!(add_glyphs_for_set(&mut open, &set).unwrap().is_empty());
+
+        open.undo().unwrap();
+        assert_eq!(*open.document, original);
+        assert!(open.undo().is_err());
+    }
+}
@@ -0,0 +1,66 @@
+//! Tauri commands composing accented glyphs (`gtf_core::gtf::compose`).
+//!
+//! The frontend previews a batch first and then inserts the same characters
+//! with the same options; composition is repeated on insert, so the result
+//! always matches the current document.
+
+use crate::gtf::compose::{self, ComposeBatch, ComposeOptions};
+use crate::history::DocumentEdit;
+use crate::state::{AppState, DocumentId};
+
+/// Characters the document has no glyph for but could compose.
+#[tauri::command]
+pub fn get_composable_characters(
+    document_id: DocumentId,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<char>, String> {
+    let workspace = state.workspace.lock().unwrap();
+    let open = workspace.get(document_id)?;
+    Ok(compose::composable_chars(&open.document))
+}
+
+#[tauri::command]
+pub fn preview_composed_glyphs(
+    document_id: DocumentId,
+    chars: Vec<char>,
+    options: ComposeOptions,
+    state: tauri::State<'_, AppState>,
+) -> Result<ComposeBatch, String> {
+    let workspace = state.workspace.lock().unwrap();
+    let open = workspace.get(document_id)?;
+    Ok(compose::compose_glyphs(&open.document, &chars, &options))
+}
+
+/// Appends the composed glyphs to the document as one undo step, the same way
+/// `add_glyph_for_char` appends a blank one. Characters that cannot be
+/// composed are returned in `failed` and skipped.
+#[tauri::command]
+pub fn insert_composed_glyphs(
+    document_id: DocumentId,
+    chars: Vec<char>,
+    options: ComposeOptions,
+    state: tauri::State<'_, AppState>,
+) -> Result<ComposeBatch, String> {
+    let mut workspace = state.workspace.lock().unwrap();
+    let open = workspace.get_mut(document_id)?;
+
+    let batch = compose::compose_glyphs(&open.document, &chars, &options);
+    if batch.composed.is_empty() {
+        return Ok(batch);
+    }
+    let start = open.document.glyphs.len();
+    let edits = batch
+        .composed
+        .iter()
+        .enumerate()
+        .map(|(offset, composed)| DocumentEdit::InsertGlyph {
+            index: start + offset,
+            glyph: composed.glyph.clone(),
+        })
+        .collect();
+    open.apply_edit(
+        format!("Compose {} glyph(s)", batch.composed.len()),
+        DocumentEdit::Compound(edits),
+    )?;
+    Ok(batch)
+}
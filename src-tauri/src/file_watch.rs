@@ -0,0 +1,326 @@
+//! Detection of changes made to open files by other programs.
+//!
+//! A background thread polls the files of all open documents and emits
+//! `FILE_CHANGED_EVENT` once per external change. The frontend then asks for
+//! a per-glyph comparison (`get_disk_changes`) and lets the user reload the
+//! file, keep the in-memory version or take individual glyphs from disk.
+//!
+//! Polling compares mtime and size first and only hashes the content when
+//! those differ, so idle files cost one `stat` per interval. Files are read
+//! and parsed without holding the workspace lock.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::gtf::types::GtfDocument;
+use crate::history::{DocumentEdit, History};
+use crate::recovery::RecoveryState;
+use crate::state::{AppState, DocumentId, DocumentOverview, OpenDocument, Workspace};
+use crate::storage::FileStamp;
+
+pub const FILE_CHANGED_EVENT: &str = "document-file-changed";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Payload of `FILE_CHANGED_EVENT`.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileChangedEvent {
+    pub document_id: DocumentId,
+    pub file_path: String,
+}
+
+/// How a glyph on disk differs from the open document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiskGlyphChange {
+    /// The glyph exists only in the file on disk.
+    Added,
+    /// The glyph exists only in the open document.
+    Removed,
+    /// Both have the glyph, but its content differs.
+    Modified,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiskGlyphDiff {
+    pub name: String,
+    pub change: DiskGlyphChange,
+}
+
+/// Differences between the file on disk and the open document.
+#[derive(Debug, Clone, Serialize)]
+pub struct DiskChanges {
+    pub document_id: DocumentId,
+    pub file_path: String,
+    pub header_changed: bool,
+    pub glyphs: Vec<DiskGlyphDiff>,
+    /// The open document has unsaved changes of its own.
+    pub has_local_changes: bool,
+}
+
+/// Starts the background thread that watches files of open documents.
+pub fn spawn_file_watcher(app: AppHandle) {
+    thread::spawn(move || {
+        // Last state seen on disk per document, so one change is reported once
+        let mut seen: HashMap<DocumentId, FileStamp> = HashMap::new();
+        loop {
+            thread::sleep(POLL_INTERVAL);
+            let state = app.state::<AppState>();
+
+            let watched: Vec<(DocumentId, String, FileStamp)> = {
+                let workspace = state.workspace.lock().unwrap();
+                workspace
+                    .iter()
+                    .filter_map(|(id, open)| {
+                        Some((id, open.file_path.clone()?, open.disk_stamp.clone()?))
+                    })
+                    .collect()
+            };
+            seen.retain(|id, _| watched.iter().any(|(watched_id, ..)| watched_id == id));
+
+            for (id, file_path, loaded) in watched {
+                let path = Path::new(&file_path);
+                let last = seen.get(&id).unwrap_or(&loaded);
+                if last.matches_metadata(path) {
+                    continue;
+                }
+                // A deleted or unreadable file is not reported; saving recreates it
+                let Ok(current) = FileStamp::read(path) else {
+                    continue;
+                };
+                let changed = !current.same_content(last) && !current.same_content(&loaded);
+                seen.insert(id, current);
+                if !changed {
+                    continue;
+                }
+
+                // Skip changes made by our own save in the meantime
+                let still_loaded = state
+                    .workspace
+                    .lock()
+                    .unwrap()
+                    .get(id)
+                    .is_ok_and(|open| open.disk_stamp.as_ref() == Some(&loaded));
+                if still_loaded {
+                    let _ = app.emit(
+                        FILE_CHANGED_EVENT,
+                        FileChangedEvent {
+                            document_id: id,
+                            file_path: file_path.clone(),
+                        },
+                    );
+                }
+            }
+        }
+    });
+}
+
+/// Path of the file an open document was loaded from (short lock only).
+fn file_path_of(state: &AppState, document_id: DocumentId) -> Result<String, String> {
+    state
+        .workspace
+        .lock()
+        .unwrap()
+        .get(document_id)?
+        .file_path
+        .clone()
+        .ok_or_else(|| "Document has no file on disk".to_string())
+}
+
+/// Reads and parses the file in one streaming pass, the same way
+/// `load_gtf_file` does it, so the stamp describes exactly the parsed content.
+/// Called without holding the workspace lock.
+fn read_disk_version(file_path: &str) -> Result<(FileStamp, GtfDocument), String> {
+    let (disk, stamp) = crate::read_gtf_file(file_path)?;
+    Ok((stamp, disk))
+}
+
+/// Looks the document up again after reading its file without the lock and
+/// makes sure it still belongs to that file (it may have been saved elsewhere).
+fn reopen<'a>(
+    workspace: &'a mut Workspace,
+    document_id: DocumentId,
+    file_path: &str,
+) -> Result<&'a mut OpenDocument, String> {
+    let open = workspace.get_mut(document_id)?;
+    if open.file_path.as_deref() != Some(file_path) {
+        return Err(format!(
+            "Document {} no longer belongs to '{}'",
+            document_id, file_path
+        ));
+    }
+    Ok(open)
+}
+
+/// Compares glyphs by name. Glyphs are listed in disk order, glyphs missing
+/// on disk follow in document order.
+fn compare(memory: &GtfDocument, disk: &GtfDocument) -> (bool, Vec<DiskGlyphDiff>) {
+    let memory_by_name: HashMap<&str, _> =
+        memory.glyphs.iter().map(|g| (g.name.as_str(), g)).collect();
+    let disk_names: HashSet<&str> = disk.glyphs.iter().map(|g| g.name.as_str()).collect();
+
+    let mut glyphs: Vec<DiskGlyphDiff> = disk
+        .glyphs
+        .iter()
+        .filter_map(|glyph| {
+            let change = match memory_by_name.get(glyph.name.as_str()) {
+                None => DiskGlyphChange::Added,
+                Some(existing) if *existing != glyph => DiskGlyphChange::Modified,
+                Some(_) => return None,
+            };
+            Some(DiskGlyphDiff {
+                name: glyph.name.clone(),
+                change,
+            })
+        })
+        .collect();
+    glyphs.extend(
+        memory
+            .glyphs
+            .iter()
+            .filter(|g| !disk_names.contains(g.name.as_str()))
+            .map(|g| DiskGlyphDiff {
+                name: g.name.clone(),
+                change: DiskGlyphChange::Removed,
+            }),
+    );
+    (memory.header != disk.header, glyphs)
+}
+
+/// Builds the edit that makes glyph `name` match its version on disk.
+fn glyph_edit(
+    memory: &GtfDocument,
+    disk: &GtfDocument,
+    name: &str,
+) -> Result<DocumentEdit, String> {
+    let memory_index = memory.glyphs.iter().position(|g| g.name == name);
+    let disk_index = disk.glyphs.iter().position(|g| g.name == name);
+    match (memory_index, disk_index) {
+        (Some(index), Some(disk_index)) => Ok(DocumentEdit::ReplaceGlyph {
+            index,
+            before: memory.glyphs[index].clone(),
+            after: disk.glyphs[disk_index].clone(),
+        }),
+        (None, Some(disk_index)) => Ok(DocumentEdit::InsertGlyph {
+            index: disk_index.min(memory.glyphs.len()),
+            glyph: disk.glyphs[disk_index].clone(),
+        }),
+        (Some(index), None) => Ok(DocumentEdit::RemoveGlyph {
+            index,
+            glyph: memory.glyphs[index].clone(),
+        }),
+        (None, None) => Err(format!("Glyph '{}' not found", name)),
+    }
+}
+
+/// Re-parses the file of a document and reports what differs from the open version.
+#[tauri::command]
+pub fn get_disk_changes(
+    document_id: DocumentId,
+    state: tauri::State<'_, AppState>,
+) -> Result<DiskChanges, String> {
+    let file_path = file_path_of(&state, document_id)?;
+    let (_, disk) = read_disk_version(&file_path)?;
+
+    let mut workspace = state.workspace.lock().unwrap();
+    let open = reopen(&mut workspace, document_id, &file_path)?;
+    let (header_changed, glyphs) = compare(&open.document, &disk);
+    Ok(DiskChanges {
+        document_id,
+        file_path,
+        header_changed,
+        glyphs,
+        has_local_changes: open.is_dirty,
+    })
+}
+
+/// Replaces the document with the file on disk, dropping unsaved changes and history.
+#[tauri::command]
+pub fn reload_from_disk(
+    document_id: DocumentId,
+    state: tauri::State<'_, AppState>,
+    recovery: tauri::State<'_, RecoveryState>,
+) -> Result<DocumentOverview, String> {
+    let file_path = file_path_of(&state, document_id)?;
+    let (stamp, disk) = read_disk_version(&file_path)?;
+
+    let mut workspace = state.workspace.lock().unwrap();
+    let open = reopen(&mut workspace, document_id, &file_path)?;
+    open.replace_document(disk);
+    open.history = History::default();
+    open.disk_stamp = Some(stamp);
+    open.is_dirty = false;
+    recovery.discard_document(document_id);
+    Ok(open.overview(document_id))
+}
+
+/// Keeps the open version. The current file on disk is accepted as the
+/// loaded one, so the next save overwrites it without a conflict.
+#[tauri::command]
+pub fn keep_local_version(
+    document_id: DocumentId,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    let file_path = file_path_of(&state, document_id)?;
+    let stamp = FileStamp::read(Path::new(&file_path))?;
+
+    let mut workspace = state.workspace.lock().unwrap();
+    let open = reopen(&mut workspace, document_id, &file_path)?;
+    open.disk_stamp = Some(stamp);
+    open.is_dirty = true;
+    Ok(())
+}
+
+/// Takes the listed glyphs (and optionally the header) from the file on disk
+/// as one undo step. Glyphs missing on disk are removed from the document.
+#[tauri::command]
+pub fn merge_disk_changes(
+    document_id: DocumentId,
+    glyph_names: Vec<String>,
+    include_header: bool,
+    state: tauri::State<'_, AppState>,
+) -> Result<DocumentOverview, String> {
+    let file_path = file_path_of(&state, document_id)?;
+    let (stamp, disk) = read_disk_version(&file_path)?;
+
+    let mut workspace = state.workspace.lock().unwrap();
+    let open = reopen(&mut workspace, document_id, &file_path)?;
+    let mut edits: Vec<DocumentEdit> = Vec::new();
+    if include_header && open.document.header != disk.header {
+        let edit = DocumentEdit::ReplaceHeader {
+            before: open.document.header.clone(),
+            after: disk.header.clone(),
+        };
+        edit.apply(open.document_mut())?;
+        edits.push(edit);
+    }
+    for name in &glyph_names {
+        let step = glyph_edit(&open.document, &disk, name).and_then(|edit| {
+            edit.apply(open.document_mut())?;
+            Ok(edit)
+        });
+        match step {
+            Ok(edit) => edits.push(edit),
+            Err(err) => {
+                for edit in edits.iter().rev() {
+                    edit.revert(open.document_mut())?;
+                }
+                return Err(err);
+            }
+        }
+    }
+
+    if !edits.is_empty() {
+        open.history
+            .push_applied("Merge changes from disk", DocumentEdit::Compound(edits));
+    }
+    open.disk_stamp = Some(stamp);
+    // Everything taken over -> the document matches the file again
+    open.is_dirty = *open.document != disk;
+    Ok(open.overview(document_id))
+}
@@ -0,0 +1,891 @@
+//! Undo/redo history for document-mutating commands.
+//!
+//! Every mutation of `AppState.document` is expressed as a `DocumentEdit`
+//! (command pattern) which knows how to apply and revert itself. The
+//! `History` keeps the undo/redo stacks, merges consecutive pixel edits of a
+//! single stroke into one step and drops the oldest steps once the memory
+//! budget is exceeded.
+
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::gtf::color::Color;
+use crate::gtf::types::{Glyph, GtfDocument, GtfHeader, Palette};
+
+/// Default memory budget for the undo/redo stacks (approximate bytes).
+pub const DEFAULT_HISTORY_BUDGET: usize = 64 * 1024 * 1024;
+
+/// Pixel edits without an explicit stroke id are merged when they follow
+/// each other on the same glyph within this window.
+const STROKE_GAP: Duration = Duration::from_millis(750);
+
+/// One changed pixel inside a `SetPixels` edit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PixelChange {
+    pub row: usize,
+    pub col: usize,
+    pub before: char,
+    pub after: char,
+}
+
+/// Glyphs and header an edit changed, so the frontend can refetch only those.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct EditSummary {
+    /// Names of glyphs inserted or modified, as they are after the edit.
+    pub touched_glyphs: Vec<String>,
+    /// Names of glyphs removed or renamed away.
+    pub removed_glyphs: Vec<String>,
+    pub header_changed: bool,
+}
+
+/// A reversible change of the document.
+///
+/// Glyphs are addressed by index, which stays valid because all mutations go
+/// through the history in order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DocumentEdit {
+    InsertGlyph {
+        index: usize,
+        glyph: Glyph,
+    },
+    RemoveGlyph {
+        index: usize,
+        glyph: Glyph,
+    },
+    ReplaceGlyph {
+        index: usize,
+        before: Glyph,
+        after: Glyph,
+    },
+    SetPixels {
+        index: usize,
+        changes: Vec<PixelChange>,
+    },
+    ReplaceHeader {
+        before: GtfHeader,
+        after: GtfHeader,
+    },
+    /// Several edits applied (and reverted) as one step.
+    Compound(Vec<DocumentEdit>),
+}
+
+impl DocumentEdit {
+    /// Applies the edit. On error the document is left as it was, including
+    /// the sub-edits of a `Compound` applied before the failing one.
+    pub fn apply(&self, doc: &mut GtfDocument) -> Result<(), String> {
+        match self {
+            DocumentEdit::InsertGlyph { index, glyph } => {
+                if *index > doc.glyphs.len() {
+                    return Err(format!("Glyph index {} out of range", index));
+                }
+                doc.glyphs.insert(*index, glyph.clone());
+            }
+            DocumentEdit::RemoveGlyph { index, .. } => {
+                if *index >= doc.glyphs.len() {
+                    return Err(format!("Glyph index {} out of range", index));
+                }
+                doc.glyphs.remove(*index);
+            }
+            DocumentEdit::ReplaceGlyph { index, after, .. } => {
+                let slot = doc
+                    .glyphs
+                    .get_mut(*index)
+                    .ok_or_else(|| format!("Glyph index {} out of range", index))?;
+                *slot = after.clone();
+            }
+            DocumentEdit::SetPixels { index, changes } => {
+                let glyph = doc
+                    .glyphs
+                    .get_mut(*index)
+                    .ok_or_else(|| format!("Glyph index {} out of range", index))?;
+                for (i, change) in changes.iter().enumerate() {
+                    if let Err(err) = glyph.set_pixel(change.row, change.col, change.after) {
+                        for done in changes[..i].iter().rev() {
+                            glyph.set_pixel(done.row, done.col, done.before)?;
+                        }
+                        return Err(err);
+                    }
+                }
+            }
+            DocumentEdit::ReplaceHeader { after, .. } => {
+                doc.header = after.clone();
+            }
+            DocumentEdit::Compound(edits) => {
+                for (i, edit) in edits.iter().enumerate() {
+                    if let Err(err) = edit.apply(doc) {
+                        // Roll back the sub-edits applied so far, newest first
+                        for done in edits[..i].iter().rev() {
+                            done.revert(doc)?;
+                        }
+                        return Err(err);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reverts the edit; like `apply`, a failed revert leaves the document unchanged.
+    pub fn revert(&self, doc: &mut GtfDocument) -> Result<(), String> {
+        match self {
+            DocumentEdit::InsertGlyph { index, .. } => {
+                if *index >= doc.glyphs.len() {
+                    return Err(format!("Glyph index {} out of range", index));
+                }
+                doc.glyphs.remove(*index);
+            }
+            DocumentEdit::RemoveGlyph { index, glyph } => {
+                if *index > doc.glyphs.len() {
+                    return Err(format!("Glyph index {} out of range", index));
+                }
+                doc.glyphs.insert(*index, glyph.clone());
+            }
+            DocumentEdit::ReplaceGlyph { index, before, .. } => {
+                let slot = doc
+                    .glyphs
+                    .get_mut(*index)
+                    .ok_or_else(|| format!("Glyph index {} out of range", index))?;
+                *slot = before.clone();
+            }
+            DocumentEdit::SetPixels { index, changes } => {
+                let glyph = doc
+                    .glyphs
+                    .get_mut(*index)
+                    .ok_or_else(|| format!("Glyph index {} out of range", index))?;
+                for (i, change) in changes.iter().enumerate().rev() {
+                    if let Err(err) = glyph.set_pixel(change.row, change.col, change.before) {
+                        for done in &changes[i + 1..] {
+                            glyph.set_pixel(done.row, done.col, done.after)?;
+                        }
+                        return Err(err);
+                    }
+                }
+            }
+            DocumentEdit::ReplaceHeader { before, .. } => {
+                doc.header = before.clone();
+            }
+            DocumentEdit::Compound(edits) => {
+                for (i, edit) in edits.iter().enumerate().rev() {
+                    if let Err(err) = edit.revert(doc) {
+                        // Re-apply the sub-edits reverted so far, oldest first
+                        for done in &edits[i + 1..] {
+                            done.apply(doc)?;
+                        }
+                        return Err(err);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Edit that turns `before` into `after`, for commands that compute a
+    /// whole new document (fixes, transformations). Glyphs are matched by
+    /// name: only glyphs missing on one side are removed or inserted, and
+    /// glyphs whose content or position changed are replaced in place, so
+    /// adding a glyph to a large font records just that glyph. `None` if
+    /// nothing changed.
+    pub fn between(before: &GtfDocument, after: &GtfDocument) -> Option<DocumentEdit> {
+        let mut edits = Vec::new();
+        if before.header != after.header {
+            edits.push(DocumentEdit::ReplaceHeader {
+                before: before.header.clone(),
+                after: after.header.clone(),
+            });
+        }
+
+        // Glyphs of `before` still present in `after` (by name, duplicates counted)
+        let mut wanted: HashMap<&str, usize> = HashMap::new();
+        for glyph in &after.glyphs {
+            *wanted.entry(glyph.name.as_str()).or_default() += 1;
+        }
+        let mut kept = Vec::with_capacity(before.glyphs.len());
+        let mut removed = Vec::new();
+        for (index, glyph) in before.glyphs.iter().enumerate() {
+            match wanted.get_mut(glyph.name.as_str()) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    kept.push(glyph);
+                }
+                _ => removed.push(index),
+            }
+        }
+        for &index in removed.iter().rev() {
+            edits.push(DocumentEdit::RemoveGlyph {
+                index,
+                glyph: before.glyphs[index].clone(),
+            });
+        }
+
+        // Inserting the new glyphs at their final index, in order, leaves the
+        // kept glyphs in the remaining slots
+        let mut available: HashMap<&str, usize> = HashMap::new();
+        for glyph in &kept {
+            *available.entry(glyph.name.as_str()).or_default() += 1;
+        }
+        let mut kept = kept.into_iter();
+        let mut merged = Vec::with_capacity(after.glyphs.len());
+        for (index, glyph) in after.glyphs.iter().enumerate() {
+            match available.get_mut(glyph.name.as_str()) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    merged.extend(kept.next());
+                }
+                _ => {
+                    edits.push(DocumentEdit::InsertGlyph {
+                        index,
+                        glyph: glyph.clone(),
+                    });
+                    merged.push(glyph);
+                }
+            }
+        }
+
+        for (index, (old, new)) in merged.into_iter().zip(&after.glyphs).enumerate() {
+            if old != new {
+                edits.push(DocumentEdit::ReplaceGlyph {
+                    index,
+                    before: old.clone(),
+                    after: new.clone(),
+                });
+            }
+        }
+        (!edits.is_empty()).then_some(DocumentEdit::Compound(edits))
+    }
+
+    /// What the edit changes when applied to `before`. Glyph indices are
+    /// followed through the edit, so names are resolved as the edit applies.
+    pub fn summary(&self, before: &GtfDocument) -> EditSummary {
+        let mut names: Vec<&str> = before.glyphs.iter().map(|g| g.name.as_str()).collect();
+        let mut touched: BTreeSet<String> = BTreeSet::new();
+        let mut removed: BTreeSet<String> = BTreeSet::new();
+        let mut header_changed = false;
+        self.collect_changes(&mut names, &mut touched, &mut removed, &mut header_changed);
+        EditSummary {
+            touched_glyphs: touched.into_iter().collect(),
+            removed_glyphs: removed.into_iter().collect(),
+            header_changed,
+        }
+    }
+
+    fn collect_changes<'a>(
+        &'a self,
+        names: &mut Vec<&'a str>,
+        touched: &mut BTreeSet<String>,
+        removed: &mut BTreeSet<String>,
+        header_changed: &mut bool,
+    ) {
+        match self {
+            DocumentEdit::InsertGlyph { index, glyph } => {
+                names.insert((*index).min(names.len()), &glyph.name);
+                removed.remove(&glyph.name);
+                touched.insert(glyph.name.clone());
+            }
+            DocumentEdit::RemoveGlyph { index, .. } => {
+                if *index < names.len() {
+                    mark_removed(names.remove(*index), touched, removed);
+                }
+            }
+            DocumentEdit::ReplaceGlyph { index, after, .. } => {
+                if let Some(name) = names.get_mut(*index) {
+                    if *name != after.name {
+                        mark_removed(name, touched, removed);
+                        removed.remove(&after.name);
+                    }
+                    *name = &after.name;
+                    touched.insert(after.name.clone());
+                }
+            }
+            DocumentEdit::SetPixels { index, .. } => {
+                if let Some(name) = names.get(*index) {
+                    touched.insert(name.to_string());
+                }
+            }
+            DocumentEdit::ReplaceHeader { .. } => *header_changed = true,
+            DocumentEdit::Compound(edits) => {
+                for edit in edits {
+                    edit.collect_changes(names, touched, removed, header_changed);
+                }
+            }
+        }
+    }
+
+    /// Rough estimate of the heap memory held by this edit.
+    fn approx_bytes(&self) -> usize {
+        let base = std::mem::size_of::<DocumentEdit>();
+        base + match self {
+            DocumentEdit::InsertGlyph { glyph, .. } | DocumentEdit::RemoveGlyph { glyph, .. } => {
+                glyph_bytes(glyph)
+            }
+            DocumentEdit::ReplaceGlyph { before, after, .. } => {
+                glyph_bytes(before) + glyph_bytes(after)
+            }
+            DocumentEdit::SetPixels { changes, .. } => {
+                changes.len() * std::mem::size_of::<PixelChange>()
+            }
+            DocumentEdit::ReplaceHeader { before, after } => {
+                header_bytes(before) + header_bytes(after)
+            }
+            DocumentEdit::Compound(edits) => edits.iter().map(|e| e.approx_bytes()).sum(),
+        }
+    }
+}
+
+fn mark_removed(name: &str, touched: &mut BTreeSet<String>, removed: &mut BTreeSet<String>) {
+    touched.remove(name);
+    removed.insert(name.to_string());
+}
+
+fn palette_bytes(palette: Option<&Palette>) -> usize {
+    palette.map_or(0, |p| {
+        p.entries.len() * (std::mem::size_of::<char>() + std::mem::size_of::<Color>())
+    })
+}
+
+fn glyph_bytes(glyph: &Glyph) -> usize {
+    std::mem::size_of::<Glyph>()
+        + glyph.name.len()
+        + glyph.unicode.as_ref().map_or(0, |u| u.len())
+        + glyph.bitmap.iter().map(|row| row.len()).sum::<usize>()
+        + palette_bytes(glyph.palette.as_ref())
+        + glyph
+            .validation_warnings
+            .as_ref()
+            .map_or(0, |w| w.iter().map(|s| s.len()).sum())
+}
+
+fn header_bytes(header: &GtfHeader) -> usize {
+    std::mem::size_of::<GtfHeader>()
+        + [
+            &header.font_name,
+            &header.version,
+            &header.author,
+            &header.description,
+        ]
+        .iter()
+        .map(|field| field.as_ref().map_or(0, |s| s.len()))
+        .sum::<usize>()
+        + palette_bytes(header.default_palette.as_ref())
+}
+
+struct HistoryEntry {
+    id: u64,
+    label: String,
+    /// Shared with journals taken by autosave, so taking one copies no edits.
+    edit: Arc<DocumentEdit>,
+    bytes: usize,
+}
+
+/// Identifies the pixel stroke that is still open for merging.
+struct OpenStroke {
+    index: usize,
+    stroke_id: Option<u64>,
+    last_touch: Instant,
+}
+
+/// Undo/redo stacks for a single document.
+pub struct History {
+    undo_stack: VecDeque<HistoryEntry>,
+    redo_stack: Vec<HistoryEntry>,
+    open_stroke: Option<OpenStroke>,
+    budget_bytes: usize,
+    used_bytes: usize,
+    next_id: u64,
+}
+
+impl History {
+    pub fn new(budget_bytes: usize) -> Self {
+        Self {
+            undo_stack: VecDeque::new(),
+            redo_stack: Vec::new(),
+            open_stroke: None,
+            budget_bytes,
+            used_bytes: 0,
+            next_id: 1,
+        }
+    }
+
+    /// Applies the edit to the document and records it as a new undo step.
+    pub fn apply(
+        &mut self,
+        doc: &mut GtfDocument,
+        label: impl Into<String>,
+        edit: DocumentEdit,
+    ) -> Result<(), String> {
+        edit.apply(doc)?;
+        self.open_stroke = None;
+        self.push(label.into(), Arc::new(edit));
+        Ok(())
+    }
+
+    /// Records an edit that the caller has already applied to the document
+    /// (e.g. a validated batch, see `operations::apply_operations`).
+    pub fn push_applied(&mut self, label: impl Into<String>, edit: DocumentEdit) {
+        self.open_stroke = None;
+        self.push(label.into(), Arc::new(edit));
+    }
+
+    /// Changes one pixel and records it, merging it into the previous step
+    /// when it belongs to the same stroke.
+    ///
+    /// A stroke is identified by `stroke_id` when the caller provides one,
+    /// otherwise consecutive edits of the same glyph within `STROKE_GAP` form a stroke.
+    pub fn apply_pixel(
+        &mut self,
+        doc: &mut GtfDocument,
+        index: usize,
+        row: usize,
+        col: usize,
+        new_char: char,
+        stroke_id: Option<u64>,
+    ) -> Result<(), String> {
+        let glyph = doc
+            .glyphs
+            .get_mut(index)
+            .ok_or_else(|| format!("Glyph index {} out of range", index))?;
+        let before = glyph.set_pixel(row, col, new_char)?;
+        let change = PixelChange {
+            row,
+            col,
+            before,
+            after: new_char,
+        };
+        let label = format!("Draw in '{}'", glyph.name);
+
+        let now = Instant::now();
+        let continues_stroke = self.open_stroke.as_ref().is_some_and(|open| {
+            open.index == index
+                && open.stroke_id == stroke_id
+                && (stroke_id.is_some() || now.duration_since(open.last_touch) <= STROKE_GAP)
+        });
+
+        let stroke_entry = self.undo_stack.back_mut().filter(|entry| {
+            continues_stroke && matches!(*entry.edit, DocumentEdit::SetPixels { .. })
+        });
+        if let Some(HistoryEntry { edit, bytes, .. }) = stroke_entry {
+            if let DocumentEdit::SetPixels { changes, .. } = Arc::make_mut(edit) {
+                match changes.iter_mut().find(|c| c.row == row && c.col == col) {
+                    // Same pixel painted twice within a stroke: keep the original `before`
+                    Some(existing) => existing.after = new_char,
+                    None => {
+                        changes.push(change);
+                        *bytes += std::mem::size_of::<PixelChange>();
+                        self.used_bytes += std::mem::size_of::<PixelChange>();
+                    }
+                }
+                if let Some(open) = self.open_stroke.as_mut() {
+                    open.last_touch = now;
+                }
+                self.enforce_budget();
+                return Ok(());
+            }
+        }
+
+        self.push(
+            label,
+            Arc::new(DocumentEdit::SetPixels {
+                index,
+                changes: vec![change],
+            }),
+        );
+        self.open_stroke = Some(OpenStroke {
+            index,
+            stroke_id,
+            last_touch: now,
+        });
+        Ok(())
+    }
+
+    /// Reverts the most recent step. Returns its label.
+    pub fn undo(&mut self, doc: &mut GtfDocument) -> Result<String, String> {
+        self.open_stroke = None;
+        let entry = self.undo_stack.back().ok_or("Nothing to undo")?;
+        // A failed revert leaves the document untouched, so the step stays on the stack
+        entry
+            .edit
+            .revert(doc)
+            .map_err(|err| format!("Failed to undo '{}': {}", entry.label, err))?;
+        let entry = self.undo_stack.pop_back().ok_or("Nothing to undo")?;
+        let label = entry.label.clone();
+        self.redo_stack.push(entry);
+        Ok(label)
+    }
+
+    /// Re-applies the most recently undone step. Returns its label.
+    pub fn redo(&mut self, doc: &mut GtfDocument) -> Result<String, String> {
+        self.open_stroke = None;
+        let entry = self.redo_stack.last().ok_or("Nothing to redo")?;
+        entry
+            .edit
+            .apply(doc)
+            .map_err(|err| format!("Failed to redo '{}': {}", entry.label, err))?;
+        let entry = self.redo_stack.pop().ok_or("Nothing to redo")?;
+        let label = entry.label.clone();
+        self.undo_stack.push_back(entry);
+        Ok(label)
+    }
+
+    /// Copies the undo/redo stacks into a serializable journal (used by autosave).
+    pub fn journal(&self) -> HistoryJournal {
+        let step = |entry: &HistoryEntry| JournalStep {
+            label: entry.label.clone(),
+            edit: entry.edit.clone(),
+        };
+        HistoryJournal {
+            undo: self.undo_stack.iter().map(step).collect(),
+            redo: self.redo_stack.iter().map(step).collect(),
+        }
+    }
+
+    /// Rebuilds a history from a journal written by `journal`. The document the
+    /// journal belongs to must be in the state reached after all `undo` steps.
+    pub fn from_journal(journal: HistoryJournal, budget_bytes: usize) -> Self {
+        let mut history = Self::new(budget_bytes);
+        for step in journal.undo {
+            history.push(step.label, step.edit);
+        }
+        for step in journal.redo {
+            let bytes = step.edit.approx_bytes();
+            history.used_bytes += bytes;
+            history.redo_stack.push(HistoryEntry {
+                id: history.next_id,
+                label: step.label,
+                edit: step.edit,
+                bytes,
+            });
+            history.next_id += 1;
+        }
+        history
+    }
+
+    /// Snapshot of the stacks for the UI.
+    pub fn info(&self) -> HistoryInfo {
+        let describe = |entry: &HistoryEntry| HistoryStep {
+            id: entry.id,
+            label: entry.label.clone(),
+        };
+        HistoryInfo {
+            undo: self.undo_stack.iter().map(describe).collect(),
+            redo: self.redo_stack.iter().rev().map(describe).collect(),
+            used_bytes: self.used_bytes,
+            budget_bytes: self.budget_bytes,
+        }
+    }
+
+    fn push(&mut self, label: String, edit: Arc<DocumentEdit>) {
+        // A new step invalidates everything that could be redone
+        for entry in self.redo_stack.drain(..) {
+            self.used_bytes -= entry.bytes;
+        }
+
+        let bytes = edit.approx_bytes();
+        self.used_bytes += bytes;
+        self.undo_stack.push_back(HistoryEntry {
+            id: self.next_id,
+            label,
+            edit,
+            bytes,
+        });
+        self.next_id += 1;
+        self.enforce_budget();
+    }
+
+    /// Drops the oldest steps until the stacks fit into the budget.
+    /// The newest step is always kept, even if it alone exceeds the budget.
+    fn enforce_budget(&mut self) {
+        while self.used_bytes > self.budget_bytes && self.undo_stack.len() > 1 {
+            if let Some(oldest) = self.undo_stack.pop_front() {
+                self.used_bytes -= oldest.bytes;
+            }
+        }
+    }
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_BUDGET)
+    }
+}
+
+/// One step as shown in the UI history list.
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryStep {
+    pub id: u64,
+    pub label: String,
+}
+
+/// Current state of the undo/redo stacks.
+/// `undo` is ordered oldest → newest, `redo` is ordered next → last.
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryInfo {
+    pub undo: Vec<HistoryStep>,
+    pub redo: Vec<HistoryStep>,
+    pub used_bytes: usize,
+    pub budget_bytes: usize,
+}
+
+/// One recorded step in a `HistoryJournal`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalStep {
+    pub label: String,
+    pub edit: Arc<DocumentEdit>,
+}
+
+/// Serializable copy of the undo/redo stacks.
+/// `undo` is ordered oldest → newest, `redo` is kept in stack order (last item is redone first).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HistoryJournal {
+    pub undo: Vec<JournalStep>,
+    pub redo: Vec<JournalStep>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glyph(name: &str, bitmap: &[&str]) -> Glyph {
+        Glyph {
+            name: name.to_string(),
+            bitmap: bitmap.iter().map(|row| row.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn document() -> GtfDocument {
+        GtfDocument {
+            header: GtfHeader {
+                font_name: Some("Test".to_string()),
+                ..Default::default()
+            },
+            glyphs: vec![glyph("A", &["..", "##"]), glyph("B", &["#.", ".#"])],
+        }
+    }
+
+    #[test]
+    fn undo_and_redo_round_trip() {
+        let original = document();
+        let mut doc = original.clone();
+        let mut history = History::default();
+
+        let mut modified = doc.clone();
+        modified.glyphs[1].bitmap = vec!["##".to_string(), "##".to_string()];
+        modified.glyphs.push(glyph("C", &["#"]));
+        modified.header.author = Some("Someone".to_string());
+        let edit = DocumentEdit::between(&doc, &modified).unwrap();
+        history.apply(&mut doc, "Edit", edit).unwrap();
+        assert_eq!(doc, modified);
+
+        assert_eq!(history.undo(&mut doc).unwrap(), "Edit");
+        assert_eq!(doc, original);
+        assert_eq!(history.redo(&mut doc).unwrap(), "Edit");
+        assert_eq!(doc, modified);
+        assert!(history.redo(&mut doc).is_err());
+    }
+
+    #[test]
+    fn between_records_only_added_and_removed_glyphs() {
+        let mut large = document();
+        large.glyphs = (0..2000)
+            .map(|i| glyph(&format!("g{}", i), &["#.#.", ".#.#"]))
+            .collect();
+        let mut added = large.clone();
+        added.glyphs.insert(1000, glyph("new", &["####"]));
+
+        let edit = DocumentEdit::between(&large, &added).unwrap();
+        let DocumentEdit::Compound(edits) = &edit else {
+            panic!("expected a compound edit");
+        };
+        assert!(matches!(
+            edits.as_slice(),
+            [DocumentEdit::InsertGlyph { index: 1000, .. }]
+        ));
+        // The compound plus one inserted glyph, not the 2000 glyphs of the font
+        assert_eq!(
+            edit.approx_bytes(),
+            2 * std::mem::size_of::<DocumentEdit>() + glyph_bytes(&added.glyphs[1000])
+        );
+
+        // Removed, inserted, changed and reordered glyphs still round-trip
+        let mut changed = large.clone();
+        changed.glyphs.remove(5);
+        changed.glyphs.swap(0, 1);
+        changed.glyphs[10].bitmap = vec!["####".to_string()];
+        changed.glyphs.push(glyph("tail", &["#"]));
+        let edit = DocumentEdit::between(&large, &changed).unwrap();
+        let mut doc = large.clone();
+        edit.apply(&mut doc).unwrap();
+        assert_eq!(doc, changed);
+        edit.revert(&mut doc).unwrap();
+        assert_eq!(doc, large);
+    }
+
+    #[test]
+    fn summary_follows_renames_and_shifted_indices() {
+        let before = document();
+        let mut after = before.clone();
+        after.glyphs.insert(0, glyph("C", &["#"]));
+        after.glyphs[1].name = "A2".to_string();
+        after.glyphs.remove(2);
+        after.header.author = Some("Someone".to_string());
+
+        let edit = DocumentEdit::between(&before, &after).unwrap();
+        assert_eq!(
+            edit.summary(&before),
+            EditSummary {
+                touched_glyphs: vec!["A2".to_string(), "C".to_string()],
+                removed_glyphs: vec!["A".to_string(), "B".to_string()],
+                header_changed: true,
+            }
+        );
+
+        // Pixel edits name the glyph at their index after earlier inserts
+        let edit = DocumentEdit::Compound(vec![
+            DocumentEdit::InsertGlyph {
+                index: 0,
+                glyph: glyph("C", &["#"]),
+            },
+            DocumentEdit::SetPixels {
+                index: 2,
+                changes: vec![],
+            },
+        ]);
+        assert_eq!(edit.summary(&before).touched_glyphs, vec!["B", "C"]);
+    }
+
+    #[test]
+    fn between_identical_documents_is_none() {
+        let doc = document();
+        assert!(DocumentEdit::between(&doc, &doc.clone()).is_none());
+    }
+
+    #[test]
+    fn failed_compound_rolls_back_applied_edits() {
+        let original = document();
+        let mut doc = original.clone();
+        let mut history = History::default();
+        let edit = DocumentEdit::Compound(vec![
+            DocumentEdit::ReplaceGlyph {
+                index: 0,
+                before: original.glyphs[0].clone(),
+                after: glyph("A", &["##", "##"]),
+            },
+            DocumentEdit::InsertGlyph {
+                index: 1,
+                glyph: glyph("C", &["#"]),
+            },
+            DocumentEdit::RemoveGlyph {
+                index: 7,
+                glyph: glyph("X", &[]),
+            },
+        ]);
+
+        assert!(history.apply(&mut doc, "Broken", edit).is_err());
+        assert_eq!(doc, original);
+        assert!(history.info().undo.is_empty());
+    }
+
+    #[test]
+    fn failed_pixel_edit_restores_earlier_pixels() {
+        let original = document();
+        let mut doc = original.clone();
+        let edit = DocumentEdit::SetPixels {
+            index: 0,
+            changes: vec![
+                PixelChange {
+                    row: 0,
+                    col: 0,
+                    before: '.',
+                    after: '#',
+                },
+                PixelChange {
+                    row: 5,
+                    col: 0,
+                    before: '.',
+                    after: '#',
+                },
+            ],
+        };
+        assert!(edit.apply(&mut doc).is_err());
+        assert_eq!(doc, original);
+    }
+
+    #[test]
+    fn failed_undo_keeps_the_step() {
+        let mut doc = document();
+        let mut history = History::default();
+        let edit = DocumentEdit::InsertGlyph {
+            index: 2,
+            glyph: glyph("C", &["#"]),
+        };
+        history.apply(&mut doc, "Add C", edit).unwrap();
+
+        // Someone removed the glyph behind the history's back
+        doc.glyphs.truncate(2);
+        let before = doc.clone();
+        assert!(history.undo(&mut doc).is_err());
+        assert_eq!(doc, before);
+        assert_eq!(history.info().undo.len(), 1);
+    }
+
+    #[test]
+    fn pixels_of_one_stroke_are_one_step() {
+        let original = document();
+        let mut doc = original.clone();
+        let mut history = History::default();
+        history
+            .apply_pixel(&mut doc, 0, 0, 0, '#', Some(1))
+            .unwrap();
+        history
+            .apply_pixel(&mut doc, 0, 0, 1, '#', Some(1))
+            .unwrap();
+        history
+            .apply_pixel(&mut doc, 0, 0, 0, '+', Some(1))
+            .unwrap();
+        history
+            .apply_pixel(&mut doc, 0, 1, 0, '.', Some(2))
+            .unwrap();
+
+        assert_eq!(doc.glyphs[0].bitmap, vec!["+#", ".#"]);
+        assert_eq!(history.info().undo.len(), 2);
+        history.undo(&mut doc).unwrap();
+        history.undo(&mut doc).unwrap();
+        assert_eq!(doc, original);
+    }
+
+    #[test]
+    fn budget_drops_oldest_steps_but_keeps_newest() {
+        let mut doc = document();
+        let mut history = History::new(1);
+        for ch in ['#', '.', '#'] {
+            let edit = DocumentEdit::SetPixels {
+                index: 1,
+                changes: vec![PixelChange {
+                    row: 0,
+                    col: 0,
+                    before: doc.glyphs[1].bitmap[0].chars().next().unwrap(),
+                    after: ch,
+                }],
+            };
+            history.apply(&mut doc, "Pixel", edit).unwrap();
+        }
+        assert_eq!(history.info().undo.len(), 1);
+    }
+
+    #[test]
+    fn journal_rebuilds_the_stacks() {
+        let mut doc = document();
+        let mut history = History::default();
+        let mut modified = doc.clone();
+        modified.glyphs.remove(0);
+        let edit = DocumentEdit::between(&doc, &modified).unwrap();
+        history.apply(&mut doc, "Remove A", edit).unwrap();
+        history.undo(&mut doc).unwrap();
+
+        let mut restored = History::from_journal(history.journal(), DEFAULT_HISTORY_BUDGET);
+        assert_eq!(restored.redo(&mut doc).unwrap(), "Remove A");
+        assert_eq!(doc, modified);
+    }
+}
@@ -1,10 +1,20 @@
-mod exporters;
-mod gtf;
-mod importers;
+mod charset;
+mod compose;
+mod file_watch;
+mod history;
+mod lint;
+mod operations;
+mod recovery;
 mod rendering;
 mod state;
+mod storage;
+pub use gtf_core::{exporters, gtf, importers};
 use base64::Engine;
+use history::DocumentEdit;
+use state::DocumentId;
 use std::fs;
+use std::path::Path;
+use tauri::Manager;
 
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 // We remove the default greet function
@@ -13,116 +23,432 @@ use std::fs;
 //     format!("Hello, {}! You've been greeted from Rust!", name)
 // }
 
-/// Načte a zparsuje GTF soubor a uloží ho do globálního stavu Rustu.
+/// Otevře soubor a zparsuje ho streamově (bez načtení celého textu do paměti).
+/// Otisk souboru se počítá ze stejných bajtů, které čte parser, takže odpovídá načtené verzi.
+pub(crate) fn read_gtf_file(path: &str) -> Result<(gtf::GtfDocument, storage::FileStamp), String> {
+    let mut reader = storage::StampingReader::open(Path::new(path))?;
+    let doc = gtf::parse_gtf_reader(&mut reader)?;
+    Ok((doc, reader.finish(Path::new(path))?))
+}
+
+/// Načte a zparsuje GTF soubor a otevře ho jako nový dokument ve workspace.
+/// Frontendu vrátí jen přehled (hlavička + počet glyfů), celý dokument zůstává v backendu.
+/// Glyfy se pak stránkují přes `get_glyph_summaries` a bitmapy se načítají přes `get_glyphs`.
 #[tauri::command]
 fn load_gtf_file(
     path: String,
     state: tauri::State<'_, state::AppState>,
-) -> Result<gtf::GtfDocument, String> {
-    // 1. Přečteme obsah souboru z disku
-    let content = fs::read_to_string(&path)
-        .map_err(|err| format!("Failed to read file '{}': {}", path, err))?;
-
-    // 2. Zparsujeme text na GTF strukturu
-    let doc = gtf::parse_gtf_content(&content)?;
+) -> Result<state::DocumentOverview, String> {
+    // 1.+2. Čteme soubor po řádcích a rovnou ho parsujeme na GTF strukturu.
+    // Zároveň bereme otisk souboru, aby pozdější uložení poznalo, že ho mezitím změnil někdo jiný.
+    let (doc, stamp) = read_gtf_file(&path)?;
 
-    // 3. Uložíme dokument, cestu a resetujeme 'dirty' příznak v globálním stavu
+    // 3. Uložíme dokument a cestu do workspace, dokument není 'dirty'
     // lock() nám zajistí, že v tuhle chvíli s daty nepracuje jiný příkaz.
-    *state.document.lock().unwrap() = Some(doc.clone());
-    *state.file_path.lock().unwrap() = Some(path);
-    *state.is_dirty.lock().unwrap() = false;
+    // Dokument do stavu přesuneme, ne klonujeme
+    let mut open = state::OpenDocument::new(doc, Some(path), false);
+    open.disk_stamp = Some(stamp);
+    let mut workspace = state.workspace.lock().unwrap();
+    let document_id = workspace.open(open);
+    Ok(workspace.get(document_id)?.overview(document_id))
+}
 
-    // Vrátíme dokument frontendu pro první zobrazení
-    Ok(doc)
+/// Totéž co `load_gtf_file` (dříve jediná varianta, která neposílala celý dokument).
+#[tauri::command]
+fn load_gtf_file_paged(
+    path: String,
+    state: tauri::State<'_, state::AppState>,
+) -> Result<state::DocumentOverview, String> {
+    load_gtf_file(path, state)
 }
 
-/// Uloží aktuální dokument ze stavu na disk.
+/// Přehled otevřeného dokumentu (hlavička + počet glyfů) bez bitmap, např. po undo
+/// nebo jiné úpravě v backendu.
+#[tauri::command]
+fn get_document_overview(
+    document_id: DocumentId,
+    state: tauri::State<'_, state::AppState>,
+) -> Result<state::DocumentOverview, String> {
+    let workspace = state.workspace.lock().unwrap();
+    Ok(workspace.get(document_id)?.overview(document_id))
+}
+
+/// Vrátí stránku souhrnů glyfů (jméno, kódový bod, velikost) bez bitmap.
+#[tauri::command]
+fn get_glyph_summaries(
+    document_id: DocumentId,
+    offset: usize,
+    limit: usize,
+    state: tauri::State<'_, state::AppState>,
+) -> Result<state::GlyphPage, String> {
+    let workspace = state.workspace.lock().unwrap();
+    let doc = &workspace.get(document_id)?.document;
+
+    let glyphs = doc
+        .glyphs
+        .iter()
+        .skip(offset)
+        .take(limit)
+        .map(|g| g.summary())
+        .collect();
+
+    Ok(state::GlyphPage {
+        offset,
+        total: doc.glyphs.len(),
+        glyphs,
+    })
+}
+
+/// Vrátí kompletní glyfy (včetně bitmap a palet) podle jmen – načítání na vyžádání.
+/// Neznámá jména se tiše přeskočí.
+#[tauri::command]
+fn get_glyphs(
+    document_id: DocumentId,
+    glyph_names: Vec<String>,
+    state: tauri::State<'_, state::AppState>,
+) -> Result<Vec<gtf::types::Glyph>, String> {
+    let workspace = state.workspace.lock().unwrap();
+    let doc = &workspace.get(document_id)?.document;
+
+    // Jména indexujeme jednou, ať dotaz na tisíce glyfů neprochází font pro každé jméno
+    let index: std::collections::HashMap<&str, &gtf::types::Glyph> =
+        doc.glyphs.iter().map(|g| (g.name.as_str(), g)).collect();
+    Ok(glyph_names
+        .iter()
+        .filter_map(|name| index.get(name.as_str()).map(|g| (*g).clone()))
+        .collect())
+}
+
+/// Uloží dokument ze stavu na disk.
+/// Zapisuje atomicky (dočasný soubor + přejmenování) a případně ponechá rotující `.bak` kopie.
+/// Pokud se soubor na disku od načtení změnil, uložení odmítne, dokud frontend nepošle `force`.
 #[tauri::command]
 fn save_gtf_file(
+    document_id: DocumentId,
     path: Option<String>,
+    force: Option<bool>,
     state: tauri::State<'_, state::AppState>,
+    recovery: tauri::State<'_, recovery::RecoveryState>,
 ) -> Result<(), String> {
-    // Získáme lock na dokument
-    let doc_lock = state.document.lock().unwrap();
-    let doc = doc_lock.as_ref().ok_or("No document loaded to save")?;
-
-    // Určíme cestu (buď předaná, nebo ta z dřívějška)
-    let final_path = match path {
-        Some(p) => {
-            *state.file_path.lock().unwrap() = Some(p.clone());
-            p
+    let settings = state.save_settings.lock().unwrap().clone();
+
+    // Pod zámkem jen určíme cestu a vezmeme snímek dokumentu (`Arc`, bez kopírování),
+    // serializace a zápis s fsync běží bez zámku, aby neblokovaly ostatní příkazy
+    let (final_path, document, revision, previous_stamp) = {
+        let mut workspace = state.workspace.lock().unwrap();
+        let open = workspace.get_mut(document_id)?;
+
+        // Určíme cestu (buď předaná, nebo ta z dřívějška)
+        let final_path = path
+            .or_else(|| open.file_path.clone())
+            .ok_or("No path specified")?;
+
+        // Přepisujeme soubor, ze kterého jsme četli? Pak nesmí být mezitím změněný
+        let same_file = open.file_path.as_deref() == Some(final_path.as_str());
+        if !force.unwrap_or(false) && same_file {
+            if let Some(stamp) = &open.disk_stamp {
+                storage::ensure_unchanged(Path::new(&final_path), stamp)?;
+            }
+        }
+        // Během zápisu dokument otisk nemá, aby hlídání souborů nebralo náš zápis za cizí změnu
+        let previous_stamp = if same_file {
+            open.disk_stamp.take()
+        } else {
+            None
+        };
+        (
+            final_path,
+            std::sync::Arc::clone(&open.document),
+            open.revision(),
+            previous_stamp,
+        )
+    };
+
+    // Serializace do textu (GTF formát), palety glyfů podle nastavení, a zápis na disk
+    let written = gtf::serialize_gtf_document_with(
+        &document,
+        &gtf::SerializeOptions {
+            palette_output: settings.palette_output,
+        },
+    )
+    .and_then(|content| {
+        storage::write_atomic(
+            Path::new(&final_path),
+            content.as_bytes(),
+            settings.backup_count,
+        )
+    });
+
+    let mut workspace = state.workspace.lock().unwrap();
+    // Dokument mezitím zavřený – soubor je zapsaný, není co aktualizovat
+    let Ok(open) = workspace.get_mut(document_id) else {
+        return written.map(|_| ());
+    };
+    let stamp = match written {
+        Ok(stamp) => stamp,
+        Err(err) => {
+            if open.disk_stamp.is_none() {
+                open.disk_stamp = previous_stamp;
+            }
+            return Err(err);
         }
-        None => state
-            .file_path
-            .lock()
-            .unwrap()
-            .clone()
-            .ok_or("No path specified")?,
     };
 
-    // Serializace do textu (GTF formát)
-    let content = gtf::serialize_gtf_document(&doc)?;
+    // Soubor je uložen; 'dirty' zůstává jen kvůli úpravám provedeným během zápisu.
+    // Bez nich už záloha pro obnovu po pádu není potřeba
+    open.file_path = Some(final_path);
+    open.disk_stamp = Some(stamp);
+    if open.revision() == revision {
+        open.is_dirty = false;
+        recovery.discard_document(document_id);
+    }
 
-    // Zápis na disk
-    fs::write(&final_path, content)
-        .map_err(|err| format!("Failed to write file '{}': {}", final_path, err))?;
+    Ok(())
+}
 
-    // Soubor je uložen, už není 'dirty'
-    *state.is_dirty.lock().unwrap() = false;
+/// Vrátí nastavení ukládání (počet záložních kopií, zápis palet glyfů).
+#[tauri::command]
+fn get_save_settings(
+    state: tauri::State<'_, state::AppState>,
+) -> Result<storage::SaveSettings, String> {
+    Ok(state.save_settings.lock().unwrap().clone())
+}
 
+#[tauri::command]
+fn set_save_settings(
+    settings: storage::SaveSettings,
+    state: tauri::State<'_, state::AppState>,
+) -> Result<(), String> {
+    if settings.backup_count > storage::MAX_BACKUP_COUNT {
+        return Err(format!(
+            "At most {} backup copies can be kept",
+            storage::MAX_BACKUP_COUNT
+        ));
+    }
+    *state.save_settings.lock().unwrap() = settings;
     Ok(())
 }
 
 #[tauri::command]
 fn get_current_document(
+    document_id: DocumentId,
     state: tauri::State<'_, state::AppState>,
-) -> Result<Option<gtf::GtfDocument>, String> {
-    Ok(state.document.lock().unwrap().clone())
+) -> Result<gtf::GtfDocument, String> {
+    Ok(state
+        .workspace
+        .lock()
+        .unwrap()
+        .get(document_id)?
+        .document
+        .as_ref()
+        .clone())
 }
 
 #[tauri::command]
-fn init_new_document(state: tauri::State<'_, state::AppState>) -> Result<gtf::GtfDocument, String> {
+fn init_new_document(
+    state: tauri::State<'_, state::AppState>,
+) -> Result<state::OpenedDocument, String> {
     let new_doc = gtf::GtfDocument::default();
-    *state.document.lock().unwrap() = Some(new_doc.clone());
-    *state.file_path.lock().unwrap() = None;
-    *state.is_dirty.lock().unwrap() = true; // Nový nesoubor je technicky 'dirty'
-    Ok(new_doc)
-}
-/// Vrátí základní informace o stavu (jestli je dokument načten, cesta, is_dirty).
-#[tauri::command]
-fn get_state_info(state: tauri::State<'_, state::AppState>) -> state::StateInfo {
-    state::StateInfo {
-        has_document: state.document.lock().unwrap().is_some(),
-        file_path: state.file_path.lock().unwrap().clone(),
-        is_dirty: *state.is_dirty.lock().unwrap(),
+    // Nový nesoubor je technicky 'dirty'
+    let document_id = state
+        .workspace
+        .lock()
+        .unwrap()
+        .open(state::OpenDocument::new(new_doc.clone(), None, true));
+    Ok(state::OpenedDocument {
+        document_id,
+        document: new_doc,
+    })
+}
+/// Vrátí základní informace o stavu dokumentu (cesta, is_dirty).
+#[tauri::command]
+fn get_state_info(
+    document_id: DocumentId,
+    state: tauri::State<'_, state::AppState>,
+) -> Result<state::StateInfo, String> {
+    let workspace = state.workspace.lock().unwrap();
+    let open = workspace.get(document_id)?;
+    Ok(state::StateInfo {
+        document_id,
+        has_document: true,
+        file_path: open.file_path.clone(),
+        is_dirty: open.is_dirty,
+    })
+}
+
+/// Vrátí seznam všech otevřených dokumentů.
+#[tauri::command]
+fn list_documents(state: tauri::State<'_, state::AppState>) -> Vec<state::DocumentInfo> {
+    state.workspace.lock().unwrap().list()
+}
+
+/// Přepne aktivní dokument a vrátí jeho přehled pro zobrazení.
+#[tauri::command]
+fn switch_document(
+    document_id: DocumentId,
+    state: tauri::State<'_, state::AppState>,
+) -> Result<state::DocumentOverview, String> {
+    let mut workspace = state.workspace.lock().unwrap();
+    workspace.set_active(document_id)?;
+    Ok(workspace.get(document_id)?.overview(document_id))
+}
+
+/// Zavře dokument. Neuložené změny zahodí jen s `force`, jinak vrátí chybu.
+/// Vrací id dokumentu, který je po zavření aktivní (pokud nějaký zbyl).
+#[tauri::command]
+fn close_document(
+    document_id: DocumentId,
+    force: bool,
+    state: tauri::State<'_, state::AppState>,
+    recovery: tauri::State<'_, recovery::RecoveryState>,
+) -> Result<Option<DocumentId>, String> {
+    let mut workspace = state.workspace.lock().unwrap();
+    if workspace.get(document_id)?.is_dirty && !force {
+        return Err(format!("Document {} has unsaved changes", document_id));
     }
+    workspace.close(document_id)?;
+    recovery.discard_document(document_id);
+    Ok(workspace.active())
+}
+
+/// Porovná dva otevřené dokumenty (přidané/odebrané/přejmenované glyfy, hlavička, palety, pixely).
+#[tauri::command]
+fn diff_documents(
+    old_document_id: DocumentId,
+    new_document_id: DocumentId,
+    state: tauri::State<'_, state::AppState>,
+) -> Result<gtf::diff::DocumentDiff, String> {
+    let workspace = state.workspace.lock().unwrap();
+    Ok(gtf::diff::diff_documents(
+        &workspace.get(old_document_id)?.document,
+        &workspace.get(new_document_id)?.document,
+    ))
+}
+
+/// Výsledek kopírování glyfů mezi dokumenty.
+#[derive(serde::Serialize)]
+struct CopiedGlyph {
+    source_name: String,
+    target_name: String,
+    palette: gtf::palette::PaletteReconciliation,
+}
+
+/// Zkopíruje glyfy z jednoho otevřeného dokumentu do druhého.
+/// Paleta se sladí s výchozí paletou cíle (viz `gtf::palette::reconcile_glyph_palette`).
+/// Existující glyf se stejným jménem se s `replace_existing` přepíše, jinak dostane kopie nové jméno.
+/// Celé kopírování je v cílovém dokumentu jeden krok historie.
+#[tauri::command]
+fn copy_glyphs_between_documents(
+    source_document_id: DocumentId,
+    target_document_id: DocumentId,
+    glyph_names: Vec<String>,
+    replace_existing: bool,
+    state: tauri::State<'_, state::AppState>,
+) -> Result<Vec<CopiedGlyph>, String> {
+    let mut workspace = state.workspace.lock().unwrap();
+    let (source, target) = workspace.pair_mut(source_document_id, target_document_id)?;
+
+    let source_default = source.document.header.default_palette.as_ref();
+    let mut working = (*target.document).clone();
+    let mut edits = Vec::new();
+    let mut copied = Vec::new();
+
+    for name in &glyph_names {
+        let glyph = source
+            .document
+            .glyphs
+            .iter()
+            .find(|g| &g.name == name)
+            .ok_or_else(|| format!("Glyph '{}' not found in source document", name))?;
+
+        let (mut new_glyph, report) = gtf::palette::reconcile_glyph_palette(
+            glyph,
+            source_default,
+            working.header.default_palette.as_ref(),
+        );
+
+        let existing = working.glyphs.iter().position(|g| g.name == glyph.name);
+        let edit = match existing {
+            Some(pos) if replace_existing => DocumentEdit::ReplaceGlyph {
+                index: pos,
+                before: working.glyphs[pos].clone(),
+                after: new_glyph.clone(),
+            },
+            _ => {
+                if existing.is_some() {
+                    let mut counter = 1;
+                    let mut new_name = format!("{}{}", glyph.name, counter);
+                    while working.glyphs.iter().any(|g| g.name == new_name) {
+                        counter += 1;
+                        new_name = format!("{}{}", glyph.name, counter);
+                    }
+                    new_glyph.name = new_name;
+                }
+                DocumentEdit::InsertGlyph {
+                    index: working.glyphs.len(),
+                    glyph: new_glyph.clone(),
+                }
+            }
+        };
+        // Aplikujeme na pracovní kopii, aby další glyfy viděly už přidaná jména
+        edit.apply(&mut working)?;
+        edits.push(edit);
+
+        copied.push(CopiedGlyph {
+            source_name: glyph.name.clone(),
+            target_name: new_glyph.name,
+            palette: report,
+        });
+    }
+
+    if !edits.is_empty() {
+        target.apply_edit(
+            format!("Copy {} glyph(s)", edits.len()),
+            DocumentEdit::Compound(edits),
+        )?;
+    }
+    Ok(copied)
 }
 
 /// Aktualizuje (nebo přidá) konkrétní glyf v dokumentu.
 #[tauri::command]
 fn update_glyph(
+    document_id: DocumentId,
     glyph_name: String,
     updated_glyph: gtf::types::Glyph,
     state: tauri::State<'_, state::AppState>,
 ) -> Result<(), String> {
-    let mut doc_lock = state.document.lock().unwrap();
-    let doc = doc_lock.as_mut().ok_or("No document loaded")?;
-
-    if let Some(pos) = doc.glyphs.iter().position(|g| g.name == glyph_name) {
-        doc.glyphs[pos] = updated_glyph;
+    let mut workspace = state.workspace.lock().unwrap();
+    let open = workspace.get_mut(document_id)?;
+    let doc = &open.document;
+
+    let edit = if let Some(pos) = doc.glyphs.iter().position(|g| g.name == glyph_name) {
+        operations::validate_glyph_name(doc, Some(&glyph_name), &updated_glyph.name)?;
+        DocumentEdit::ReplaceGlyph {
+            index: pos,
+            before: doc.glyphs[pos].clone(),
+            after: updated_glyph,
+        }
     } else {
         // Pokud neexistuje pod starým jménem, prostě ho přidáme (např. u nového glyfu)
-        doc.glyphs.push(updated_glyph);
-    }
-    *state.is_dirty.lock().unwrap() = true;
-    Ok(())
+        operations::validate_glyph_name(doc, None, &updated_glyph.name)?;
+        DocumentEdit::InsertGlyph {
+            index: doc.glyphs.len(),
+            glyph: updated_glyph,
+        }
+    };
+    open.apply_edit(format!("Edit glyph '{}'", glyph_name), edit)
 }
 
 /// Vytvoří nový prázdný glyf s výchozím nastavením.
 #[tauri::command]
-fn add_empty_glyph(state: tauri::State<'_, state::AppState>) -> Result<gtf::types::Glyph, String> {
-    let mut doc_lock = state.document.lock().unwrap();
-    let doc = doc_lock.as_mut().ok_or("No document loaded")?;
+fn add_empty_glyph(
+    document_id: DocumentId,
+    state: tauri::State<'_, state::AppState>,
+) -> Result<gtf::types::Glyph, String> {
+    let mut workspace = state.workspace.lock().unwrap();
+    let open = workspace.get_mut(document_id)?;
+    let doc = &open.document;
 
     let mut base_name = "NewGlyph".to_string();
     let mut new_name = base_name.clone();
@@ -136,22 +462,31 @@ fn add_empty_glyph(state: tauri::State<'_, state::AppState>) -> Result<gtf::type
         width: 5,
         height: 7,
     });
+    let fill = gtf::palette::default_background_char(doc).unwrap_or(gtf::resize::DEFAULT_FILL);
     let initial_bitmap =
-        vec![".".repeat(initial_size.width as usize); initial_size.height as usize];
-    let initial_palette = doc.header.default_palette.clone().unwrap_or_default();
+        vec![fill.to_string().repeat(initial_size.width as usize); initial_size.height as usize];
 
     let new_glyph = gtf::types::Glyph {
         name: new_name,
         unicode: None,
         char_repr: None,
         size: Some(initial_size),
-        palette: Some(initial_palette),
+        offset: None,
+        cell: None,
+        // Barvy se dědí z výchozí palety, glyf zatím nemá vlastní přepisy
+        palette: Some(gtf::types::Palette::default()),
         bitmap: initial_bitmap,
         validation_warnings: None,
     };
 
-    doc.glyphs.push(new_glyph.clone());
-    *state.is_dirty.lock().unwrap() = true;
+    let index = doc.glyphs.len();
+    open.apply_edit(
+        format!("Add glyph '{}'", new_glyph.name),
+        DocumentEdit::InsertGlyph {
+            index,
+            glyph: new_glyph.clone(),
+        },
+    )?;
 
     Ok(new_glyph)
 }
@@ -159,77 +494,101 @@ fn add_empty_glyph(state: tauri::State<'_, state::AppState>) -> Result<gtf::type
 /// Vytvoří nový glyf pro konkrétní znak.
 #[tauri::command]
 fn add_glyph_for_char(
+    document_id: DocumentId,
     char: char,
     state: tauri::State<'_, state::AppState>,
 ) -> Result<gtf::types::Glyph, String> {
-    let mut doc_lock = state.document.lock().unwrap();
-    let doc = doc_lock.as_mut().ok_or("No document loaded")?;
+    let mut workspace = state.workspace.lock().unwrap();
+    let open = workspace.get_mut(document_id)?;
+    let doc = &open.document;
 
     // Pokud už existuje, vrátíme ho (nebo ho můžeme vybrat ve Vue)
     if let Some(existing) = doc.glyphs.iter().find(|g| g.char_repr == Some(char)) {
         return Ok(existing.clone());
     }
 
-    let base_name = if char.is_alphanumeric() {
-        char.to_string()
-    } else {
-        "Glyph".to_string()
-    };
-    let mut new_name = base_name.clone();
-    let mut counter = 1;
-    if doc.glyphs.iter().any(|g| g.name == new_name) {
-        new_name = format!("{}{}", base_name, counter);
-        while doc.glyphs.iter().any(|g| g.name == new_name) {
-            counter += 1;
-            new_name = format!("{}{}", base_name, counter);
-        }
-    }
-
-    let initial_size = doc.header.default_size.clone().unwrap_or(gtf::types::Size {
-        width: 5,
-        height: 7,
-    });
-    let initial_bitmap =
-        vec![".".repeat(initial_size.width as usize); initial_size.height as usize];
-    let initial_palette = doc.header.default_palette.clone().unwrap_or_default();
+    let fill = gtf::palette::default_background_char(doc).unwrap_or(gtf::resize::DEFAULT_FILL);
+    let new_glyph = gtf::charmap::blank_glyph_for_char(doc, char, fill);
 
-    let unicode = format!("U+{:04X}", char as u32);
+    let index = doc.glyphs.len();
+    open.apply_edit(
+        format!("Add glyph '{}'", new_glyph.name),
+        DocumentEdit::InsertGlyph {
+            index,
+            glyph: new_glyph.clone(),
+        },
+    )?;
 
-    let new_glyph = gtf::types::Glyph {
-        name: new_name,
-        unicode: Some(unicode),
-        char_repr: Some(char),
-        size: Some(initial_size),
-        palette: Some(initial_palette),
-        bitmap: initial_bitmap,
-        validation_warnings: None,
-    };
+    Ok(new_glyph)
+}
 
-    doc.glyphs.push(new_glyph.clone());
-    *state.is_dirty.lock().unwrap() = true;
+/// Výsledek normalizace CHAR / UNICODE.
+#[derive(serde::Serialize)]
+struct CharmapNormalization {
+    report: gtf::charmap::NormalizeReport,
+    /// Změněné glyfy pod novými jmény, frontend si načte jen je.
+    touched_glyphs: Vec<String>,
+    /// Původní jména přejmenovaných glyfů.
+    removed_glyphs: Vec<String>,
+}
 
-    Ok(new_glyph)
+/// Doplní chybějící UNICODE z CHAR a naopak, případně přejmenuje glyfy podle konvence.
+/// Konflikty se jen nahlásí. S `dry_run` se dokument nemění a vrátí se jen náhled
+/// změn; jinak je celá normalizace jeden krok historie.
+#[tauri::command]
+fn normalize_character_mapping(
+    document_id: DocumentId,
+    options: gtf::charmap::NormalizeOptions,
+    dry_run: Option<bool>,
+    state: tauri::State<'_, state::AppState>,
+) -> Result<CharmapNormalization, String> {
+    let mut workspace = state.workspace.lock().unwrap();
+    let open = workspace.get_mut(document_id)?;
+
+    let mut normalized = (*open.document).clone();
+    let report = gtf::charmap::normalize_charmap(&mut normalized, &options);
+    let mut summary = history::EditSummary::default();
+    if let Some(edit) = DocumentEdit::between(&open.document, &normalized) {
+        summary = edit.summary(&open.document);
+        if !dry_run.unwrap_or(false) {
+            open.apply_edit("Normalize character mapping", edit)?;
+        }
+    }
+    Ok(CharmapNormalization {
+        report,
+        touched_glyphs: summary.touched_glyphs,
+        removed_glyphs: summary.removed_glyphs,
+    })
 }
 
-/// Aplikuje výchozí paletu z hlavičky na konkrétní glyf.
+/// Vrátí glyf k výchozí paletě z hlavičky: zahodí jeho vlastní přepisy barev.
 #[tauri::command]
 fn apply_default_palette_to_glyph(
+    document_id: DocumentId,
     glyph_name: String,
     state: tauri::State<'_, state::AppState>,
 ) -> Result<gtf::types::Glyph, String> {
-    let mut doc_lock = state.document.lock().unwrap();
-    let doc = doc_lock.as_mut().ok_or("No document loaded")?;
-
-    let default_palette = doc
-        .header
-        .default_palette
-        .clone()
-        .ok_or("No default palette defined in header")?;
-
-    if let Some(glyph) = doc.glyphs.iter_mut().find(|g| g.name == glyph_name) {
-        glyph.palette = Some(default_palette);
-        *state.is_dirty.lock().unwrap() = true;
-        Ok(glyph.clone())
+    let mut workspace = state.workspace.lock().unwrap();
+    let open = workspace.get_mut(document_id)?;
+    let doc = &open.document;
+
+    if doc.header.default_palette.is_none() {
+        return Err("No default palette defined in header".to_string());
+    }
+
+    if let Some(pos) = doc.glyphs.iter().position(|g| g.name == glyph_name) {
+        let before = doc.glyphs[pos].clone();
+        let mut after = before.clone();
+        after.palette = Some(gtf::types::Palette::default());
+        open.apply_edit(
+            format!("Apply default palette to '{}'", glyph_name),
+            DocumentEdit::ReplaceGlyph {
+                index: pos,
+                before,
+                after: after.clone(),
+            },
+        )?;
+        Ok(after)
     } else {
         Err(format!("Glyph '{}' not found", glyph_name))
     }
@@ -238,13 +597,15 @@ fn apply_default_palette_to_glyph(
 /// Aktualizuje konkrétní pole glyfu.
 #[tauri::command]
 fn update_glyph_field(
+    document_id: DocumentId,
     glyph_name: String,
     field: String,
     value: serde_json::Value,
     state: tauri::State<'_, state::AppState>,
 ) -> Result<gtf::types::Glyph, String> {
-    let mut doc_lock = state.document.lock().unwrap();
-    let doc = doc_lock.as_mut().ok_or("No document loaded")?;
+    let mut workspace = state.workspace.lock().unwrap();
+    let open = workspace.get_mut(document_id)?;
+    let doc = &open.document;
 
     let pos = doc
         .glyphs
@@ -253,43 +614,42 @@ fn update_glyph_field(
         .ok_or_else(|| format!("Glyph '{}' not found", glyph_name))?;
 
     let mut glyph = doc.glyphs[pos].clone();
-
-    match field.as_str() {
-        "name" => {
-            let name = value.as_str().ok_or("Name must be a string")?;
-            glyph.name = name.to_string();
-        }
-        "unicode" => {
-            glyph.unicode = value
-                .as_str()
-                .filter(|s| !s.is_empty())
-                .map(|s| s.to_string());
-        }
-        "char_repr" => {
-            let s = value.as_str().unwrap_or("");
-            glyph.char_repr = s.chars().next();
-        }
-        _ => return Err(format!("Field '{}' not supported for atomic update", field)),
-    }
-
-    doc.glyphs[pos] = glyph.clone();
-    *state.is_dirty.lock().unwrap() = true;
+    operations::set_glyph_field(&mut glyph, &field, &value)?;
+    operations::validate_glyph_name(doc, Some(&glyph_name), &glyph.name)?;
+
+    let before = doc.glyphs[pos].clone();
+    open.apply_edit(
+        format!("Change {} of '{}'", field, glyph_name),
+        DocumentEdit::ReplaceGlyph {
+            index: pos,
+            before,
+            after: glyph.clone(),
+        },
+    )?;
     Ok(glyph)
 }
 
 /// Smaže glyf z dokumentu.
 #[tauri::command]
 fn remove_glyph(
+    document_id: DocumentId,
     glyph_name: String,
     state: tauri::State<'_, state::AppState>,
 ) -> Result<(), String> {
-    let mut doc_lock = state.document.lock().unwrap();
-    let doc = doc_lock.as_mut().ok_or("No document loaded")?;
+    let mut workspace = state.workspace.lock().unwrap();
+    let open = workspace.get_mut(document_id)?;
 
-    if let Some(pos) = doc.glyphs.iter().position(|g| g.name == glyph_name) {
-        doc.glyphs.remove(pos);
-        *state.is_dirty.lock().unwrap() = true;
-        Ok(())
+    if let Some(pos) = open
+        .document
+        .glyphs
+        .iter()
+        .position(|g| g.name == glyph_name)
+    {
+        let glyph = open.document.glyphs[pos].clone();
+        open.apply_edit(
+            format!("Remove glyph '{}'", glyph_name),
+            DocumentEdit::RemoveGlyph { index: pos, glyph },
+        )
     } else {
         Err(format!("Glyph '{}' not found", glyph_name))
     }
@@ -297,141 +657,412 @@ fn remove_glyph(
 
 /// Změní jeden pixel v bitmapě konkrétního glyfu.
 /// Ideální pro kreslení tužkou ve Vue.
+/// Pixely se stejným `stroke_id` (nebo rychle po sobě bez něj) tvoří jeden krok historie.
 #[tauri::command]
 fn update_glyph_pixel(
+    document_id: DocumentId,
     glyph_name: String,
     row: usize,
     col: usize,
     new_char: char,
+    stroke_id: Option<u64>,
     state: tauri::State<'_, state::AppState>,
 ) -> Result<(), String> {
-    let mut doc_lock = state.document.lock().unwrap();
-    let doc = doc_lock.as_mut().ok_or("No document loaded")?;
-
-    if let Some(glyph) = doc.glyphs.iter_mut().find(|g| g.name == glyph_name) {
-        if row < glyph.bitmap.len() {
-            let row_str = &mut glyph.bitmap[row];
-            if col < row_str.chars().count() {
-                // V Rustu jsou stringy UTF-8, takže musíme opatrně měnit znak na pozici
-                let mut chars: Vec<char> = row_str.chars().collect();
-                chars[col] = new_char;
-                *row_str = chars.into_iter().collect();
-
-                *state.is_dirty.lock().unwrap() = true;
-                return Ok(());
-            }
-        }
-        Err("Pixel coordinates out of bounds".to_string())
-    } else {
-        Err(format!("Glyph '{}' not found", glyph_name))
-    }
+    let mut workspace = state.workspace.lock().unwrap();
+    let open = workspace.get_mut(document_id)?;
+
+    let pos = open
+        .document
+        .glyphs
+        .iter()
+        .position(|g| g.name == glyph_name)
+        .ok_or_else(|| format!("Glyph '{}' not found", glyph_name))?;
+
+    let (document, history) = open.parts_mut();
+    history.apply_pixel(document, pos, row, col, new_char, stroke_id)?;
+    open.is_dirty = true;
+    Ok(())
 }
 
 /// Aktualizuje hlavičku dokumentu ve stavu.
 #[tauri::command]
 fn update_header(
+    document_id: DocumentId,
     new_header: gtf::types::GtfHeader,
     state: tauri::State<'_, state::AppState>,
 ) -> Result<(), String> {
-    let mut doc_lock = state.document.lock().unwrap();
-    let doc = doc_lock.as_mut().ok_or("No document loaded")?;
+    check_palette_roles(&new_header.roles, new_header.default_palette.as_ref())?;
+    let mut workspace = state.workspace.lock().unwrap();
+    let open = workspace.get_mut(document_id)?;
 
-    doc.header = new_header;
-    *state.is_dirty.lock().unwrap() = true;
-    Ok(())
+    let edit = DocumentEdit::ReplaceHeader {
+        before: open.document.header.clone(),
+        after: new_header,
+    };
+    open.apply_edit("Edit header", edit)
 }
 
 /// Aktualizuje globální paletu fontu ve stavu.
 #[tauri::command]
 fn update_default_palette(
+    document_id: DocumentId,
     new_palette: gtf::types::Palette,
     state: tauri::State<'_, state::AppState>,
 ) -> Result<(), String> {
-    let mut doc_lock = state.document.lock().unwrap();
-    let doc = doc_lock.as_mut().ok_or("No document loaded")?;
+    let mut workspace = state.workspace.lock().unwrap();
+    let open = workspace.get_mut(document_id)?;
+
+    let mut after = open.document.header.clone();
+    after.default_palette = Some(new_palette);
+    let edit = DocumentEdit::ReplaceHeader {
+        before: open.document.header.clone(),
+        after,
+    };
+    open.apply_edit("Edit default palette", edit)
+}
 
-    doc.header.default_palette = Some(new_palette);
-    *state.is_dirty.lock().unwrap() = true;
+/// Role palety musí ukazovat na znaky výchozí palety.
+fn check_palette_roles(
+    roles: &gtf::types::PaletteRoles,
+    default_palette: Option<&gtf::types::Palette>,
+) -> Result<(), String> {
+    for ch in [roles.background, roles.foreground].into_iter().flatten() {
+        if !default_palette.is_some_and(|p| p.entries.contains_key(&ch)) {
+            return Err(format!("Character '{}' is not in the default palette", ch));
+        }
+    }
     Ok(())
 }
 
-/// Resizes a bitmap to new dimensions.
-/// - Height adjustment: Adds rows filled with default_char ('.') or removes rows
-/// - Width adjustment: Pads rows with default_char or truncates them
+/// Navrhne role palety (pozadí, popředí) podle využití barev v glyfech; nic neukládá.
+#[tauri::command]
+fn detect_palette_roles(
+    document_id: DocumentId,
+    state: tauri::State<'_, state::AppState>,
+) -> Result<gtf::types::PaletteRoles, String> {
+    let workspace = state.workspace.lock().unwrap();
+    let open = workspace.get(document_id)?;
+    Ok(gtf::palette::detect_roles(&open.document))
+}
+
+/// Nastaví role palety v hlavičce. Znaky musí být ve výchozí paletě.
 #[tauri::command]
+fn set_palette_roles(
+    document_id: DocumentId,
+    roles: gtf::types::PaletteRoles,
+    state: tauri::State<'_, state::AppState>,
+) -> Result<(), String> {
+    let mut workspace = state.workspace.lock().unwrap();
+    let open = workspace.get_mut(document_id)?;
+
+    check_palette_roles(&roles, open.document.header.default_palette.as_ref())?;
+    let mut after = open.document.header.clone();
+    after.roles = roles;
+    let edit = DocumentEdit::ReplaceHeader {
+        before: open.document.header.clone(),
+        after,
+    };
+    open.apply_edit("Set palette roles", edit)
+}
+
+/// Přejmenuje znak palety `from` na `to` ve všech paletách, bitmapách a rolích (`gtf::remap`).
+/// Pokud `to` už existuje, znaky se sloučí a `to` si ponechá svou barvu. Jeden krok historie.
+#[tauri::command]
+fn remap_palette_char(
+    document_id: DocumentId,
+    from: char,
+    to: char,
+    state: tauri::State<'_, state::AppState>,
+) -> Result<gtf::remap::RemapReport, String> {
+    let mut workspace = state.workspace.lock().unwrap();
+    let open = workspace.get_mut(document_id)?;
+
+    let mut remapped = (*open.document).clone();
+    let report = gtf::remap::remap_char(&mut remapped, from, to)?;
+    if let Some(edit) = DocumentEdit::between(&open.document, &remapped) {
+        let verb = if report.merged { "Merge" } else { "Remap" };
+        open.apply_edit(format!("{} palette '{}' → '{}'", verb, from, to), edit)?;
+    }
+    Ok(report)
+}
+
+/// Nahradí barvu `from` barvou `to` ve všech paletách; vrátí počet změněných položek.
+#[tauri::command]
+fn replace_palette_color(
+    document_id: DocumentId,
+    from: gtf::Color,
+    to: gtf::Color,
+    state: tauri::State<'_, state::AppState>,
+) -> Result<usize, String> {
+    let mut workspace = state.workspace.lock().unwrap();
+    let open = workspace.get_mut(document_id)?;
+
+    let mut replaced = (*open.document).clone();
+    let count = gtf::remap::replace_color(&mut replaced, from, to);
+    if let Some(edit) = DocumentEdit::between(&open.document, &replaced) {
+        open.apply_edit(format!("Replace color {} → {}", from, to), edit)?;
+    }
+    Ok(count)
+}
+
+/// Sloučí podobné barvy v rámci tolerance a znaky výchozí palety se stejnou barvou.
+/// S `dry_run` se dokument nemění a vrátí se jen náhled.
+#[tauri::command]
+fn merge_similar_colors(
+    document_id: DocumentId,
+    options: gtf::remap::ColorMergeOptions,
+    dry_run: Option<bool>,
+    state: tauri::State<'_, state::AppState>,
+) -> Result<gtf::remap::ColorMergeReport, String> {
+    let mut workspace = state.workspace.lock().unwrap();
+    let open = workspace.get_mut(document_id)?;
+
+    let mut merged = (*open.document).clone();
+    let report = gtf::remap::merge_similar_colors(&mut merged, &options);
+    if !dry_run.unwrap_or(false) {
+        if let Some(edit) = DocumentEdit::between(&open.document, &merged) {
+            open.apply_edit("Merge similar colors", edit)?;
+        }
+    }
+    Ok(report)
+}
+
+/// Odstraní z palet glyfů položky shodné s výchozí paletou (optimalizovaná forma podle specifikace).
+#[tauri::command]
+fn optimize_glyph_palettes(
+    document_id: DocumentId,
+    state: tauri::State<'_, state::AppState>,
+) -> Result<gtf::remap::PaletteOptimization, String> {
+    let mut workspace = state.workspace.lock().unwrap();
+    let open = workspace.get_mut(document_id)?;
+
+    let mut optimized = (*open.document).clone();
+    let report = gtf::remap::optimize_palettes(&mut optimized);
+    if let Some(edit) = DocumentEdit::between(&open.document, &optimized) {
+        open.apply_edit("Optimize glyph palettes", edit)?;
+    }
+    Ok(report)
+}
+
+/// Provede dávku operací (pixely, pole, změna velikosti, přidání/odebrání glyfů, palety)
+/// atomicky: buď projdou všechny, nebo se dokument nezmění. V historii jsou jeden krok.
+#[tauri::command]
+fn apply_operations(
+    document_id: DocumentId,
+    operations: Vec<operations::Operation>,
+    label: Option<String>,
+    state: tauri::State<'_, state::AppState>,
+) -> Result<operations::OperationsResult, String> {
+    let mut workspace = state.workspace.lock().unwrap();
+    let open = workspace.get_mut(document_id)?;
+
+    operations::apply_operations(open, operations, label)
+}
+
+/// Vrátí poslední krok historie (undo).
+#[tauri::command]
+fn undo(
+    document_id: DocumentId,
+    state: tauri::State<'_, state::AppState>,
+) -> Result<history::HistoryInfo, String> {
+    let mut workspace = state.workspace.lock().unwrap();
+    let open = workspace.get_mut(document_id)?;
+
+    open.undo()?;
+    Ok(open.history.info())
+}
+
+/// Znovu provede naposledy vrácený krok (redo).
+#[tauri::command]
+fn redo(
+    document_id: DocumentId,
+    state: tauri::State<'_, state::AppState>,
+) -> Result<history::HistoryInfo, String> {
+    let mut workspace = state.workspace.lock().unwrap();
+    let open = workspace.get_mut(document_id)?;
+
+    open.redo()?;
+    Ok(open.history.info())
+}
+
+/// Vrátí seznam kroků v historii (pro panel historie a stav tlačítek undo/redo).
+#[tauri::command]
+fn get_history(
+    document_id: DocumentId,
+    state: tauri::State<'_, state::AppState>,
+) -> Result<history::HistoryInfo, String> {
+    Ok(state
+        .workspace
+        .lock()
+        .unwrap()
+        .get(document_id)?
+        .history
+        .info())
+}
+
+/// Resizes a bitmap to new dimensions (see `gtf::resize::resize_bitmap_anchored`).
+/// Without `anchor` the top-left corner stays in place. The fill character is
+/// `fill`, else the background of `glyph_name` in `document_id`, else `'.'`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
 fn resize_bitmap(
     bitmap: Vec<String>,
     old_size: gtf::Size,
     new_size: gtf::Size,
+    anchor: Option<gtf::resize::Anchor>,
+    fill: Option<char>,
+    document_id: Option<DocumentId>,
+    glyph_name: Option<String>,
+    state: tauri::State<'_, state::AppState>,
 ) -> Result<Vec<String>, String> {
-    // Validate inputs
-    if new_size.width == 0 || new_size.height == 0 {
-        return Err("New size dimensions must be greater than zero".to_string());
-    }
+    let fill = match (fill, document_id, glyph_name) {
+        (Some(fill), _, _) => Some(fill),
+        (None, Some(document_id), Some(glyph_name)) => {
+            let workspace = state.workspace.lock().unwrap();
+            let doc = &workspace.get(document_id)?.document;
+            doc.glyphs
+                .iter()
+                .find(|g| g.name == glyph_name)
+                .and_then(|glyph| {
+                    let background = gtf::palette::background_color(doc)?;
+                    gtf::palette::background_char(glyph, doc, background)
+                })
+        }
+        _ => None,
+    };
+    gtf::resize::resize_bitmap_anchored(
+        &bitmap,
+        &old_size,
+        &new_size,
+        anchor.unwrap_or_default(),
+        fill.unwrap_or(gtf::resize::DEFAULT_FILL),
+    )
+}
 
-    if old_size.width == 0 || old_size.height == 0 {
-        return Err("Old size dimensions must be greater than zero".to_string());
+/// Změní výšku celého fontu; všechny glyfy dostanou (nebo ztratí) stejné řádky nahoře
+/// a dole podle kotvy, takže účaří zůstanou zarovnaná. Jeden krok historie.
+#[tauri::command]
+fn change_font_height(
+    document_id: DocumentId,
+    height: u32,
+    anchor: Option<gtf::resize::Anchor>,
+    state: tauri::State<'_, state::AppState>,
+) -> Result<gtf::resize::FontHeightChange, String> {
+    let mut workspace = state.workspace.lock().unwrap();
+    let open = workspace.get_mut(document_id)?;
+
+    let mut resized = (*open.document).clone();
+    let change = gtf::resize::change_font_height(
+        &mut resized,
+        height,
+        anchor.unwrap_or(gtf::resize::Anchor::Bottom),
+    )?;
+    if let Some(edit) = DocumentEdit::between(&open.document, &resized) {
+        open.apply_edit(
+            format!(
+                "Change font height {} → {}",
+                change.old_height, change.new_height
+            ),
+            edit,
+        )?;
     }
+    Ok(change)
+}
 
-    // Default character for padding (always '.')
-    const DEFAULT_CHAR: char = '.';
+/// Otočí, překlopí, posune nebo transponuje glyfy (`gtf::transform`) jako jeden krok historie.
+/// Bez `glyph_names` se transformace použije na všechny glyfy dokumentu.
+#[tauri::command]
+fn transform_glyphs(
+    document_id: DocumentId,
+    transform: gtf::transform::Transform,
+    glyph_names: Option<Vec<String>>,
+    state: tauri::State<'_, state::AppState>,
+) -> Result<operations::OperationsResult, String> {
+    let mut workspace = state.workspace.lock().unwrap();
+    let open = workspace.get_mut(document_id)?;
+
+    let names = glyph_names
+        .unwrap_or_else(|| open.document.glyphs.iter().map(|g| g.name.clone()).collect());
+    let label = match names.as_slice() {
+        [name] => format!("{} '{}'", transform, name),
+        _ => format!("{} ({} glyphs)", transform, names.len()),
+    };
+    let ops = names
+        .into_iter()
+        .map(|glyph_name| operations::Operation::Transform {
+            glyph_name,
+            transform,
+        })
+        .collect();
+    operations::apply_operations(open, ops, Some(label))
+}
 
-    let mut result = bitmap;
+/// Vrátí ink bounding boxy všech glyfů (`gtf::bounds`), např. pro těsné balení při exportu.
+#[tauri::command]
+fn get_glyph_bounds(
+    document_id: DocumentId,
+    state: tauri::State<'_, state::AppState>,
+) -> Result<Vec<gtf::bounds::GlyphBounds>, String> {
+    let workspace = state.workspace.lock().unwrap();
+    let open = workspace.get(document_id)?;
+    Ok(gtf::bounds::glyph_bounds(&open.document))
+}
 
-    // 1. Adjust Height
-    let current_height = result.len() as u32;
-    if new_size.height > current_height {
-        // Add rows
-        let row_to_add: String = std::iter::repeat(DEFAULT_CHAR)
-            .take(new_size.width as usize)
-            .collect();
-        for _ in current_height..new_size.height {
-            result.push(row_to_add.clone());
-        }
-    } else if new_size.height < current_height {
-        // Remove rows
-        result.truncate(new_size.height as usize);
-    }
+/// Ořízne prázdné okraje glyfů a odebraný posun zapíše do `OFFSET` a velikost buňky do `CELL`,
+/// jako jeden krok historie. Font bez role `BACKGROUND` ji při prvním ořezu dostane.
+/// Bez `glyph_names` se ořežou všechny glyfy; glyfy bez inkoustu nebo už oříznuté se přeskočí.
+#[tauri::command]
+fn trim_glyphs(
+    document_id: DocumentId,
+    glyph_names: Option<Vec<String>>,
+    state: tauri::State<'_, state::AppState>,
+) -> Result<operations::OperationsResult, String> {
+    let mut workspace = state.workspace.lock().unwrap();
+    let open = workspace.get_mut(document_id)?;
 
-    // 2. Adjust Width (applied to the potentially height-adjusted bitmap)
-    if new_size.width != old_size.width {
-        result = result
-            .into_iter()
-            .map(|row| {
-                let current_row = row.as_str();
-                if new_size.width > old_size.width {
-                    // Add padding
-                    let current_width = current_row.chars().count();
-                    let padding_needed = (new_size.width as usize).saturating_sub(current_width);
-                    if padding_needed > 0 {
-                        let padding: String = std::iter::repeat(DEFAULT_CHAR)
-                            .take(padding_needed)
-                            .collect();
-                        format!("{}{}", current_row, padding)
-                    } else {
-                        current_row.to_string()
-                    }
-                } else {
-                    // Truncate - take only the first new_size.width characters
-                    current_row
-                        .chars()
-                        .take(new_size.width as usize)
-                        .collect::<String>()
-                }
-            })
-            .collect();
-    }
+    let doc = &open.document;
+    let background = gtf::palette::background_color(doc);
+    let names: Vec<String> = doc
+        .glyphs
+        .iter()
+        .filter(|g| glyph_names.as_ref().is_none_or(|names| names.contains(&g.name)))
+        .filter(|g| gtf::bounds::trim_glyph(g, doc, background).is_some())
+        .map(|g| g.name.clone())
+        .collect();
+    let label = match names.as_slice() {
+        [name] => format!("Trim '{}'", name),
+        _ => format!("Trim {} glyphs", names.len()),
+    };
+    let ops = names
+        .into_iter()
+        .map(|glyph_name| operations::Operation::Trim { glyph_name })
+        .collect();
+    operations::apply_operations(open, ops, Some(label))
+}
 
-    Ok(result)
+/// Najde glyfy se stejnými nebo téměř stejnými bitmapami (`gtf::similarity`).
+/// Bez `options` hledá jen přesné duplikáty glyfů s inkoustem.
+#[tauri::command]
+fn find_duplicate_glyphs(
+    document_id: DocumentId,
+    options: Option<gtf::similarity::SimilarityOptions>,
+    state: tauri::State<'_, state::AppState>,
+) -> Result<Vec<gtf::similarity::DuplicateCluster>, String> {
+    let workspace = state.workspace.lock().unwrap();
+    let open = workspace.get(document_id)?;
+    Ok(gtf::similarity::find_duplicates(
+        &open.document,
+        &options.unwrap_or_default(),
+    ))
 }
 
-/// Importuje font ze souboru, zparsuje ho a uloží do globálního stavu.
+/// Importuje font ze souboru, zparsuje ho a otevře jako nový dokument ve workspace.
 #[tauri::command]
 fn import_font_file(
     path: String,
     format: Option<String>,
     state: tauri::State<'_, state::AppState>,
-) -> Result<gtf::GtfDocument, String> {
+) -> Result<state::DocumentOverview, String> {
     let fmt = match format {
         Some(f) => f,
         None => {
@@ -443,22 +1074,54 @@ fn import_font_file(
     let doc = importers::import_file(&path, &fmt)?;
 
     // Synchronizace do stavu
-    *state.document.lock().unwrap() = Some(doc.clone());
-    *state.file_path.lock().unwrap() = Some(path);
-    *state.is_dirty.lock().unwrap() = true; // Importovaný soubor považujeme za nový/změněný vůči gtf
+    // Importovaný soubor považujeme za nový/změněný vůči gtf
+    let mut workspace = state.workspace.lock().unwrap();
+    let document_id = workspace.open(state::OpenDocument::new(doc, Some(path), true));
+    Ok(workspace.get(document_id)?.overview(document_id))
+}
 
-    Ok(doc)
+/// Vytvoří z dokumentu odvozený řez (tučný, obrys, stín, kurzíva) a otevře ho jako nový dokument.
+#[tauri::command]
+fn generate_font_style(
+    document_id: DocumentId,
+    options: gtf::style::StyleOptions,
+    state: tauri::State<'_, state::AppState>,
+) -> Result<state::DocumentOverview, String> {
+    // Generování řezu běží nad snímkem dokumentu mimo zámek, stejně jako škálování
+    let source = std::sync::Arc::clone(&state.workspace.lock().unwrap().get(document_id)?.document);
+    let styled = gtf::style::generate_style(&source, &options)?;
+
+    let mut workspace = state.workspace.lock().unwrap();
+    let document_id = workspace.open(state::OpenDocument::new(styled, None, true));
+    Ok(workspace.get(document_id)?.overview(document_id))
 }
 
-/// Exportuje aktuální dokument ze stavu do zvoleného formátu.
+/// Zvětší nebo zmenší celý font (`gtf::scale`) a otevře výsledek jako nový dokument.
+#[tauri::command]
+fn scale_font(
+    document_id: DocumentId,
+    mode: gtf::scale::ScaleMode,
+    state: tauri::State<'_, state::AppState>,
+) -> Result<state::DocumentOverview, String> {
+    // Škálování velkého fontu trvá, proto běží nad snímkem dokumentu mimo zámek
+    let source = std::sync::Arc::clone(&state.workspace.lock().unwrap().get(document_id)?.document);
+    let scaled = gtf::scale::scale_document(&source, &mode)?;
+
+    let mut workspace = state.workspace.lock().unwrap();
+    let document_id = workspace.open(state::OpenDocument::new(scaled, None, true));
+    Ok(workspace.get(document_id)?.overview(document_id))
+}
+
+/// Exportuje dokument ze stavu do zvoleného formátu.
 #[tauri::command]
 fn export_font_file(
+    document_id: DocumentId,
     path: String,
     format: String,
     state: tauri::State<'_, state::AppState>,
 ) -> Result<(), String> {
-    let doc_lock = state.document.lock().unwrap();
-    let doc = doc_lock.as_ref().ok_or("No document loaded to export")?;
+    let workspace = state.workspace.lock().unwrap();
+    let doc = &workspace.get(document_id)?.document;
 
     exporters::export_file(doc, &path, &format)
 }
@@ -530,32 +1193,96 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .manage(state::AppState::new()) // Tímto říkáme Tauri, ať si AppState schová do paměti
+        .manage(recovery::RecoveryState::new())
+        .setup(|app| {
+            // Zálohy pro obnovu po pádu ukládáme do app-data adresáře
+            let recovery_dir = app.path().app_data_dir()?.join("recovery");
+            app.state::<recovery::RecoveryState>().init(recovery_dir);
+            recovery::spawn_autosave(app.handle().clone());
+            file_watch::spawn_file_watcher(app.handle().clone());
+            Ok(())
+        })
         // Register command handlers
         .invoke_handler(tauri::generate_handler![
             load_gtf_file,
+            load_gtf_file_paged,
+            get_document_overview,
+            get_glyph_summaries,
+            get_glyphs,
             save_gtf_file,
+            get_save_settings,
+            set_save_settings,
             init_new_document,
             get_current_document,
             get_state_info,
+            list_documents,
+            switch_document,
+            close_document,
+            copy_glyphs_between_documents,
+            diff_documents,
             update_glyph,
             remove_glyph,
             add_empty_glyph,
             add_glyph_for_char,
+            normalize_character_mapping,
             apply_default_palette_to_glyph,
             update_glyph_field,
             update_glyph_pixel,
             update_header,
             update_default_palette,
+            detect_palette_roles,
+            set_palette_roles,
+            remap_palette_char,
+            replace_palette_color,
+            merge_similar_colors,
+            optimize_glyph_palettes,
+            apply_operations,
+            undo,
+            redo,
+            get_history,
             resize_bitmap,
+            change_font_height,
+            transform_glyphs,
+            get_glyph_bounds,
+            trim_glyphs,
+            find_duplicate_glyphs,
             import_font_file,
+            generate_font_style,
+            scale_font,
             export_font_file,
             get_importers,
             get_exporters,
             save_png_file,
             copy_image_to_clipboard,
             copy_text_to_clipboard,
-            rendering::render_departure_board
+            rendering::render_departure_board,
+            rendering::render_document_diff,
+            recovery::get_autosave_settings,
+            recovery::set_autosave_settings,
+            recovery::list_recovery_sessions,
+            recovery::restore_recovery_session,
+            recovery::discard_recovery_session,
+            file_watch::get_disk_changes,
+            file_watch::reload_from_disk,
+            file_watch::keep_local_version,
+            file_watch::merge_disk_changes,
+            lint::get_lint_rules,
+            lint::lint_document,
+            lint::apply_lint_fixes,
+            charset::get_character_sets,
+            charset::set_custom_character_sets,
+            charset::analyze_character_coverage,
+            charset::add_missing_glyphs,
+            compose::get_composable_characters,
+            compose::preview_composed_glyphs,
+            compose::insert_composed_glyphs
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Při řádném ukončení zálohy mažeme, zůstanou jen po pádu
+            if let tauri::RunEvent::Exit = event {
+                app.state::<recovery::RecoveryState>().discard_session();
+            }
+        });
 }
@@ -0,0 +1,95 @@
+//! Tauri commands running the font linter (`gtf_core::gtf::lint`) on open documents.
+//!
+//! Without an explicit configuration the nearest `.gtflint.json` above the
+//! document file is used, so all fonts of a project share the same rules.
+
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::gtf::lint::{self, LintConfig, LintFinding, RuleInfo};
+use crate::history::{DocumentEdit, EditSummary};
+use crate::state::{AppState, DocumentId, OpenDocument};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LintReport {
+    /// Configuration file the rules came from, `None` for defaults or an explicit config.
+    pub config_path: Option<String>,
+    pub findings: Vec<LintFinding>,
+}
+
+/// Findings fixed by `apply_lint_fixes` and the glyphs the fixes changed.
+#[derive(Debug, Clone, Serialize)]
+pub struct LintFixResult {
+    pub fixed: Vec<LintFinding>,
+    pub touched_glyphs: Vec<String>,
+    pub removed_glyphs: Vec<String>,
+    pub header_changed: bool,
+}
+
+/// Explicit configuration wins, then the project file, then the defaults.
+fn resolve_config(
+    open: &OpenDocument,
+    config: Option<LintConfig>,
+) -> Result<(Option<String>, LintConfig), String> {
+    if let Some(config) = config {
+        config.check_rule_ids()?;
+        return Ok((None, config));
+    }
+    let discovered = match &open.file_path {
+        Some(path) => LintConfig::discover(Path::new(path))?,
+        None => None,
+    };
+    Ok(match discovered {
+        Some((path, config)) => (Some(path.to_string_lossy().into_owned()), config),
+        None => (None, LintConfig::default()),
+    })
+}
+
+#[tauri::command]
+pub fn get_lint_rules() -> Vec<RuleInfo> {
+    lint::RULES.to_vec()
+}
+
+#[tauri::command]
+pub fn lint_document(
+    document_id: DocumentId,
+    config: Option<LintConfig>,
+    state: tauri::State<'_, AppState>,
+) -> Result<LintReport, String> {
+    let workspace = state.workspace.lock().unwrap();
+    let open = workspace.get(document_id)?;
+    let (config_path, config) = resolve_config(open, config)?;
+    Ok(LintReport {
+        config_path,
+        findings: lint::lint_document(&open.document, &config),
+    })
+}
+
+/// Applies the automatic fixes (all rules, or only `rules`) as one undo step
+/// and returns what was fixed, so the frontend refetches only those glyphs.
+#[tauri::command]
+pub fn apply_lint_fixes(
+    document_id: DocumentId,
+    rules: Option<Vec<String>>,
+    config: Option<LintConfig>,
+    state: tauri::State<'_, AppState>,
+) -> Result<LintFixResult, String> {
+    let mut workspace = state.workspace.lock().unwrap();
+    let open = workspace.get_mut(document_id)?;
+    let (_, config) = resolve_config(open, config)?;
+
+    let mut fixed = (*open.document).clone();
+    let applied = lint::fix_document(&mut fixed, &config, rules.as_deref());
+    let mut summary = EditSummary::default();
+    if let Some(edit) = DocumentEdit::between(&open.document, &fixed) {
+        summary = edit.summary(&open.document);
+        open.apply_edit(format!("Fix {} lint finding(s)", applied.len()), edit)?;
+    }
+    Ok(LintFixResult {
+        fixed: applied,
+        touched_glyphs: summary.touched_glyphs,
+        removed_glyphs: summary.removed_glyphs,
+        header_changed: summary.header_changed,
+    })
+}
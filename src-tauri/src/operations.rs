@@ -0,0 +1,602 @@
+//! Batch operations applied atomically as a single undo step.
+//!
+//! The frontend sends a list of typed `Operation`s to `apply_operations`
+//! instead of one IPC call per change. Each operation is converted into a
+//! `DocumentEdit` against the current document and applied immediately; if
+//! any operation fails, all edits applied so far are reverted and the document
+//! is left untouched.
+
+use std::cell::OnceCell;
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::gtf;
+use crate::gtf::color::Color;
+use crate::gtf::resize::{self, Anchor};
+use crate::gtf::transform::Transform;
+use crate::gtf::types::{Glyph, GtfDocument, Size};
+use crate::history::{DocumentEdit, PixelChange};
+use crate::state::OpenDocument;
+
+/// One change requested by the frontend.
+///
+/// Glyphs are referenced by name; operations are applied in order, so a glyph
+/// renamed by an earlier operation is addressed by its new name afterwards.
+/// Palette operations without `glyph_name` target the header `DEFAULT_PALETTE`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Operation {
+    SetPixel {
+        glyph_name: String,
+        row: usize,
+        col: usize,
+        value: char,
+    },
+    SetField {
+        glyph_name: String,
+        field: String,
+        value: serde_json::Value,
+    },
+    /// Pads with the glyph's background character; top-left anchored by default.
+    Resize {
+        glyph_name: String,
+        size: Size,
+        #[serde(default)]
+        anchor: Anchor,
+    },
+    Transform {
+        glyph_name: String,
+        transform: Transform,
+    },
+    /// Crops to the ink bounding box and records the removed border in `OFFSET`
+    /// and the cell size in `CELL`. The first trim also sets the `BACKGROUND` role.
+    Trim {
+        glyph_name: String,
+    },
+    AddGlyph {
+        glyph: Glyph,
+    },
+    RemoveGlyph {
+        glyph_name: String,
+    },
+    SetPaletteEntry {
+        glyph_name: Option<String>,
+        char: char,
+        color: Color,
+    },
+    RemovePaletteEntry {
+        glyph_name: Option<String>,
+        char: char,
+    },
+}
+
+impl Operation {
+    fn kind(&self) -> &'static str {
+        match self {
+            Operation::SetPixel { .. } => "set_pixel",
+            Operation::SetField { .. } => "set_field",
+            Operation::Resize { .. } => "resize",
+            Operation::Transform { .. } => "transform",
+            Operation::Trim { .. } => "trim",
+            Operation::AddGlyph { .. } => "add_glyph",
+            Operation::RemoveGlyph { .. } => "remove_glyph",
+            Operation::SetPaletteEntry { .. } => "set_palette_entry",
+            Operation::RemovePaletteEntry { .. } => "remove_palette_entry",
+        }
+    }
+}
+
+/// Summary of an applied batch so the frontend can refresh only what changed.
+#[derive(Debug, Clone, Serialize)]
+pub struct OperationsResult {
+    pub applied: usize,
+    pub touched_glyphs: Vec<String>,
+    pub removed_glyphs: Vec<String>,
+    pub header_changed: bool,
+}
+
+/// Updates a single metadata field of a glyph (`name`, `unicode`, `char_repr`).
+pub fn set_glyph_field(
+    glyph: &mut Glyph,
+    field: &str,
+    value: &serde_json::Value,
+) -> Result<(), String> {
+    match field {
+        "name" => {
+            let name = value.as_str().ok_or("Name must be a string")?;
+            glyph.name = name.to_string();
+        }
+        "unicode" => {
+            glyph.unicode = value
+                .as_str()
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string());
+        }
+        "char_repr" => {
+            let s = value.as_str().unwrap_or("");
+            glyph.char_repr = s.chars().next();
+        }
+        _ => return Err(format!("Field '{}' not supported for atomic update", field)),
+    }
+    Ok(())
+}
+
+/// Checks that a glyph may be named `name`: not empty, no spaces and not used
+/// by another glyph. `current` is the glyph's present name when renaming.
+pub fn validate_glyph_name(
+    doc: &GtfDocument,
+    current: Option<&str>,
+    name: &str,
+) -> Result<(), String> {
+    if current == Some(name) {
+        return Ok(());
+    }
+    if name.trim().is_empty() || name.contains(' ') {
+        return Err(format!("Invalid glyph name '{}'", name));
+    }
+    if doc.glyphs.iter().any(|g| g.name == name) {
+        return Err(format!("Glyph '{}' already exists", name));
+    }
+    Ok(())
+}
+
+/// Validates and applies all operations as one undo step labelled `label`.
+/// On error nothing is changed and the message names the failing operation.
+pub fn apply_operations(
+    open: &mut OpenDocument,
+    operations: Vec<Operation>,
+    label: Option<String>,
+) -> Result<OperationsResult, String> {
+    let mut edits: Vec<DocumentEdit> = Vec::with_capacity(operations.len());
+    let mut touched: BTreeSet<String> = BTreeSet::new();
+    let mut removed: BTreeSet<String> = BTreeSet::new();
+    let mut header_changed = false;
+    // Font background for resize / trim, looked up on first use and again
+    // after an operation that can change it (default palette, BACKGROUND role)
+    let mut background = OnceCell::new();
+
+    for (i, op) in operations.iter().enumerate() {
+        let step = build_edit(&open.document, op, &background).and_then(|edit| {
+            edit.apply(open.document_mut())?;
+            Ok(edit)
+        });
+
+        match step {
+            Ok(edit) => {
+                match op {
+                    Operation::RemoveGlyph { glyph_name } => {
+                        touched.remove(glyph_name);
+                        removed.insert(glyph_name.clone());
+                    }
+                    Operation::AddGlyph { glyph } => {
+                        removed.remove(&glyph.name);
+                        touched.insert(glyph.name.clone());
+                    }
+                    Operation::SetField {
+                        glyph_name,
+                        field,
+                        value,
+                    } if field == "name" => {
+                        touched.remove(glyph_name);
+                        removed.insert(glyph_name.clone());
+                        if let Some(new_name) = value.as_str() {
+                            removed.remove(new_name);
+                            touched.insert(new_name.to_string());
+                        }
+                    }
+                    Operation::SetPixel { glyph_name, .. }
+                    | Operation::SetField { glyph_name, .. }
+                    | Operation::Resize { glyph_name, .. }
+                    | Operation::Transform { glyph_name, .. } => {
+                        touched.insert(glyph_name.clone());
+                    }
+                    Operation::Trim { glyph_name } => {
+                        touched.insert(glyph_name.clone());
+                        // The first trim also sets the BACKGROUND role
+                        if matches!(edit, DocumentEdit::Compound(_)) {
+                            header_changed = true;
+                            background.take();
+                        }
+                    }
+                    Operation::SetPaletteEntry { glyph_name, .. }
+                    | Operation::RemovePaletteEntry { glyph_name, .. } => match glyph_name {
+                        Some(name) => {
+                            touched.insert(name.clone());
+                        }
+                        None => {
+                            header_changed = true;
+                            background.take();
+                        }
+                    },
+                }
+                edits.push(edit);
+            }
+            Err(err) => {
+                // Roll back everything applied so far, newest first
+                for edit in edits.iter().rev() {
+                    edit.revert(open.document_mut())?;
+                }
+                return Err(format!("Operation {} ({}): {}", i + 1, op.kind(), err));
+            }
+        }
+    }
+
+    let applied = edits.len();
+    if applied > 0 {
+        let label = label.unwrap_or_else(|| format!("{} operation(s)", applied));
+        open.history
+            .push_applied(label, DocumentEdit::Compound(edits));
+        open.is_dirty = true;
+    }
+
+    Ok(OperationsResult {
+        applied,
+        touched_glyphs: touched.into_iter().collect(),
+        removed_glyphs: removed.into_iter().collect(),
+        header_changed,
+    })
+}
+
+fn find_glyph(doc: &GtfDocument, name: &str) -> Result<usize, String> {
+    doc.glyphs
+        .iter()
+        .position(|g| g.name == name)
+        .ok_or_else(|| format!("Glyph '{}' not found", name))
+}
+
+/// Converts an operation into a reversible edit, validating it against `doc`.
+fn build_edit(
+    doc: &GtfDocument,
+    op: &Operation,
+    background: &OnceCell<Option<Color>>,
+) -> Result<DocumentEdit, String> {
+    let background = || *background.get_or_init(|| gtf::palette::background_color(doc));
+    let glyph_fill = |glyph: &Glyph| {
+        background()
+            .and_then(|background| gtf::palette::background_char(glyph, doc, background))
+            .unwrap_or(resize::DEFAULT_FILL)
+    };
+    match op {
+        Operation::SetPixel {
+            glyph_name,
+            row,
+            col,
+            value,
+        } => {
+            let index = find_glyph(doc, glyph_name)?;
+            let before = doc.glyphs[index]
+                .bitmap
+                .get(*row)
+                .and_then(|r| r.chars().nth(*col))
+                .ok_or("Pixel coordinates out of bounds")?;
+            Ok(DocumentEdit::SetPixels {
+                index,
+                changes: vec![PixelChange {
+                    row: *row,
+                    col: *col,
+                    before,
+                    after: *value,
+                }],
+            })
+        }
+        Operation::SetField {
+            glyph_name,
+            field,
+            value,
+        } => {
+            let index = find_glyph(doc, glyph_name)?;
+            let before = doc.glyphs[index].clone();
+            let mut after = before.clone();
+            set_glyph_field(&mut after, field, value)?;
+            validate_glyph_name(doc, Some(&before.name), &after.name)?;
+            Ok(DocumentEdit::ReplaceGlyph {
+                index,
+                before,
+                after,
+            })
+        }
+        Operation::Resize {
+            glyph_name,
+            size,
+            anchor,
+        } => {
+            let index = find_glyph(doc, glyph_name)?;
+            let before = doc.glyphs[index].clone();
+            let old_size = before.size.clone().unwrap_or(Size {
+                width: before
+                    .bitmap
+                    .iter()
+                    .map(|r| r.chars().count())
+                    .max()
+                    .unwrap_or(0) as u32,
+                height: before.bitmap.len() as u32,
+            });
+            let fill = glyph_fill(&before);
+            let mut after = before.clone();
+            after.bitmap =
+                resize::resize_bitmap_anchored(&before.bitmap, &old_size, size, *anchor, fill)?;
+            after.size = Some(size.clone());
+            Ok(DocumentEdit::ReplaceGlyph {
+                index,
+                before,
+                after,
+            })
+        }
+        Operation::Transform {
+            glyph_name,
+            transform,
+        } => {
+            let index = find_glyph(doc, glyph_name)?;
+            let before = doc.glyphs[index].clone();
+            let after = gtf::transform::transform_glyph(&before, transform, glyph_fill(&before))?;
+            Ok(DocumentEdit::ReplaceGlyph {
+                index,
+                before,
+                after,
+            })
+        }
+        Operation::Trim { glyph_name } => {
+            let index = find_glyph(doc, glyph_name)?;
+            let before = doc.glyphs[index].clone();
+            let after = gtf::bounds::trim_glyph(&before, doc, background())
+                .ok_or("Nothing to trim (no blank border or no ink)")?;
+            let trim = DocumentEdit::ReplaceGlyph {
+                index,
+                before,
+                after,
+            };
+            // Trimmed pixels stop counting towards background detection
+            Ok(match gtf::bounds::background_role_for_trim(doc) {
+                Some(ch) => {
+                    let mut header = doc.header.clone();
+                    header.roles.background = Some(ch);
+                    DocumentEdit::Compound(vec![
+                        DocumentEdit::ReplaceHeader {
+                            before: doc.header.clone(),
+                            after: header,
+                        },
+                        trim,
+                    ])
+                }
+                None => trim,
+            })
+        }
+        Operation::AddGlyph { glyph } => {
+            validate_glyph_name(doc, None, &glyph.name)?;
+            Ok(DocumentEdit::InsertGlyph {
+                index: doc.glyphs.len(),
+                glyph: glyph.clone(),
+            })
+        }
+        Operation::RemoveGlyph { glyph_name } => {
+            let index = find_glyph(doc, glyph_name)?;
+            Ok(DocumentEdit::RemoveGlyph {
+                index,
+                glyph: doc.glyphs[index].clone(),
+            })
+        }
+        Operation::SetPaletteEntry {
+            glyph_name,
+            char,
+            color,
+        } => {
+            if char.is_whitespace() {
+                return Err("Palette character must not be whitespace".to_string());
+            }
+            palette_edit(doc, glyph_name.as_deref(), |entries| {
+                entries.insert(*char, *color);
+                Ok(())
+            })
+        }
+        Operation::RemovePaletteEntry { glyph_name, char } => {
+            palette_edit(doc, glyph_name.as_deref(), |entries| {
+                entries
+                    .remove(char)
+                    .map(|_| ())
+                    .ok_or_else(|| format!("Palette has no entry for '{}'", char))
+            })
+        }
+    }
+}
+
+/// Builds an edit that changes either a glyph palette or the default palette.
+fn palette_edit(
+    doc: &GtfDocument,
+    glyph_name: Option<&str>,
+    change: impl FnOnce(&mut std::collections::HashMap<char, Color>) -> Result<(), String>,
+) -> Result<DocumentEdit, String> {
+    match glyph_name {
+        Some(name) => {
+            let index = find_glyph(doc, name)?;
+            let before = doc.glyphs[index].clone();
+            let mut after = before.clone();
+            change(&mut after.palette.get_or_insert_with(Default::default).entries)?;
+            Ok(DocumentEdit::ReplaceGlyph {
+                index,
+                before,
+                after,
+            })
+        }
+        None => {
+            let before = doc.header.clone();
+            let mut after = before.clone();
+            change(
+                &mut after
+                    .default_palette
+                    .get_or_insert_with(Default::default)
+                    .entries,
+            )?;
+            Ok(DocumentEdit::ReplaceHeader { before, after })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn open_document() -> OpenDocument {
+        let glyph = |name: &str| Glyph {
+            name: name.to_string(),
+            size: Some(Size {
+                width: 2,
+                height: 2,
+            }),
+            bitmap: vec!["..".to_string(), "..".to_string()],
+            ..Default::default()
+        };
+        OpenDocument::new(
+            GtfDocument {
+                glyphs: vec![glyph("A"), glyph("B")],
+                ..Default::default()
+            },
+            None,
+            false,
+        )
+    }
+
+    fn operations(value: serde_json::Value) -> Vec<Operation> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn batch_is_one_undo_step() {
+        let mut open = open_document();
+        let original = (*open.document).clone();
+        let ops = operations(json!([
+            { "op": "set_pixel", "glyph_name": "A", "row": 0, "col": 0, "value": "#" },
+            { "op": "set_field", "glyph_name": "A", "field": "name", "value": "Alpha" },
+            { "op": "set_pixel", "glyph_name": "Alpha", "row": 1, "col": 1, "value": "#" },
+            { "op": "remove_glyph", "glyph_name": "B" },
+        ]));
+
+        let result = apply_operations(&mut open, ops, None).unwrap();
+        assert_eq!(result.applied, 4);
+        assert_eq!(result.touched_glyphs, vec!["Alpha"]);
+        assert_eq!(result.removed_glyphs, vec!["A", "B"]);
+        assert_eq!(open.document.glyphs.len(), 1);
+        assert_eq!(open.document.glyphs[0].bitmap, vec!["#.", ".#"]);
+        assert!(open.is_dirty);
+
+        assert_eq!(open.history.info().undo.len(), 1);
+        open.undo().unwrap();
+        assert_eq!(*open.document, original);
+    }
+
+    #[test]
+    fn failing_operation_leaves_document_untouched() {
+        let mut open = open_document();
+        let original = (*open.document).clone();
+        let ops = operations(json!([
+            { "op": "set_pixel", "glyph_name": "A", "row": 0, "col": 0, "value": "#" },
+            { "op": "set_pixel", "glyph_name": "A", "row": 9, "col": 0, "value": "#" },
+        ]));
+
+        let err = apply_operations(&mut open, ops, None).unwrap_err();
+        assert!(err.starts_with("Operation 2 (set_pixel)"), "{}", err);
+        assert_eq!(*open.document, original);
+        assert!(open.history.info().undo.is_empty());
+        assert!(!open.is_dirty);
+    }
+
+    #[test]
+    fn renames_are_validated() {
+        let mut open = open_document();
+        for name in ["B", "", "A B"] {
+            let ops = operations(json!([
+                { "op": "set_field", "glyph_name": "A", "field": "name", "value": name },
+            ]));
+            assert!(
+                apply_operations(&mut open, ops, None).is_err(),
+                "{:?}",
+                name
+            );
+        }
+        assert!(validate_glyph_name(&open.document, Some("A"), "A").is_ok());
+        assert!(validate_glyph_name(&open.document, None, "A").is_err());
+    }
+
+    #[test]
+    fn palette_operations_target_glyph_or_default_palette() {
+        let mut open = open_document();
+        let ops = operations(json!([
+            { "op": "set_palette_entry", "glyph_name": null, "char": "#", "color": "#FFFFFF" },
+            { "op": "set_palette_entry", "glyph_name": "B", "char": "#", "color": "#FF0000" },
+        ]));
+
+        let result = apply_operations(&mut open, ops, Some("Colors".to_string())).unwrap();
+        assert!(result.header_changed);
+        assert_eq!(result.touched_glyphs, vec!["B"]);
+        let default = open.document.header.default_palette.as_ref().unwrap();
+        assert_eq!(
+            default.entries[&'#'],
+            Color::from_rgba([255, 255, 255, 255])
+        );
+        let glyph = open.document.glyphs[1].palette.as_ref().unwrap();
+        assert_eq!(glyph.entries[&'#'], Color::from_rgba([255, 0, 0, 255]));
+        assert_eq!(open.history.info().undo[0].label, "Colors");
+    }
+
+    #[test]
+    fn first_trim_pins_the_background() {
+        let mut open = open_document();
+        let (doc, _) = open.parts_mut();
+        doc.glyphs[0].bitmap = vec!["#.".to_string(), "..".to_string()];
+        doc.glyphs[1].bitmap = vec!["..".to_string(), ".#".to_string()];
+        doc.header.default_palette = Some(gtf::types::Palette {
+            entries: [
+                ('.', Color::from_rgba([0, 0, 0, 255])),
+                ('#', Color::from_rgba([255, 255, 255, 255])),
+            ]
+            .into(),
+        });
+        let original = (*open.document).clone();
+        let ops = operations(json!([
+            { "op": "trim", "glyph_name": "A" },
+            { "op": "trim", "glyph_name": "B" },
+        ]));
+
+        let result = apply_operations(&mut open, ops, None).unwrap();
+        assert!(result.header_changed);
+        assert_eq!(open.document.header.roles.background, Some('.'));
+        let b = &open.document.glyphs[1];
+        assert_eq!(b.bitmap, vec!["#"]);
+        assert_eq!(b.offset, Some(gtf::types::Offset { x: 1, y: 1 }));
+        assert_eq!(gtf::bounds::cell_bitmap(b, '.'), original.glyphs[1].bitmap);
+
+        open.undo().unwrap();
+        assert_eq!(*open.document, original);
+
+        let ops = operations(json!([{ "op": "trim", "glyph_name": "A" }]));
+        open.redo().unwrap();
+        let err = apply_operations(&mut open, ops, None).unwrap_err();
+        assert!(err.contains("Nothing to trim"), "{}", err);
+    }
+
+    #[test]
+    fn default_palette_changes_refresh_the_background() {
+        let mut open = open_document();
+        let (doc, _) = open.parts_mut();
+        doc.glyphs[1].bitmap = vec![",#".to_string(), ",,".to_string()];
+        doc.header.default_palette = Some(gtf::types::Palette {
+            entries: [
+                ('.', Color::from_rgba([0, 0, 0, 255])),
+                (',', Color::from_rgba([255, 0, 0, 255])),
+                ('#', Color::from_rgba([255, 255, 255, 255])),
+            ]
+            .into(),
+        });
+        // '.' turns white, so the white '#' becomes the background of B
+        let ops = operations(json!([
+            { "op": "resize", "glyph_name": "A", "size": { "width": 3, "height": 2 } },
+            { "op": "set_palette_entry", "glyph_name": null, "char": ".", "color": "#FFFFFF" },
+            { "op": "resize", "glyph_name": "B", "size": { "width": 3, "height": 2 } },
+        ]));
+
+        apply_operations(&mut open, ops, None).unwrap();
+        assert_eq!(open.document.glyphs[0].bitmap, vec!["...", "..."]);
+        assert_eq!(open.document.glyphs[1].bitmap, vec![",##", ",,#"]);
+    }
+}
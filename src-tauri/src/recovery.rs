@@ -0,0 +1,521 @@
+//! Autosave and crash recovery.
+//!
+//! While the app runs, a background thread periodically writes every dirty
+//! document together with its undo journal into
+//! `<app data>/recovery/<session id>/<document id>.json`. Snapshots of saved
+//! or closed documents are removed and the session directory is deleted on a
+//! clean exit, so every session directory found at startup belongs to a run
+//! that did not finish properly and can be restored.
+//!
+//! A document is only snapshotted when its revision changed since the last
+//! snapshot. Under the workspace lock the autosave merely takes `Arc` copies
+//! of the document and journal; serializing and writing happen after the lock
+//! is released, and the finished file is only moved into place if the
+//! document is still open and dirty.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::gtf::types::GtfDocument;
+use crate::history::{History, HistoryJournal, DEFAULT_HISTORY_BUDGET};
+use crate::state::{AppState, DocumentId, DocumentOverview, OpenDocument, Workspace};
+use crate::storage::FileStamp;
+
+pub const DEFAULT_AUTOSAVE_INTERVAL_SECS: u64 = 30;
+const MIN_AUTOSAVE_INTERVAL_SECS: u64 = 5;
+
+/// How often the autosave thread wakes up to check the interval and settings.
+const TICK: Duration = Duration::from_secs(1);
+
+/// Bumped whenever `RecoverySnapshot` changes incompatibly.
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// User-configurable autosave behaviour.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutosaveSettings {
+    pub enabled: bool,
+    pub interval_secs: u64,
+}
+
+impl Default for AutosaveSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: DEFAULT_AUTOSAVE_INTERVAL_SECS,
+        }
+    }
+}
+
+/// Recovery directory of the running session plus autosave settings.
+pub struct RecoveryState {
+    session_id: String,
+    root: Mutex<Option<PathBuf>>,
+    settings: Mutex<AutosaveSettings>,
+    /// Document revision stored in each snapshot on disk.
+    written: Mutex<HashMap<DocumentId, u64>>,
+}
+
+impl RecoveryState {
+    pub fn new() -> Self {
+        let started = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        Self {
+            session_id: format!("{}-{}", started, std::process::id()),
+            root: Mutex::new(None),
+            settings: Mutex::new(AutosaveSettings::default()),
+            written: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sets the directory holding all recovery sessions (called once from `setup`).
+    pub fn init(&self, root: PathBuf) {
+        *self.root.lock().unwrap() = Some(root);
+    }
+
+    fn root(&self) -> Result<PathBuf, String> {
+        self.root
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or_else(|| "Recovery directory is not initialized".to_string())
+    }
+
+    fn session_dir(&self) -> Result<PathBuf, String> {
+        Ok(self.root()?.join(&self.session_id))
+    }
+
+    /// Removes the snapshot of one document, e.g. after it was saved or closed.
+    /// Callers hold the workspace lock, so a snapshot being written concurrently
+    /// sees the new state before it is moved into place.
+    pub fn discard_document(&self, document_id: DocumentId) {
+        self.written.lock().unwrap().remove(&document_id);
+        if let Ok(dir) = self.session_dir() {
+            let _ = fs::remove_file(snapshot_path(&dir, document_id));
+        }
+    }
+
+    /// Removes the whole directory of the running session (clean exit).
+    pub fn discard_session(&self) {
+        if let Ok(dir) = self.session_dir() {
+            let _ = fs::remove_dir_all(dir);
+        }
+    }
+
+    /// Writes snapshots of dirty documents changed since their last snapshot and
+    /// drops snapshots of the rest. Returns the number of written snapshots.
+    pub fn autosave(&self, workspace: &Mutex<Workspace>) -> Result<usize, String> {
+        let dir = self.session_dir()?;
+
+        // Only cheap `Arc` copies under the lock, serialization happens after releasing it
+        let (changed, clean) = {
+            let workspace = workspace.lock().unwrap();
+            let written = self.written.lock().unwrap();
+            let mut changed = Vec::new();
+            let mut clean = Vec::new();
+            for (id, open) in workspace.iter() {
+                if !open.is_dirty {
+                    clean.push(id);
+                } else if written.get(&id) != Some(&open.revision()) {
+                    let snapshot = RecoverySnapshot {
+                        version: SNAPSHOT_VERSION,
+                        saved_at: unix_now(),
+                        file_path: open.file_path.clone(),
+                        disk_stamp: open.disk_stamp.clone(),
+                        document: Arc::clone(&open.document),
+                        journal: open.history.journal(),
+                    };
+                    changed.push((id, open.revision(), snapshot));
+                }
+            }
+            (changed, clean)
+        };
+
+        for id in clean {
+            if self.written.lock().unwrap().remove(&id).is_some() {
+                let _ = fs::remove_file(snapshot_path(&dir, id));
+            }
+        }
+        if changed.is_empty() {
+            return Ok(0);
+        }
+
+        fs::create_dir_all(&dir).map_err(|e| {
+            format!(
+                "Failed to create recovery directory '{}': {}",
+                dir.display(),
+                e
+            )
+        })?;
+        let mut count = 0;
+        for (id, revision, snapshot) in changed {
+            let json = serde_json::to_string(&snapshot)
+                .map_err(|e| format!("Failed to serialize recovery snapshot: {}", e))?;
+            drop(snapshot);
+
+            // Write to a temporary file first so a crash never leaves a half-written snapshot
+            let path = snapshot_path(&dir, id);
+            let tmp = path.with_extension("json.tmp");
+            fs::write(&tmp, json)
+                .map_err(|e| format!("Failed to write '{}': {}", tmp.display(), e))?;
+
+            // Saved or closed in the meantime: the snapshot must not reappear
+            let workspace = workspace.lock().unwrap();
+            if !workspace.get(id).is_ok_and(|open| open.is_dirty) {
+                let _ = fs::remove_file(&tmp);
+                continue;
+            }
+            fs::rename(&tmp, &path)
+                .map_err(|e| format!("Failed to write '{}': {}", path.display(), e))?;
+            self.written.lock().unwrap().insert(id, revision);
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Lists sessions left behind by previous runs, newest first.
+    pub fn list_sessions(&self) -> Result<Vec<RecoverableSession>, String> {
+        let root = self.root()?;
+        let Ok(entries) = fs::read_dir(&root) else {
+            return Ok(Vec::new());
+        };
+
+        let mut sessions = Vec::new();
+        for entry in entries.flatten() {
+            let session_id = entry.file_name().to_string_lossy().to_string();
+            if session_id == self.session_id || !entry.path().is_dir() {
+                continue;
+            }
+            let documents: Vec<RecoverableDocument> = read_snapshots(&entry.path())
+                .into_iter()
+                .map(|(id, snapshot)| RecoverableDocument {
+                    document_id: id,
+                    file_path: snapshot.file_path,
+                    font_name: snapshot.document.header.font_name.clone(),
+                    glyph_count: snapshot.document.glyphs.len(),
+                    undo_steps: snapshot.journal.undo.len(),
+                    saved_at: snapshot.saved_at,
+                })
+                .collect();
+            if documents.is_empty() {
+                continue;
+            }
+            sessions.push(RecoverableSession {
+                saved_at: documents.iter().map(|d| d.saved_at).max().unwrap_or(0),
+                session_id,
+                documents,
+            });
+        }
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.saved_at));
+        Ok(sessions)
+    }
+
+    fn crashed_session_dir(&self, session_id: &str) -> Result<PathBuf, String> {
+        let valid = !session_id.is_empty()
+            && session_id.chars().all(|c| c.is_ascii_digit() || c == '-')
+            && session_id != self.session_id;
+        if !valid {
+            return Err(format!("Invalid recovery session '{}'", session_id));
+        }
+        let dir = self.root()?.join(session_id);
+        if !dir.is_dir() {
+            return Err(format!("Recovery session '{}' not found", session_id));
+        }
+        Ok(dir)
+    }
+}
+
+impl Default for RecoveryState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What the autosave writes for a single document.
+#[derive(Serialize, Deserialize)]
+struct RecoverySnapshot {
+    version: u32,
+    saved_at: u64,
+    file_path: Option<String>,
+    /// The file as it was loaded, so saving a restored document still detects external changes.
+    #[serde(default)]
+    disk_stamp: Option<FileStamp>,
+    document: Arc<GtfDocument>,
+    journal: HistoryJournal,
+}
+
+/// One document that can be restored from a crashed session.
+#[derive(Serialize)]
+pub struct RecoverableDocument {
+    /// Id the document had in the crashed session.
+    pub document_id: DocumentId,
+    pub file_path: Option<String>,
+    pub font_name: Option<String>,
+    pub glyph_count: usize,
+    pub undo_steps: usize,
+    /// Seconds since the Unix epoch.
+    pub saved_at: u64,
+}
+
+#[derive(Serialize)]
+pub struct RecoverableSession {
+    pub session_id: String,
+    pub saved_at: u64,
+    pub documents: Vec<RecoverableDocument>,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn snapshot_path(dir: &Path, document_id: DocumentId) -> PathBuf {
+    dir.join(format!("{}.json", document_id))
+}
+
+/// Reads all valid snapshots of a session directory, ordered by document id.
+/// Unreadable or incompatible files are skipped.
+fn read_snapshots(dir: &Path) -> Vec<(DocumentId, RecoverySnapshot)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut snapshots: Vec<(DocumentId, RecoverySnapshot)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                return None;
+            }
+            let id = path.file_stem()?.to_str()?.parse().ok()?;
+            let content = fs::read_to_string(&path).ok()?;
+            let snapshot: RecoverySnapshot = serde_json::from_str(&content).ok()?;
+            (snapshot.version == SNAPSHOT_VERSION).then_some((id, snapshot))
+        })
+        .collect();
+    snapshots.sort_by_key(|(id, _)| *id);
+    snapshots
+}
+
+/// Starts the background thread that autosaves dirty documents.
+pub fn spawn_autosave(app: AppHandle) {
+    thread::spawn(move || {
+        let mut last_run = Instant::now();
+        loop {
+            thread::sleep(TICK);
+            let recovery = app.state::<RecoveryState>();
+            let settings = recovery.settings.lock().unwrap().clone();
+            if !settings.enabled || last_run.elapsed() < Duration::from_secs(settings.interval_secs)
+            {
+                continue;
+            }
+            last_run = Instant::now();
+            if let Err(err) = recovery.autosave(&app.state::<AppState>().workspace) {
+                eprintln!("Autosave failed: {}", err);
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_autosave_settings(
+    recovery: tauri::State<'_, RecoveryState>,
+) -> Result<AutosaveSettings, String> {
+    Ok(recovery.settings.lock().unwrap().clone())
+}
+
+#[tauri::command]
+pub fn set_autosave_settings(
+    settings: AutosaveSettings,
+    recovery: tauri::State<'_, RecoveryState>,
+) -> Result<(), String> {
+    if settings.interval_secs < MIN_AUTOSAVE_INTERVAL_SECS {
+        return Err(format!(
+            "Autosave interval must be at least {} seconds",
+            MIN_AUTOSAVE_INTERVAL_SECS
+        ));
+    }
+    *recovery.settings.lock().unwrap() = settings;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_recovery_sessions(
+    recovery: tauri::State<'_, RecoveryState>,
+) -> Result<Vec<RecoverableSession>, String> {
+    recovery.list_sessions()
+}
+
+/// Opens all documents of a crashed session (as unsaved, with their original
+/// `file_path` and undo history) and removes the session from disk.
+#[tauri::command]
+pub fn restore_recovery_session(
+    session_id: String,
+    state: tauri::State<'_, AppState>,
+    recovery: tauri::State<'_, RecoveryState>,
+) -> Result<Vec<DocumentOverview>, String> {
+    let dir = recovery.crashed_session_dir(&session_id)?;
+    let snapshots = read_snapshots(&dir);
+    if snapshots.is_empty() {
+        return Err(format!(
+            "Recovery session '{}' contains no readable documents",
+            session_id
+        ));
+    }
+
+    let mut workspace = state.workspace.lock().unwrap();
+    let restored = snapshots
+        .into_iter()
+        .map(|(_, snapshot)| {
+            let document = Arc::unwrap_or_clone(snapshot.document);
+            let mut open = OpenDocument::new(document, snapshot.file_path, true);
+            open.history = History::from_journal(snapshot.journal, DEFAULT_HISTORY_BUDGET);
+            open.disk_stamp = snapshot.disk_stamp;
+            let document_id = workspace.open(open);
+            workspace
+                .get(document_id)
+                .map(|open| open.overview(document_id))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    drop(workspace);
+
+    let _ = fs::remove_dir_all(&dir);
+    Ok(restored)
+}
+
+#[tauri::command]
+pub fn discard_recovery_session(
+    session_id: String,
+    recovery: tauri::State<'_, RecoveryState>,
+) -> Result<(), String> {
+    let dir = recovery.crashed_session_dir(&session_id)?;
+    fs::remove_dir_all(&dir)
+        .map_err(|e| format!("Failed to remove recovery session '{}': {}", session_id, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gtf::types::Glyph;
+    use crate::history::DocumentEdit;
+
+    fn recovery_root(name: &str) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("gtf-recovery-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        root
+    }
+
+    /// State of a later run; session ids only differ between processes otherwise.
+    fn next_run(root: PathBuf) -> RecoveryState {
+        let recovery = RecoveryState {
+            session_id: "0-0".to_string(),
+            ..RecoveryState::new()
+        };
+        recovery.init(root);
+        recovery
+    }
+
+    fn dirty_document() -> OpenDocument {
+        let mut open = OpenDocument::new(GtfDocument::default(), None, false);
+        add_glyph(&mut open, "A");
+        open
+    }
+
+    fn add_glyph(open: &mut OpenDocument, name: &str) {
+        let edit = DocumentEdit::InsertGlyph {
+            index: open.document.glyphs.len(),
+            glyph: Glyph {
+                name: name.to_string(),
+                ..Default::default()
+            },
+        };
+        open.apply_edit(format!("Add {}", name), edit).unwrap();
+    }
+
+    #[test]
+    fn snapshots_only_changed_documents() {
+        let root = recovery_root("changed");
+        let recovery = RecoveryState::new();
+        recovery.init(root.clone());
+        let workspace = Mutex::new(Workspace::default());
+        let id = workspace.lock().unwrap().open(dirty_document());
+
+        assert_eq!(recovery.autosave(&workspace).unwrap(), 1);
+        assert_eq!(recovery.autosave(&workspace).unwrap(), 0);
+
+        add_glyph(workspace.lock().unwrap().get_mut(id).unwrap(), "B");
+        assert_eq!(recovery.autosave(&workspace).unwrap(), 1);
+
+        let snapshots = read_snapshots(&recovery.session_dir().unwrap());
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].1.document.glyphs.len(), 2);
+        assert_eq!(snapshots[0].1.journal.undo.len(), 2);
+
+        // Saved: the snapshot is dropped on the next run
+        workspace.lock().unwrap().get_mut(id).unwrap().is_dirty = false;
+        assert_eq!(recovery.autosave(&workspace).unwrap(), 0);
+        assert!(read_snapshots(&recovery.session_dir().unwrap()).is_empty());
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn discarded_documents_are_not_recoverable() {
+        let root = recovery_root("discarded");
+        let recovery = RecoveryState::new();
+        recovery.init(root.clone());
+        let workspace = Mutex::new(Workspace::default());
+        let id = workspace.lock().unwrap().open(dirty_document());
+        assert_eq!(recovery.autosave(&workspace).unwrap(), 1);
+
+        {
+            let mut workspace = workspace.lock().unwrap();
+            workspace.close(id).unwrap();
+            recovery.discard_document(id);
+        }
+        assert_eq!(recovery.autosave(&workspace).unwrap(), 0);
+
+        let next_run = next_run(root.clone());
+        assert!(next_run.list_sessions().unwrap().is_empty());
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn snapshot_keeps_file_path_and_disk_stamp() {
+        let root = recovery_root("stamp");
+        fs::create_dir_all(&root).unwrap();
+        let file = root.join("font.gtf");
+        fs::write(&file, "GTF v3\n").unwrap();
+        let stamp = FileStamp::read(&file).unwrap();
+
+        let recovery = RecoveryState::new();
+        recovery.init(root.join("recovery"));
+        let workspace = Mutex::new(Workspace::default());
+        let mut open = dirty_document();
+        open.file_path = Some(file.to_string_lossy().to_string());
+        open.disk_stamp = Some(stamp.clone());
+        workspace.lock().unwrap().open(open);
+        recovery.autosave(&workspace).unwrap();
+
+        let next_run = next_run(root.join("recovery"));
+        let sessions = next_run.list_sessions().unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].documents[0].glyph_count, 1);
+
+        let snapshots = read_snapshots(&recovery.session_dir().unwrap());
+        assert_eq!(snapshots[0].1.disk_stamp, Some(stamp));
+
+        let _ = fs::remove_dir_all(root);
+    }
+}
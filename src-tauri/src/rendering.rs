@@ -0,0 +1,59 @@
+//! Tauri commands rendering open documents with `gtf_core::rendering`.
+
+use crate::gtf::GtfDocument;
+use crate::state::{AppState, DocumentId};
+use base64::Engine;
+use gtf_core::rendering::{departure_board, diff_image, RenderRequest};
+use std::sync::Arc;
+
+#[tauri::command]
+pub fn render_departure_board(
+    document_id: DocumentId,
+    request: RenderRequest,
+    state: tauri::State<'_, AppState>,
+) -> Result<String, String> {
+    // 1. Získáme data - buď z požadavku, nebo snímek (`Arc`) ze stavu,
+    // aby renderování a kódování PNG neblokovalo ostatní příkazy
+    let stored: Arc<GtfDocument>;
+    let doc = match &request.gtf_data {
+        Some(d) => d,
+        None => {
+            stored = Arc::clone(
+                &state
+                    .workspace
+                    .lock()
+                    .unwrap()
+                    .get(document_id)
+                    .map_err(|e| format!("No document loaded in backend for rendering: {}", e))?
+                    .document,
+            );
+            &stored
+        }
+    };
+
+    // 2. Vyrenderujeme s použitím správných dat
+    let png_bytes = departure_board::render_board(&request, doc)?;
+    Ok(base64::engine::general_purpose::STANDARD.encode(png_bytes))
+}
+
+/// Vyrenderuje vizuální diff dvou otevřených dokumentů (staré | nové | změněné pixely) do PNG.
+#[tauri::command]
+pub fn render_document_diff(
+    old_document_id: DocumentId,
+    new_document_id: DocumentId,
+    pixel_scale: Option<u32>,
+    state: tauri::State<'_, AppState>,
+) -> Result<String, String> {
+    // Snímky dokumentů, porovnání a kódování PNG už běží bez zámku
+    let (old, new) = {
+        let workspace = state.workspace.lock().unwrap();
+        (
+            Arc::clone(&workspace.get(old_document_id)?.document),
+            Arc::clone(&workspace.get(new_document_id)?.document),
+        )
+    };
+
+    let diff = crate::gtf::diff::diff_documents(&old, &new);
+    let png_bytes = diff_image::render_diff(&old, &new, &diff, pixel_scale.unwrap_or(8))?;
+    Ok(base64::engine::general_purpose::STANDARD.encode(png_bytes))
+}
@@ -1,30 +1,264 @@
-use crate::gtf::types::GtfDocument;
+use crate::gtf::charset::CharacterSet;
+use crate::gtf::types::{GlyphSummary, GtfDocument, GtfHeader};
+use crate::history::{DocumentEdit, History};
+use crate::storage::{FileStamp, SaveSettings};
 use serde::Serialize;
-use std::sync::Mutex;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+/// Identifikátor otevřeného dokumentu ve workspace.
+pub type DocumentId = u32;
 
 /// Toto je hlavní stav naší aplikace uložený v Rustu.
 /// Používáme Mutex, protože k datům může přistupovat více vláken najednou (např. když přijde víc invoke příkazů).
-/// Option v GtfDocument znamená: Buď máme otevřený soubor (Some), nebo ne (None).
+/// Všechny otevřené dokumenty žijí ve `Workspace` pod jedním zámkem, aby šlo bezpečně kopírovat mezi nimi.
 pub struct AppState {
-    pub document: Mutex<Option<GtfDocument>>,
-    pub file_path: Mutex<Option<String>>,
-    pub is_dirty: Mutex<bool>,
+    pub workspace: Mutex<Workspace>,
+    pub save_settings: Mutex<SaveSettings>,
+    /// Uživatelem definované znakové sady (kontrola pokrytí vedle vestavěných).
+    pub custom_character_sets: Mutex<Vec<CharacterSet>>,
 }
 
 impl AppState {
     pub fn new() -> Self {
         Self {
-            document: Mutex::new(None),
-            file_path: Mutex::new(None),
-            is_dirty: Mutex::new(false),
+            workspace: Mutex::new(Workspace::default()),
+            save_settings: Mutex::new(SaveSettings::default()),
+            custom_character_sets: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+/// Jeden otevřený dokument včetně cesty, 'dirty' příznaku a historie úprav.
+pub struct OpenDocument {
+    /// Dokument je sdílený přes `Arc`, aby si autosave mohl vzít snímek bez kopírování.
+    /// Měnit ho jde jen přes `document_mut` / `parts_mut`, které zvýší `revision`.
+    pub document: Arc<GtfDocument>,
+    pub file_path: Option<String>,
+    pub is_dirty: bool,
+    pub history: History,
+    /// Jak vypadal soubor na disku při načtení/posledním uložení (kvůli detekci cizích změn).
+    pub disk_stamp: Option<FileStamp>,
+    revision: u64,
+}
+
+impl OpenDocument {
+    pub fn new(document: GtfDocument, file_path: Option<String>, is_dirty: bool) -> Self {
+        Self {
+            document: Arc::new(document),
+            file_path,
+            is_dirty,
+            history: History::default(),
+            disk_stamp: None,
+            revision: 0,
+        }
+    }
+
+    /// Počítadlo změn dokumentu – autosave podle něj pozná, že je potřeba nový snímek.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Přístup k dokumentu pro zápis. Pokud na něj drží snímek autosave, dokument se zkopíruje.
+    pub fn document_mut(&mut self) -> &mut GtfDocument {
+        self.revision += 1;
+        Arc::make_mut(&mut self.document)
+    }
+
+    /// Dokument a historie zároveň (pro úpravy, které zapisuje historie sama).
+    pub fn parts_mut(&mut self) -> (&mut GtfDocument, &mut History) {
+        self.revision += 1;
+        (Arc::make_mut(&mut self.document), &mut self.history)
+    }
+
+    /// Nahradí celý dokument (např. znovunačtení z disku).
+    pub fn replace_document(&mut self, document: GtfDocument) {
+        self.revision += 1;
+        self.document = Arc::new(document);
+    }
+
+    /// Provede úpravu přes historii (kvůli undo/redo) a označí dokument jako 'dirty'.
+    pub fn apply_edit(
+        &mut self,
+        label: impl Into<String>,
+        edit: DocumentEdit,
+    ) -> Result<(), String> {
+        let (document, history) = self.parts_mut();
+        history.apply(document, label, edit)?;
+        self.is_dirty = true;
+        Ok(())
+    }
+
+    /// Vrátí poslední krok historie. Vrací jeho popis.
+    pub fn undo(&mut self) -> Result<String, String> {
+        let (document, history) = self.parts_mut();
+        let label = history.undo(document)?;
+        self.is_dirty = true;
+        Ok(label)
+    }
+
+    /// Znovu provede naposledy vrácený krok. Vrací jeho popis.
+    pub fn redo(&mut self) -> Result<String, String> {
+        let (document, history) = self.parts_mut();
+        let label = history.redo(document)?;
+        self.is_dirty = true;
+        Ok(label)
+    }
+
+    /// Hlavička a počet glyfů pro frontend – glyfy si pak stránkuje přes `get_glyph_summaries`.
+    pub fn overview(&self, id: DocumentId) -> DocumentOverview {
+        DocumentOverview {
+            document_id: id,
+            header: self.document.header.clone(),
+            glyph_count: self.document.glyphs.len(),
+            file_path: self.file_path.clone(),
         }
     }
+
+    pub fn info(&self, id: DocumentId, is_active: bool) -> DocumentInfo {
+        DocumentInfo {
+            document_id: id,
+            font_name: self.document.header.font_name.clone(),
+            file_path: self.file_path.clone(),
+            glyph_count: self.document.glyphs.len(),
+            is_dirty: self.is_dirty,
+            is_active,
+        }
+    }
+}
+
+/// Mapa otevřených dokumentů podle id + informace, který je právě aktivní.
+#[derive(Default)]
+pub struct Workspace {
+    documents: BTreeMap<DocumentId, OpenDocument>,
+    active: Option<DocumentId>,
+    next_id: DocumentId,
+}
+
+impl Workspace {
+    /// Přidá dokument do workspace, udělá ho aktivním a vrátí jeho id.
+    pub fn open(&mut self, document: OpenDocument) -> DocumentId {
+        self.next_id += 1;
+        let id = self.next_id;
+        self.documents.insert(id, document);
+        self.active = Some(id);
+        id
+    }
+
+    pub fn get(&self, id: DocumentId) -> Result<&OpenDocument, String> {
+        self.documents
+            .get(&id)
+            .ok_or_else(|| format!("Document {} is not open", id))
+    }
+
+    pub fn get_mut(&mut self, id: DocumentId) -> Result<&mut OpenDocument, String> {
+        self.documents
+            .get_mut(&id)
+            .ok_or_else(|| format!("Document {} is not open", id))
+    }
+
+    /// Vrátí dva různé dokumenty najednou (zdroj pro čtení, cíl pro zápis).
+    pub fn pair_mut(
+        &mut self,
+        source: DocumentId,
+        target: DocumentId,
+    ) -> Result<(&OpenDocument, &mut OpenDocument), String> {
+        if source == target {
+            return Err("Source and target document must differ".to_string());
+        }
+        self.get(source)?;
+        self.get(target)?;
+
+        let mut source_doc = None;
+        let mut target_doc = None;
+        for (id, doc) in self.documents.iter_mut() {
+            if *id == source {
+                source_doc = Some(&*doc);
+            } else if *id == target {
+                target_doc = Some(doc);
+            }
+        }
+        match (source_doc, target_doc) {
+            (Some(s), Some(t)) => Ok((s, t)),
+            _ => Err("Failed to access documents".to_string()),
+        }
+    }
+
+    /// Zavře dokument. Pokud byl aktivní, aktivním se stane jiný otevřený dokument.
+    pub fn close(&mut self, id: DocumentId) -> Result<OpenDocument, String> {
+        let closed = self
+            .documents
+            .remove(&id)
+            .ok_or_else(|| format!("Document {} is not open", id))?;
+        if self.active == Some(id) {
+            self.active = self.documents.keys().next_back().copied();
+        }
+        Ok(closed)
+    }
+
+    pub fn set_active(&mut self, id: DocumentId) -> Result<(), String> {
+        self.get(id)?;
+        self.active = Some(id);
+        Ok(())
+    }
+
+    pub fn active(&self) -> Option<DocumentId> {
+        self.active
+    }
+
+    /// Prochází všechny otevřené dokumenty (např. pro autosave).
+    pub fn iter(&self) -> impl Iterator<Item = (DocumentId, &OpenDocument)> {
+        self.documents.iter().map(|(id, doc)| (*id, doc))
+    }
+
+    pub fn list(&self) -> Vec<DocumentInfo> {
+        self.documents
+            .iter()
+            .map(|(id, doc)| doc.info(*id, self.active == Some(*id)))
+            .collect()
+    }
 }
 
 /// Pomocná struktura, kterou posíláme do frontendu, abychom mu řekli, co se děje.
 #[derive(Serialize)]
 pub struct StateInfo {
+    pub document_id: DocumentId,
     pub has_document: bool,
     pub file_path: Option<String>,
     pub is_dirty: bool,
 }
+
+/// Popis otevřeného dokumentu pro seznam dokumentů (záložky) ve frontendu.
+#[derive(Serialize)]
+pub struct DocumentInfo {
+    pub document_id: DocumentId,
+    pub font_name: Option<String>,
+    pub file_path: Option<String>,
+    pub glyph_count: usize,
+    pub is_dirty: bool,
+    pub is_active: bool,
+}
+
+/// Nově otevřený dokument – id, pod kterým ho frontend dál oslovuje, a jeho obsah.
+#[derive(Serialize)]
+pub struct OpenedDocument {
+    pub document_id: DocumentId,
+    pub document: GtfDocument,
+}
+
+/// Přehled dokumentu bez bitmap – posíláme ho místo celého `GtfDocument` u velkých fontů.
+#[derive(Serialize)]
+pub struct DocumentOverview {
+    pub document_id: DocumentId,
+    pub header: GtfHeader,
+    pub glyph_count: usize,
+    pub file_path: Option<String>,
+}
+
+/// Jedna stránka souhrnů glyfů (viz `get_glyph_summaries`).
+#[derive(Serialize)]
+pub struct GlyphPage {
+    pub offset: usize,
+    pub total: usize,
+    pub glyphs: Vec<GlyphSummary>,
+}
@@ -0,0 +1,331 @@
+//! Crash-safe writing of documents to disk.
+//!
+//! `write_atomic` never writes over the target directly: the content goes to a
+//! temporary file in the same directory, is flushed with `fsync` and then
+//! renamed over the target, so the target always holds either the old or the
+//! new content. Optionally the previous version is kept in rotating backups
+//! (`font.gtf.1.bak` is the newest).
+//!
+//! `FileStamp` remembers what a file looked like when it was loaded so a save
+//! can detect that another program changed it in the meantime. It is computed
+//! by `StampingReader` from the same bytes the parser reads, so the stamp
+//! always describes the parsed version and the file is read only once.
+
+use std::fs::{self, File};
+use std::hash::Hasher;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::gtf::PaletteOutput;
+
+/// Upper limit for `SaveSettings::backup_count`.
+pub const MAX_BACKUP_COUNT: usize = 20;
+
+/// User-configurable save behaviour.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SaveSettings {
+    /// Number of rotating `.bak` copies kept next to the file (0 = none).
+    pub backup_count: usize,
+    /// Glyph palettes with only overrides (default) or fully expanded.
+    #[serde(default)]
+    pub palette_output: PaletteOutput,
+}
+
+/// 64-bit FNV-1a. Stamps are stored in recovery snapshots, so the hash must
+/// stay the same across Rust releases, which `DefaultHasher` does not promise.
+#[derive(Debug, Clone, Copy)]
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Modification time, size and content hash of a file on disk.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+    hash: u64,
+}
+
+impl FileStamp {
+    /// Reads the file and computes its stamp.
+    pub fn read(path: &Path) -> Result<Self, String> {
+        StampingReader::open(path)?.finish(path)
+    }
+
+    /// Cheap check without reading the file: same mtime and size as on disk.
+    pub fn matches_metadata(&self, path: &Path) -> bool {
+        fs::metadata(path).is_ok_and(|m| m.len() == self.len && m.modified().ok() == self.modified)
+    }
+
+    /// Same size and content hash, regardless of the modification time.
+    pub fn same_content(&self, other: &FileStamp) -> bool {
+        self.len == other.len && self.hash == other.hash
+    }
+
+    fn new(metadata: &fs::Metadata, content: &[u8]) -> Self {
+        let mut hasher = Fnv1a::default();
+        hasher.write(content);
+        Self {
+            modified: metadata.modified().ok(),
+            len: content.len() as u64,
+            hash: hasher.finish(),
+        }
+    }
+}
+
+/// Buffered file reader that hashes every byte passed through it, so a file
+/// can be parsed and stamped in one pass.
+pub struct StampingReader {
+    inner: BufReader<File>,
+    modified: Option<SystemTime>,
+    hasher: Fnv1a,
+    len: u64,
+}
+
+impl StampingReader {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let read_error =
+            |err: io::Error| format!("Failed to read file '{}': {}", path.display(), err);
+        let file = File::open(path).map_err(read_error)?;
+        let metadata = file.metadata().map_err(read_error)?;
+        Ok(Self {
+            inner: BufReader::new(file),
+            modified: metadata.modified().ok(),
+            hasher: Fnv1a::default(),
+            len: 0,
+        })
+    }
+
+    /// Reads whatever the consumer left unread and returns the stamp of the whole file.
+    pub fn finish(mut self, path: &Path) -> Result<FileStamp, String> {
+        io::copy(&mut self, &mut io::sink())
+            .map_err(|err| format!("Failed to read file '{}': {}", path.display(), err))?;
+        Ok(FileStamp {
+            modified: self.modified,
+            len: self.len,
+            hash: self.hasher.finish(),
+        })
+    }
+}
+
+impl Read for StampingReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.hasher.write(&buf[..read]);
+        self.len += read as u64;
+        Ok(read)
+    }
+}
+
+impl BufRead for StampingReader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        // The consumed bytes are the start of the current buffer, which is still filled
+        let buffer = self.inner.buffer();
+        let amt = amt.min(buffer.len());
+        self.hasher.write(&buffer[..amt]);
+        self.len += amt as u64;
+        self.inner.consume(amt);
+    }
+}
+
+/// Fails if the file at `path` differs from `expected`.
+///
+/// Matching mtime and size are trusted without reading the file. Otherwise the
+/// content hash decides, so a `touch` or a sync tool rewriting identical
+/// content is not reported as a conflict. A file that no longer exists is not
+/// a conflict either.
+pub fn ensure_unchanged(path: &Path, expected: &FileStamp) -> Result<(), String> {
+    if !path.exists() || expected.matches_metadata(path) {
+        return Ok(());
+    }
+
+    let current = FileStamp::read(path)?;
+    if current.same_content(expected) {
+        Ok(())
+    } else {
+        Err(format!(
+            "File '{}' was modified on disk since it was loaded",
+            path.display()
+        ))
+    }
+}
+
+/// Path of the `index`-th backup of `path` (1 = newest).
+fn backup_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}.bak", index));
+    PathBuf::from(name)
+}
+
+/// Writes `content` to `path` via a temporary file and rename, keeping up to
+/// `backup_count` previous versions. Returns the stamp of the written file.
+pub fn write_atomic(path: &Path, content: &[u8], backup_count: usize) -> Result<FileStamp, String> {
+    let write_error =
+        |err: io::Error| format!("Failed to write file '{}': {}", path.display(), err);
+
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| format!("Invalid file path '{}'", path.display()))?;
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let tmp_path = dir.join(format!(
+        ".{}.{}.tmp",
+        file_name.to_string_lossy(),
+        std::process::id()
+    ));
+
+    let result = write_synced(&tmp_path, content)
+        .and_then(|_| {
+            // The new file replaces the old one, so it inherits its permissions
+            match fs::metadata(path) {
+                Ok(metadata) => fs::set_permissions(&tmp_path, metadata.permissions()),
+                Err(_) => Ok(()),
+            }
+        })
+        .and_then(|_| {
+            if backup_count > 0 && path.exists() {
+                rotate_backups(path, backup_count)
+            } else {
+                Ok(())
+            }
+        })
+        .and_then(|_| fs::rename(&tmp_path, path));
+    if let Err(err) = result {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(write_error(err));
+    }
+    sync_dir(dir);
+
+    let metadata = fs::metadata(path).map_err(write_error)?;
+    Ok(FileStamp::new(&metadata, content))
+}
+
+fn write_synced(path: &Path, content: &[u8]) -> io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(content)?;
+    file.sync_all()
+}
+
+/// Shifts `.1.bak` → `.2.bak` → … (dropping the oldest) and copies the current
+/// file to `.1.bak`. The original stays in place until the rename.
+fn rotate_backups(path: &Path, backup_count: usize) -> io::Result<()> {
+    let oldest = backup_path(path, backup_count);
+    if oldest.exists() {
+        fs::remove_file(&oldest)?;
+    }
+    for index in (1..backup_count).rev() {
+        let from = backup_path(path, index);
+        if from.exists() {
+            fs::rename(&from, backup_path(path, index + 1))?;
+        }
+    }
+    fs::copy(path, backup_path(path, 1))?;
+    Ok(())
+}
+
+/// Makes the rename durable. Directories cannot be opened for syncing on all
+/// platforms, so failures are ignored.
+fn sync_dir(dir: &Path) {
+    if let Ok(dir) = File::open(dir) {
+        let _ = dir.sync_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("gtf-storage-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn stamp_of_streamed_read_matches_written_content() {
+        let dir = temp_dir("stream");
+        let path = dir.join("font.gtf");
+        let content = "line one\nline two\n".repeat(2000);
+        let written = write_atomic(&path, content.as_bytes(), 0).unwrap();
+
+        let mut reader = StampingReader::open(&path).unwrap();
+        let mut first = String::new();
+        reader.read_line(&mut first).unwrap();
+        let streamed = reader.finish(&path).unwrap();
+
+        assert_eq!(streamed, written);
+        assert_eq!(FileStamp::read(&path).unwrap(), written);
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn content_hash_is_stable() {
+        // Reference values of 64-bit FNV-1a; a different value would turn every
+        // stamp stored in a recovery snapshot into a false conflict
+        let hash = |content: &[u8]| {
+            let mut hasher = Fnv1a::default();
+            hasher.write(content);
+            hasher.finish()
+        };
+        assert_eq!(hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(hash(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn detects_changed_content_but_not_touch() {
+        let dir = temp_dir("changed");
+        let path = dir.join("font.gtf");
+        let stamp = write_atomic(&path, b"original", 0).unwrap();
+        assert!(ensure_unchanged(&path, &stamp).is_ok());
+
+        // Same content rewritten (new mtime) is not a conflict
+        fs::write(&path, b"original").unwrap();
+        assert!(ensure_unchanged(&path, &stamp).is_ok());
+
+        fs::write(&path, b"modified").unwrap();
+        assert!(ensure_unchanged(&path, &stamp).is_err());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn keeps_rotating_backups() {
+        let dir = temp_dir("backups");
+        let path = dir.join("font.gtf");
+        for version in ["1", "2", "3", "4"] {
+            write_atomic(&path, version.as_bytes(), 2).unwrap();
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap(), "4");
+        assert_eq!(fs::read_to_string(backup_path(&path, 1)).unwrap(), "3");
+        assert_eq!(fs::read_to_string(backup_path(&path, 2)).unwrap(), "2");
+        assert!(!backup_path(&path, 3).exists());
+        let _ = fs::remove_dir_all(dir);
+    }
+}
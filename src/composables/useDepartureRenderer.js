@@ -6,6 +6,8 @@
  * (header, departure rows, footer, grid).
  */
 
+import { COLORS } from '../constants/design';
+
 /**
  * Create a renderer bound to the given reactive dependencies.
  *
@@ -24,6 +26,8 @@
  * @param {import('vue').ComputedRef} opts.processedDefaultPalette
  * @param {import('vue').ComputedRef} opts.glyphMap
  * @param {import('vue').ComputedRef<boolean>} opts.hasGlyphs
+ * @param {import('vue').ComputedRef<{background: string, foreground: string}>} [opts.roleColors]
+ *   Palette role colors (store `roleColors`); black / white when omitted
  */
 export function useDepartureRenderer(opts) {
   const {
@@ -41,23 +45,27 @@ export function useDepartureRenderer(opts) {
     processedDefaultPalette,
     glyphMap,
     hasGlyphs,
+    roleColors,
   } = opts;
 
+  const backgroundColor = () =>
+    (roleColors?.value?.background || COLORS.DEFAULT_BACKGROUND).toUpperCase();
+  const foregroundColor = () =>
+    (roleColors?.value?.foreground || COLORS.DEFAULT_FOREGROUND).toUpperCase();
+
   // ---------------------------------------------------------------
   // Palette helpers
   // ---------------------------------------------------------------
 
+  // Default palette overlaid with the glyph's own entries
   function getGlyphPalette(glyph) {
-    if (glyph.palette?.entries && Object.keys(glyph.palette.entries).length > 0) {
-      return glyph.palette.entries;
-    }
-    const defaults = {};
+    const entries = {};
     if (processedDefaultPalette.value) {
       for (const entry of processedDefaultPalette.value) {
-        defaults[entry.char] = entry.color;
+        entries[entry.char] = entry.color;
       }
     }
-    return defaults;
+    return Object.assign(entries, glyph.palette?.entries || {});
   }
 
   // ---------------------------------------------------------------
@@ -87,14 +95,13 @@ export function useDepartureRenderer(opts) {
         const ch = line[col];
         let color = palette[ch] || null;
 
-        // Apply color override ONLY if the pixel is effectively "white" (the tintable color in this context)
-        // or if it's a monochrome font scenario where we want to tint everything non-black.
-        // For multi-colored glyphs, we generally want to respect the original colors unless it's the "main" foreground.
-        if (colorOverride && color && color.toUpperCase() === '#FFFFFF') {
+        // Apply color override ONLY to the foreground (ink) color of the font.
+        // For multi-colored glyphs, the other colors keep their original values.
+        if (colorOverride && color && color.toUpperCase() === foregroundColor()) {
           color = colorOverride;
         }
 
-        if (color && color !== '#000000') {
+        if (color && color.toUpperCase() !== backgroundColor()) {
           ctx.fillStyle = color;
           ctx.fillRect(
             (x + col) * scale,
@@ -119,7 +126,7 @@ export function useDepartureRenderer(opts) {
       const line = glyph.bitmap[row];
       for (let col = 0; col < line.length && col < glyph.size.width; col++) {
         const color = palette[line[col]] || null;
-        if (color && color.toLowerCase() !== '#000000') {
+        if (color && color.toUpperCase() !== backgroundColor()) {
           ctx.fillStyle = '#000000';
           ctx.fillRect((x + col) * scale, (y + row) * scale, scale, scale);
         }
@@ -52,16 +52,13 @@ export function useFileOperations() {
 
             if (selectedPath && typeof selectedPath === 'string') {
                 console.log('Selected file:', selectedPath);
-                const document = await invoke('load_gtf_file', { path: selectedPath });
+                // Only the header and glyph summaries come over now, bitmaps
+                // are fetched when a glyph is shown (large fonts stay responsive)
+                const overview = await invoke('load_gtf_file_paged', { path: selectedPath });
 
-                console.log('Parsed document:', document);
+                console.log('Opened document:', overview.document_id, overview.glyph_count, 'glyphs');
 
-                store.setGtfData(
-                    document,
-                    selectedPath,
-                    'header',
-                    null
-                );
+                await store.setPagedDocument(overview);
 
                 return true; // Success
             }
@@ -81,6 +78,32 @@ export function useFileOperations() {
         }
     }
 
+    // Saves the current document. If the file was changed by another program
+    // since it was loaded, asks before overwriting. Returns false if cancelled.
+    async function saveDocument(path) {
+        const documentId = store.currentDocumentId.value;
+        try {
+            await invoke('save_gtf_file', { documentId, path });
+            return true;
+        } catch (err) {
+            if (!String(err).includes('was modified on disk')) throw err;
+
+            const shouldOverwrite = await ask(
+                'The file was changed by another program since it was opened. Overwrite it with your version?',
+                {
+                    title: 'File Changed on Disk',
+                    kind: 'warning',
+                    okLabel: 'Overwrite',
+                    cancelLabel: 'Cancel',
+                }
+            );
+            if (!shouldOverwrite) return false;
+
+            await invoke('save_gtf_file', { documentId, path, force: true });
+            return true;
+        }
+    }
+
     async function handleSaveFile() {
         if (!store.gtfData.value || !store.currentFilePath.value) {
             console.warn('Save attempted but no data or file path is available.');
@@ -92,9 +115,7 @@ export function useFileOperations() {
 
         try {
             // Rust už dokument má ve stavu, nepotřebujeme ho posílat
-            await invoke('save_gtf_file', {
-                path: store.currentFilePath.value,
-            });
+            if (!(await saveDocument(store.currentFilePath.value))) return false;
             console.log('File saved successfully (overwrite).');
             store.markSaved();
             return true;
@@ -128,9 +149,7 @@ export function useFileOperations() {
 
             if (savePath) {
                 console.log('Saving to file:', savePath);
-                await invoke('save_gtf_file', {
-                    path: savePath,
-                });
+                if (!(await saveDocument(savePath))) return false;
 
                 // Update store with new path
                 store.currentFilePath.value = savePath;
@@ -149,22 +168,90 @@ export function useFileOperations() {
     async function handleNewFile() {
         if (!(await checkUnsavedChanges())) return;
         try {
-            const document = await invoke('init_new_document');
-            store.setGtfData(document, null, 'header', null);
+            const { document_id, document } = await invoke('init_new_document');
+            store.setGtfData(document, null, 'header', null, document_id);
         } catch (err) {
             console.error('Failed to create new file in backend', err);
             error.value = `Error: ${err}`;
         }
     }
 
+    // Offers to restore documents autosaved by a session that ended unexpectedly
+    async function checkForRecovery() {
+        try {
+            const sessions = await invoke('list_recovery_sessions');
+            if (!sessions.length) return false;
+
+            // Offer the newest session, older ones stay on disk for the next start
+            const latest = sessions[0];
+
+            const names = latest.documents
+                .map(doc => doc.file_path || doc.font_name || 'Untitled')
+                .join('\n');
+            const shouldRestore = await ask(
+                `The previous session ended unexpectedly. Restore unsaved changes?\n\n${names}`,
+                {
+                    title: 'Recover Unsaved Changes',
+                    kind: 'warning',
+                    okLabel: 'Restore',
+                    cancelLabel: 'Discard',
+                }
+            );
+
+            if (!shouldRestore) {
+                await invoke('discard_recovery_session', { sessionId: latest.session_id });
+                return false;
+            }
+
+            const restored = await invoke('restore_recovery_session', { sessionId: latest.session_id });
+            await store.setPagedDocument(restored[restored.length - 1]);
+            store.markDirty();
+            return true;
+        } catch (err) {
+            console.error('Failed to restore previous session', err);
+            error.value = `Error restoring previous session: ${err}`;
+            return false;
+        }
+    }
+
+    // How glyph palettes are written: 'optimized' (only overrides of the
+    // default palette) or 'expanded' (every glyph lists its full palette)
+    const paletteOutput = ref('optimized');
+
+    async function loadSaveSettings() {
+        try {
+            const settings = await invoke('get_save_settings');
+            paletteOutput.value = settings.palette_output || 'optimized';
+        } catch (err) {
+            console.error('Failed to load save settings', err);
+        }
+    }
+
+    async function setPaletteOutput(value) {
+        try {
+            const settings = await invoke('get_save_settings');
+            await invoke('set_save_settings', {
+                settings: { ...settings, palette_output: value },
+            });
+            paletteOutput.value = value;
+        } catch (err) {
+            console.error('Failed to update save settings', err);
+            error.value = `Error updating save settings: ${err}`;
+        }
+    }
+
     return {
         isLoading,
         error,
         canSave,
         clearError,
+        paletteOutput,
+        loadSaveSettings,
+        setPaletteOutput,
         handleOpenFile,
         handleSaveFile,
         handleSaveFileAs,
-        handleNewFile
+        handleNewFile,
+        checkForRecovery
     };
 }
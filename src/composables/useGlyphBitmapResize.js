@@ -1,7 +1,12 @@
 import { watch, ref, nextTick } from 'vue';
 import { invoke } from '@tauri-apps/api/core';
+import { useGtfStore } from './useGtfStore';
+
+// Point of the bitmap that stays in place when the glyph size changes (shared)
+export const resizeAnchor = ref('top_left');
 
 export function useGlyphBitmapResize(glyphData, emit) {
+  const store = useGtfStore();
   const isUpdatingFromTextArea = ref(false);
 
   // Watch for changes in glyph size to resize the bitmap
@@ -43,6 +48,10 @@ export function useGlyphBitmapResize(glyphData, emit) {
             width: newWidth,
             height: newHeight,
           },
+          anchor: resizeAnchor.value,
+          // Lets the backend pad with the glyph's background character
+          documentId: store.currentDocumentId.value,
+          glyphName: glyphData.value.name,
         });
 
         // Check if bitmap actually changed before emitting
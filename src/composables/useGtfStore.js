@@ -1,5 +1,6 @@
 import { ref, computed } from 'vue';
 import { invoke } from '@tauri-apps/api/core';
+import { COLORS } from '../constants/design';
 
 // --- Type Definitions ---
 /**
@@ -13,12 +14,25 @@ import { invoke } from '@tauri-apps/api/core';
  * @property {string|null} validation_warnings
  */
 
+/**
+ * Glyph without bitmap and palette, as listed by `get_glyph_summaries`.
+ * @typedef {Object} GlyphSummary
+ * @property {string} name
+ * @property {string|null} unicode
+ * @property {string|null} char_repr
+ * @property {Object|null} size
+ * @property {number} warning_count
+ */
+
 /**
  * @typedef {Object} GtfData
  * @property {Object} header
- * @property {Glyph[]} glyphs
+ * @property {Array<Glyph|GlyphSummary>} glyphs Summaries for paged documents
  */
 
+// Glyph summaries fetched per request when listing a paged document
+const SUMMARY_PAGE_SIZE = 500;
+
 // Initial empty state structure
 const initialGtfData = () => (/** @type {GtfData} */ ({ header: {}, glyphs: [] }));
 
@@ -26,19 +40,51 @@ const initialGtfData = () => (/** @type {GtfData} */ ({ header: {}, glyphs: [] }
 // Define state refs *outside* the function to make them shared
 const gtfData = ref(initialGtfData());
 const currentFilePath = /** @type {import('vue').Ref<string|null>} */ (ref(null));
+// Id of the backend workspace document this store is showing
+const currentDocumentId = /** @type {import('vue').Ref<number|null>} */ (ref(null));
 const selectedGlyphName = /** @type {import('vue').Ref<string|null>} */ (ref(null));
 const currentView = /** @type {import('vue').Ref<string|null>} */ (ref(null));
 const currentError = /** @type {import('vue').Ref<string|null>} */ (ref(null));
 const isDirty = ref(false); // Track unsaved changes
+// Full glyphs (bitmap + palette) of a paged document, fetched on demand by name.
+// Documents loaded whole keep their full glyphs directly in `gtfData.glyphs`.
+const loadedGlyphs = ref({});
+// Names requested but not fetched yet; collected so one `get_glyphs` call serves
+// every preview mounted in the same tick
+const pendingGlyphNames = new Set();
+let pendingGlyphFetch = null;
+
+/**
+ * Full glyph by name, or null while it is not loaded.
+ * @param {string} name
+ * @returns {Glyph|null}
+ */
+function glyphData(name) {
+  if (!name) return null;
+  if (loadedGlyphs.value[name]) return loadedGlyphs.value[name];
+  const listed = gtfData.value?.glyphs?.find((g) => g.name === name);
+  return listed?.bitmap ? listed : null;
+}
 
 // --- Shared Computed Property ---
 const selectedGlyphData = computed(() => {
   if (!gtfData.value || !selectedGlyphName.value) {
     return null;
   }
-  return (
-    gtfData.value.glyphs.find((g) => g.name === selectedGlyphName.value) || null
-  );
+  return glyphData(selectedGlyphName.value);
+});
+
+// Colors of the header palette roles (BACKGROUND / FOREGROUND). Renderers skip
+// background pixels and tint foreground pixels; without roles the classic
+// black background and white ink are assumed.
+const roleColors = computed(() => {
+  const header = gtfData.value?.header;
+  const entries = header?.default_palette?.entries || {};
+  const roles = header?.roles || {};
+  return {
+    background: entries[roles.background] || COLORS.DEFAULT_BACKGROUND,
+    foreground: entries[roles.foreground] || COLORS.DEFAULT_FOREGROUND,
+  };
 });
 
 // --- Exported Composable Function ---
@@ -54,21 +100,26 @@ export function useGtfStore() {
    * @param {string|null} filePath
    * @param {string} view
    * @param {string|null} glyphName
+   * @param {number|null} documentId
    */
   function setGtfData(
     newData,
     filePath = null,
     view = 'header',
-    glyphName = null
+    glyphName = null,
+    documentId = null
   ) {
     // Ensure we handle potential null newData
     gtfData.value = newData || initialGtfData();
+    loadedGlyphs.value = {};
     currentFilePath.value = filePath;
+    currentDocumentId.value = documentId;
     currentView.value = view;
     selectedGlyphName.value = glyphName;
     currentError.value = null; // Clear errors on successful load/new
     isDirty.value = false; // Reset dirty flag on load/new
     console.log('GTF Store: Data updated (Singleton)', {
+      documentId,
       filePath,
       view,
       glyphName,
@@ -76,6 +127,90 @@ export function useGtfStore() {
     });
   }
 
+  /**
+   * Lists every glyph of a backend document as summaries, page by page.
+   * @param {number} documentId
+   * @returns {Promise<GlyphSummary[]>}
+   */
+  async function fetchGlyphSummaries(documentId) {
+    const summaries = [];
+    for (;;) {
+      const page = await invoke('get_glyph_summaries', {
+        documentId,
+        offset: summaries.length,
+        limit: SUMMARY_PAGE_SIZE,
+      });
+      summaries.push(...page.glyphs);
+      if (!page.glyphs.length || summaries.length >= page.total) return summaries;
+    }
+  }
+
+  /**
+   * Shows a document opened with `load_gtf_file_paged`: the header and glyph
+   * summaries are loaded now, bitmaps only when a glyph is shown.
+   * @param {{ document_id: number, header: Object, file_path: string|null }} overview
+   */
+  async function setPagedDocument(overview) {
+    const glyphs = await fetchGlyphSummaries(overview.document_id);
+    setGtfData(
+      { header: overview.header, glyphs },
+      overview.file_path,
+      'header',
+      null,
+      overview.document_id
+    );
+  }
+
+  /**
+   * Makes sure the full glyphs are loaded. Requests from the same tick are
+   * sent to the backend as one `get_glyphs` call.
+   * @param {string[]} names
+   * @returns {Promise<void>}
+   */
+  function loadGlyphs(names) {
+    const missing = names.filter((name) => !glyphData(name));
+    if (!missing.length || currentDocumentId.value === null) {
+      return pendingGlyphFetch || Promise.resolve();
+    }
+    missing.forEach((name) => pendingGlyphNames.add(name));
+    if (!pendingGlyphFetch) {
+      const documentId = currentDocumentId.value;
+      pendingGlyphFetch = Promise.resolve().then(async () => {
+        const glyphNames = [...pendingGlyphNames];
+        pendingGlyphNames.clear();
+        pendingGlyphFetch = null;
+        try {
+          const glyphs = await invoke('get_glyphs', { documentId, glyphNames });
+          // Another document may have been opened in the meantime; unknown
+          // names come back empty and must not retrigger the previews
+          if (documentId !== currentDocumentId.value || !glyphs.length) return;
+          const loaded = { ...loadedGlyphs.value };
+          glyphs.forEach((glyph) => {
+            loaded[glyph.name] = glyph;
+          });
+          loadedGlyphs.value = loaded;
+        } catch (err) {
+          console.error('GTF Store: Loading glyphs failed', err);
+        }
+      });
+    }
+    return pendingGlyphFetch;
+  }
+
+  /**
+   * Puts an updated full glyph into the list and the cache.
+   * @param {string} oldName Name before the update
+   * @param {Glyph} glyph
+   */
+  function storeGlyph(oldName, glyph) {
+    const index = gtfData.value.glyphs.findIndex((g) => g.name === oldName);
+    if (index !== -1) gtfData.value.glyphs[index] = glyph;
+    const loaded = { ...loadedGlyphs.value };
+    delete loaded[oldName];
+    loaded[glyph.name] = glyph;
+    loadedGlyphs.value = loaded;
+  }
+
   function markSaved() {
     isDirty.value = false;
     console.log('GTF Store: Marked as saved (Singleton)');
@@ -97,6 +232,7 @@ export function useGtfStore() {
   function selectGlyph(glyphName) {
     selectedGlyphName.value = glyphName;
     currentView.value = 'glyph'; // Switch view to glyph editor
+    loadGlyphs([glyphName]);
     console.log('GTF Store: Selected glyph (Singleton)', glyphName);
   }
 
@@ -107,28 +243,61 @@ export function useGtfStore() {
   }
 
   /**
-   * Syncs local state with backend.
+   * Syncs local state with backend: header and glyph summaries, plus fresh
+   * copies of the glyphs loaded so far (without the whole document).
    */
   async function refreshFromBackend() {
     try {
-      const doc = await invoke('get_current_document');
-      const info = await invoke('get_state_info');
-      if (doc) {
-        gtfData.value = doc;
-        currentFilePath.value = info.file_path;
-        isDirty.value = info.is_dirty;
-      }
+      if (currentDocumentId.value === null) return;
+      const documentId = currentDocumentId.value;
+      const overview = await invoke('get_document_overview', { documentId });
+      const summaries = await fetchGlyphSummaries(documentId);
+      const shownNames = new Set(Object.keys(loadedGlyphs.value));
+      gtfData.value.glyphs.forEach((g) => g.bitmap && shownNames.add(g.name));
+      if (selectedGlyphName.value) shownNames.add(selectedGlyphName.value);
+      const glyphs = await invoke('get_glyphs', {
+        documentId,
+        glyphNames: [...shownNames],
+      });
+      const info = await invoke('get_state_info', { documentId });
+      if (documentId !== currentDocumentId.value) return;
+
+      gtfData.value = { header: overview.header, glyphs: summaries };
+      loadedGlyphs.value = Object.fromEntries(glyphs.map((g) => [g.name, g]));
+      currentFilePath.value = info.file_path;
+      isDirty.value = info.is_dirty;
     } catch (err) {
       console.error('GTF Store: Refresh failed', err);
     }
   }
 
+  /**
+   * Undo/redo are handled by the Rust history; afterwards we resync local state.
+   */
+  async function undo() {
+    try {
+      await invoke('undo', { documentId: currentDocumentId.value });
+      await refreshFromBackend();
+    } catch (err) {
+      console.error('GTF Store: Undo failed', err);
+    }
+  }
+
+  async function redo() {
+    try {
+      await invoke('redo', { documentId: currentDocumentId.value });
+      await refreshFromBackend();
+    } catch (err) {
+      console.error('GTF Store: Redo failed', err);
+    }
+  }
+
   async function addGlyph() {
     if (!gtfData.value) return;
     console.log('GTF Store: Adding new glyph (Singleton)...');
 
     try {
-      const newGlyph = await invoke('add_empty_glyph');
+      const newGlyph = await invoke('add_empty_glyph', { documentId: currentDocumentId.value });
       gtfData.value.glyphs.push(newGlyph);
       markDirty();
       selectGlyph(newGlyph.name);
@@ -150,10 +319,12 @@ export function useGtfStore() {
     if (indexToRemove !== -1) {
       // Lokálně
       gtfData.value.glyphs.splice(indexToRemove, 1);
+      const { [nameToRemove]: _removed, ...loaded } = loadedGlyphs.value;
+      loadedGlyphs.value = loaded;
 
       // Backend
       try {
-        await invoke('remove_glyph', { glyphName: nameToRemove });
+        await invoke('remove_glyph', { documentId: currentDocumentId.value, glyphName: nameToRemove });
         markDirty();
         selectHeader();
       } catch (err) {
@@ -172,9 +343,9 @@ export function useGtfStore() {
 
     try {
       if (field === 'default_palette') {
-        await invoke('update_default_palette', { new_palette: value });
+        await invoke('update_default_palette', { documentId: currentDocumentId.value, new_palette: value });
       } else {
-        await invoke('update_header', { new_header: gtfData.value.header });
+        await invoke('update_header', { documentId: currentDocumentId.value, new_header: gtfData.value.header });
       }
       markDirty();
     } catch (err) {
@@ -185,36 +356,35 @@ export function useGtfStore() {
   async function updateGlyphData({ field, value, action }) {
     if (!gtfData.value || !selectedGlyphName.value) return;
 
-    const glyphIndex = gtfData.value.glyphs.findIndex(
-      (g) => g.name === selectedGlyphName.value
-    );
-    if (glyphIndex === -1) return;
-
     const oldName = selectedGlyphName.value;
+    // Edits need the full glyph; in a paged document it may still be loading
+    await loadGlyphs([oldName]);
+    const glyph = glyphData(oldName);
+    if (!glyph) return;
 
     try {
       if (action === 'use_default_palette') {
-        const updated = await invoke('apply_default_palette_to_glyph', { glyph_name: oldName });
-        gtfData.value.glyphs[glyphIndex] = updated;
+        const updated = await invoke('apply_default_palette_to_glyph', { documentId: currentDocumentId.value, glyph_name: oldName });
+        storeGlyph(oldName, updated);
         markDirty();
         return;
       }
 
       if (field === 'bitmap' && action === 'pixel') {
         const { row, col, char } = value;
-        const chars = [...gtfData.value.glyphs[glyphIndex].bitmap[row]];
+        const chars = [...glyph.bitmap[row]];
         chars[col] = char;
-        gtfData.value.glyphs[glyphIndex].bitmap[row] = chars.join('');
+        glyph.bitmap[row] = chars.join('');
 
-        await invoke('update_glyph_pixel', { glyph_name: oldName, row, col, new_char: char });
+        await invoke('update_glyph_pixel', { documentId: currentDocumentId.value, glyph_name: oldName, row, col, new_char: char });
         markDirty();
         return;
       }
 
       // Atomic field updates for strings (name, unicode, char_repr)
       if (['name', 'unicode', 'char_repr'].includes(field)) {
-        const updated = await invoke('update_glyph_field', { glyph_name: oldName, field, value });
-        gtfData.value.glyphs[glyphIndex] = updated;
+        const updated = await invoke('update_glyph_field', { documentId: currentDocumentId.value, glyph_name: oldName, field, value });
+        storeGlyph(oldName, updated);
         if (field === 'name') selectedGlyphName.value = value;
 
         // we modify the glyph in place, so we need to mark dirty - NEED SAVING
@@ -224,11 +394,13 @@ export function useGtfStore() {
       }
 
       // General fallback (update local and sync whole glyph)
-      gtfData.value.glyphs[glyphIndex][field] = value;
+      glyph[field] = value;
+      storeGlyph(oldName, glyph);
 
       await invoke('update_glyph', {
+        documentId: currentDocumentId.value,
         glyph_name: oldName,
-        updated_glyph: JSON.parse(JSON.stringify(gtfData.value.glyphs[glyphIndex]))
+        updated_glyph: JSON.parse(JSON.stringify(glyph))
       });
 
       // we modify the glyph in place, so we need to mark dirty - NEED SAVING
@@ -244,7 +416,7 @@ export function useGtfStore() {
     console.log(`GTF Store: Adding glyph for char: '${char}' (Singleton)`);
 
     try {
-      const result = await invoke('add_glyph_for_char', { char });
+      const result = await invoke('add_glyph_for_char', { documentId: currentDocumentId.value, char });
 
       // Update local state if it's a new glyph
       if (!gtfData.value.glyphs.some(g => g.name === result.name)) {
@@ -258,17 +430,112 @@ export function useGtfStore() {
     }
   }
 
+  /**
+   * Applies a geometric transform (flip, rotate, shift, transpose) in the backend.
+   * @param {Object} transform e.g. { kind: 'rotate', degrees: 90 }
+   * @param {string[]|null} glyphNames Glyphs to transform, null for the whole font
+   */
+  async function transformGlyphs(transform, glyphNames = null) {
+    if (currentDocumentId.value === null) return;
+    try {
+      await invoke('transform_glyphs', {
+        documentId: currentDocumentId.value,
+        transform,
+        glyphNames,
+      });
+      await refreshFromBackend();
+    } catch (err) {
+      console.error('GTF Store: Transform failed', err);
+    }
+  }
+
+  /**
+   * Crops glyphs to their ink bounding box; the removed border goes to OFFSET
+   * and the original cell size to CELL, so the glyphs render as before.
+   * @param {string[]|null} glyphNames Glyphs to trim, null for the whole font
+   */
+  async function trimGlyphs(glyphNames = null) {
+    if (currentDocumentId.value === null) return;
+    try {
+      await invoke('trim_glyphs', {
+        documentId: currentDocumentId.value,
+        glyphNames,
+      });
+      await refreshFromBackend();
+    } catch (err) {
+      console.error('GTF Store: Trim failed', err);
+    }
+  }
+
+  /**
+   * Ink bounding boxes of all glyphs (null `ink` for blank glyphs).
+   * @returns {Promise<Array>} `{ name, size, ink, trimmable }` per glyph
+   */
+  async function getGlyphBounds() {
+    if (currentDocumentId.value === null) return [];
+    return invoke('get_glyph_bounds', { documentId: currentDocumentId.value });
+  }
+
+  /**
+   * Stores the background / foreground palette characters in the header.
+   * @param {{ background: string|null, foreground: string|null }} roles
+   */
+  async function setPaletteRoles(roles) {
+    if (currentDocumentId.value === null) return;
+    try {
+      await invoke('set_palette_roles', {
+        documentId: currentDocumentId.value,
+        roles,
+      });
+      await refreshFromBackend();
+    } catch (err) {
+      console.error('GTF Store: Setting palette roles failed', err);
+    }
+  }
+
+  /**
+   * Suggests palette roles from pixel usage without storing them.
+   * @returns {Promise<{ background: string|null, foreground: string|null }|null>}
+   */
+  async function detectPaletteRoles() {
+    if (currentDocumentId.value === null) return null;
+    return invoke('detect_palette_roles', { documentId: currentDocumentId.value });
+  }
+
+  /**
+   * Changes the height of every glyph, keeping baselines aligned.
+   * @param {number} height New font height
+   * @param {string} anchor 'top', 'center' or 'bottom' (rows added/removed on the other side)
+   * @returns {Promise<Object|null>} Backend report (resized count, clipped glyphs)
+   */
+  async function changeFontHeight(height, anchor = 'bottom') {
+    if (currentDocumentId.value === null) return null;
+    const change = await invoke('change_font_height', {
+      documentId: currentDocumentId.value,
+      height,
+      anchor,
+    });
+    await refreshFromBackend();
+    return change;
+  }
+
   // Return the shared reactive state and methods
   return {
     gtfData,
     currentFilePath,
+    currentDocumentId,
     selectedGlyphName,
     currentView,
     currentError,
     isDirty,
     selectedGlyphData,
+    roleColors,
+    loadedGlyphs,
+    glyphData,
+    loadGlyphs,
     clearError,
     setGtfData,
+    setPagedDocument,
     markSaved,
     newFile,
     selectGlyph,
@@ -278,6 +545,14 @@ export function useGtfStore() {
     updateHeaderData,
     updateGlyphData,
     refreshFromBackend,
+    undo,
+    redo,
     addGlyphForChar,
+    transformGlyphs,
+    trimGlyphs,
+    getGlyphBounds,
+    changeFontHeight,
+    setPaletteRoles,
+    detectPaletteRoles,
   };
 }
@@ -1,8 +1,10 @@
 import { onMounted, onUnmounted, ref } from 'vue';
 import { useFileOperations } from './useFileOperations';
+import { useGtfStore } from './useGtfStore';
 
 export function useKeyboardShortcuts() {
     const { handleOpenFile, handleSaveFile, handleSaveFileAs, handleNewFile } = useFileOperations();
+    const { undo, redo } = useGtfStore();
     const isOverlayVisible = ref(false);
 
     // Track CMD key state for overlay
@@ -44,6 +46,18 @@ export function useKeyboardShortcuts() {
                     event.preventDefault();
                     handleNewFile();
                     break;
+                case 'z':
+                    event.preventDefault();
+                    if (event.shiftKey) {
+                        redo();
+                    } else {
+                        undo();
+                    }
+                    break;
+                case 'y':
+                    event.preventDefault();
+                    redo();
+                    break;
                 case 'i':
                     event.preventDefault();
                     // We need to trigger import dialog. 
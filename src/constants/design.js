@@ -128,6 +128,7 @@ export const GTF_EDITOR = {
     THEME: 'gtf-editor-theme',
     RECENT_FILES: 'gtf-editor-recent-files',
     PREFERENCES: 'gtf-editor-preferences',
+    CHARACTER_SETS: 'gtf-editor-character-sets',
   },
 };
 
@@ -10,14 +10,14 @@ import { invoke } from '@tauri-apps/api/core';
 /**
  * Export a font document to a file using the backend exporter for the given format.
  *
+ * @param {number} documentId - Backend workspace id of the document to export
  * @param {string} filePath - Absolute path for the output file
  * @param {string} format - Export format (e.g. 'gtf', 'dat', 'bfnt', 'bmp')
- * @param {import('../../types/gtf').GtfDocument} document - The document to export
  * @returns {Promise<void>}
  */
-export async function exportFontFile(filePath, format, document)
+export async function exportFontFile(documentId, filePath, format)
 {
-  return await invoke('export_font_file', { path: filePath, format, document });
+  return await invoke('export_font_file', { documentId, path: filePath, format });
 }
 
 /**
@@ -13,7 +13,8 @@ import { invoke } from '@tauri-apps/api/core';
  *
  * @param {string} filePath - Absolute path to the font file
  * @param {string} [format] - Format override (e.g. 'gtf', 'dat', 'fnt', 'bfnt')
- * @returns {Promise<import('../../types/gtf').GtfDocument>}
+ * @returns {Promise<{ document_id: number, header: Object, glyph_count: number, file_path: string|null }>}
+ *   Overview of the opened document; pass it to `setPagedDocument`
  */
 export async function importFontFile(filePath, format = null)
 {